@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use linux_audit_parser::*;
+
+fn clone(c: &mut Criterion) {
+    let msg = parse(include_bytes!("../src/testdata/line-syscall.txt"), false).unwrap();
+    c.bench_function("clone body (syscall)", |b| {
+        b.iter(|| black_box(&msg.body).clone())
+    });
+    c.bench_function("copy body (syscall)", |b| {
+        b.iter(|| {
+            let mut body = Body::new();
+            for (k, v) in black_box(&msg.body) {
+                body.push((k.clone(), v.clone()));
+            }
+            body
+        })
+    });
+    let msg = parse(include_bytes!("../src/testdata/line-user-acct.txt"), false).unwrap();
+    c.bench_function("clone body (user-acct)", |b| {
+        b.iter(|| black_box(&msg.body).clone())
+    });
+}
+
+fn lookup(c: &mut Criterion) {
+    let msg = parse(include_bytes!("../src/testdata/line-syscall.txt"), false).unwrap();
+    let keys = [
+        "arch", "syscall", "success", "exit", "pid", "ppid", "auid", "uid", "ses", "comm", "exe",
+        "key",
+    ];
+    c.bench_function("get (syscall)", |b| {
+        b.iter(|| {
+            let body = black_box(&msg.body);
+            keys.iter().filter_map(|k| body.get(k)).count()
+        })
+    });
+    c.bench_function("index + get (syscall)", |b| {
+        b.iter(|| {
+            let index = black_box(&msg.body).index();
+            keys.iter().filter_map(|k| index.get(k)).count()
+        })
+    });
+}
+
+fn corpus(c: &mut Criterion) {
+    let lines: Vec<Vec<u8>> =
+        std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src/testdata"))
+            .unwrap()
+            .map(|e| std::fs::read(e.unwrap().path()).unwrap())
+            .collect();
+    c.bench_function("parse testdata corpus", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(parse(black_box(line), false).ok());
+            }
+        })
+    });
+    let eoe = include_bytes!("../src/testdata/line-eoe.txt");
+    c.bench_function("parse EOE", |b| {
+        b.iter(|| parse(black_box(eoe), false).unwrap())
+    });
+}
+
+fn intern(c: &mut Criterion) {
+    let lines: Vec<Vec<u8>> =
+        std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src/testdata"))
+            .unwrap()
+            .map(|e| std::fs::read(e.unwrap().path()).unwrap())
+            .collect();
+    let plain = Parser::default();
+    let interned = Parser::builder().intern(COMMON_VALUES).build();
+    for (name, p) in [("plain", &plain), ("interned", &interned)] {
+        let bytes: usize = lines
+            .iter()
+            .filter_map(|l| p.parse(l).ok())
+            .map(|m| m.stats().arena_bytes)
+            .sum();
+        eprintln!("arena bytes for testdata corpus ({name}): {bytes}");
+    }
+    c.bench_function("parse testdata corpus (interned)", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(interned.parse(black_box(line)).ok());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, clone, lookup, corpus, intern);
+criterion_main!(benches);
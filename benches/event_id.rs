@@ -0,0 +1,80 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use linux_audit_parser::*;
+
+/// Event IDs as written by a busy system: a few hundred events per
+/// millisecond, with consecutive sequence numbers.
+fn ids(n: u32) -> Vec<EventID> {
+    (0..n)
+        .map(|i| EventID {
+            timestamp: 1_615_114_232_375 + u64::from(i / 300),
+            sequence: 15558 + i,
+        })
+        .collect()
+}
+
+/// The fields hashed separately, as with `#[derive(Hash)]`
+#[derive(Clone, PartialEq, Eq)]
+struct DerivedHash(EventID);
+
+impl Hash for DerivedHash {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.timestamp.hash(state);
+        self.0.sequence.hash(state);
+    }
+}
+
+fn insert_lookup<K: Hash + Eq + Clone, S: BuildHasher + Default>(keys: &[K]) -> usize {
+    let mut m: HashMap<K, (), S> = HashMap::default();
+    for k in keys {
+        m.insert(k.clone(), ());
+    }
+    keys.iter().filter(|k| m.contains_key(k)).count()
+}
+
+fn hashing(c: &mut Criterion) {
+    let ids = ids(1_000_000);
+    let derived_ids: Vec<_> = ids.iter().copied().map(DerivedHash).collect();
+    let node_ids: Vec<_> = ids
+        .iter()
+        .map(|id| (Some(b"host1".to_vec()), *id))
+        .collect();
+    let mut group = c.benchmark_group("EventID map, 1M entries");
+    group.sample_size(10);
+    group.bench_function("derived Hash, RandomState", |b| {
+        b.iter(|| insert_lookup::<_, RandomState>(black_box(&derived_ids)))
+    });
+    group.bench_function("u128 Hash, RandomState", |b| {
+        b.iter(|| insert_lookup::<_, RandomState>(black_box(&ids)))
+    });
+    group.bench_function("u128 Hash, EventIDBuildHasher", |b| {
+        b.iter(|| insert_lookup::<_, EventIDBuildHasher>(black_box(&ids)))
+    });
+    group.bench_function("(node, EventID), EventIDBuildHasher", |b| {
+        b.iter(|| insert_lookup::<_, EventIDBuildHasher>(black_box(&node_ids)))
+    });
+    group.finish();
+
+    // Distribution: distinct hashes in the low 20 bits, which select
+    // the bucket in a map of this size
+    let buckets = |s: &dyn Fn(&EventID) -> u64| {
+        ids.iter()
+            .map(|id| s(id) & 0xfffff)
+            .collect::<HashSet<_>>()
+            .len()
+    };
+    let fast = EventIDBuildHasher::default();
+    let derived = RandomState::new();
+    eprintln!(
+        "buckets used by 1M IDs: EventIDBuildHasher {}, derived {}",
+        buckets(&|id| fast.hash_one(id)),
+        buckets(&|id| derived.hash_one(DerivedHash(*id))),
+    );
+}
+
+criterion_group!(benches, hashing);
+criterion_main!(benches);
@@ -4,6 +4,7 @@ use std::io::prelude::*;
 use std::io::BufReader;
 use std::iter::FromIterator;
 use std::path::Path;
+use std::process::Command;
 use std::string::String;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -42,6 +43,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(|fields| (fields[0].clone(), fields[1].clone()))
         .collect();
 
+    let spec_version = spec_version("src/audit-specs");
+    let mut spec_digest: u64 = 0xcbf29ce484222325;
+    for file in [msgtype_def_file, field_def_file] {
+        for b in fs::read(file)? {
+            spec_digest = (spec_digest ^ u64::from(b)).wrapping_mul(0x100000001b3);
+        }
+    }
+
     let mut template = Vec::new();
     fs::File::open("src/const.rs.in")?.read_to_end(&mut template)?;
     let template = String::from_utf8(template)?;
@@ -71,6 +80,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }),
             ),
         )
+        .replace(
+            "/* @FIELD_NAMES@ */",
+            &String::from_iter(fields.iter().map(|(name, _)| format!(r#""{name}", "#))),
+        )
+        .replace("/* @SPEC_VERSION@ */", &format!("{spec_version:?}"))
+        .replace("/* @SPEC_DIGEST@ */", &format!(r#""{spec_digest:016x}""#))
+        .replace("/* @SPEC_MESSAGE_TYPES@ */", &constants.len().to_string())
+        .replace("/* @SPEC_FIELDS@ */", &fields.len().to_string())
         .into_bytes();
     fs::write(const_file, buf)?;
 
@@ -88,10 +105,57 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     fs::write(msgtype_file, buf)?;
 
+    if env::var_os("CARGO_FEATURE_SYSCALLS").is_some() {
+        let mut buf = String::new();
+        for (name, file) in [
+            ("X86_64", "src/syscalls/x86_64.tbl"),
+            ("I386", "src/syscalls/i386.tbl"),
+            ("GENERIC", "src/syscalls/generic.tbl"),
+        ] {
+            buf += &format!("pub(crate) const {name}: &[(i64, &str)] = &[");
+            for line in BufReader::new(fs::File::open(file)?).lines() {
+                let line = line?;
+                if line.starts_with('#') || line.trim().is_empty() {
+                    continue;
+                }
+                let mut fields = line.split_whitespace();
+                let (Some(nr), Some(syscall)) = (fields.next(), fields.next()) else {
+                    return Err(format!("{file}: invalid line: {line}").into());
+                };
+                let nr: i64 = nr.parse()?;
+                buf += &format!(r#"({nr}, "{syscall}"), "#);
+            }
+            buf += "];\n";
+        }
+        fs::write(Path::new(&out_dir).join("syscall_tables.rs"), buf)?;
+    }
+
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=const.rs.in");
     println!("cargo:rerun-if-changed={msgtype_def_file}");
     println!("cargo:rerun-if-changed={field_def_file}");
+    println!("cargo:rerun-if-changed=src/audit-specs");
+    println!("cargo:rerun-if-changed=src/syscalls");
 
     Ok(())
 }
+
+/// Determines the version of the audit-specs tables: the commit ID if
+/// `dir` is a git checkout (e.g. a submodule), the contents of a
+/// `VERSION` file, or "unknown" for plain copies.
+fn spec_version(dir: &str) -> String {
+    if Path::new(dir).join(".git").exists() {
+        if let Ok(out) = Command::new("git")
+            .args(["-C", dir, "rev-parse", "HEAD"])
+            .output()
+        {
+            if out.status.success() {
+                return String::from_utf8_lossy(&out.stdout).trim().to_string();
+            }
+        }
+    }
+    match fs::read_to_string(Path::new(dir).join("VERSION")) {
+        Ok(s) if !s.trim().is_empty() => s.trim().to_string(),
+        _ => "unknown".into(),
+    }
+}
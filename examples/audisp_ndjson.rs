@@ -0,0 +1,55 @@
+//! An _audisp_ plugin that writes events as JSON lines to the file
+//! given as argument, or to standard output.
+//!
+//! ```text
+//! # /etc/audit/plugins.d/ndjson.conf
+//! active = yes
+//! direction = out
+//! path = /usr/local/sbin/audisp_ndjson
+//! type = always
+//! args = /var/log/audit/audit.ndjson
+//! format = string
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use linux_audit_parser::audisp::{Config, Plugin, Runtime};
+use linux_audit_parser::{Event, NdjsonWriter};
+
+#[derive(Default)]
+struct Ndjson {
+    out: Option<NdjsonWriter<BufWriter<Box<dyn Write>>>>,
+}
+
+impl Plugin for Ndjson {
+    fn configure(&mut self, config: &Config) -> io::Result<()> {
+        if let Some(out) = &mut self.out {
+            out.flush()?;
+        }
+        let w: Box<dyn Write> = match config.args.first() {
+            Some(path) => Box::new(File::options().create(true).append(true).open(path)?),
+            None => Box::new(io::stdout()),
+        };
+        self.out = Some(NdjsonWriter::new(BufWriter::new(w)).auto_flush(true));
+        Ok(())
+    }
+
+    fn event(&mut self, ev: Event<'static>) -> io::Result<()> {
+        match &mut self.out {
+            Some(out) => out.write_event(&ev),
+            None => Ok(()),
+        }
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        match &mut self.out {
+            Some(out) => out.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    Runtime::new().run(Ndjson::default())
+}
@@ -0,0 +1,299 @@
+use crate::*;
+
+/// A cluster of related [`Event`]s, see [`ActivityGrouper`]
+#[derive(Debug, Clone)]
+pub struct Activity<'a> {
+    pub node: Option<Vec<u8>>,
+    /// Session ID (`ses`), if set
+    pub ses: Option<SessionId>,
+    /// Login user ID (`auid`), if set
+    pub auid: Option<u32>,
+    /// Process IDs (`pid`) seen in the events, sorted
+    pub pids: Vec<i64>,
+    /// Events in the order in which they were added
+    pub events: Vec<Event<'a>>,
+}
+
+impl Activity<'_> {
+    /// Returns the identifier of the first event.
+    pub fn start(&self) -> EventID {
+        self.events.first().map(|ev| ev.id).unwrap_or_default()
+    }
+
+    /// Returns the identifier of the last event.
+    pub fn end(&self) -> EventID {
+        self.events.last().map(|ev| ev.id).unwrap_or_default()
+    }
+
+    /// Returns the time between the first and the last event in
+    /// milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.end().timestamp.saturating_sub(self.start().timestamp)
+    }
+}
+
+/// Decides when an [`Activity`] ends, see [`ActivityGrouper`]
+pub trait WindowPolicy {
+    /// True if `ev`, which is related to `activity`, is to be added
+    /// to it rather than starting a new one
+    fn continues(&self, activity: &Activity, ev: &Event) -> bool;
+    /// True if no more events can be added to `activity` once an
+    /// event with timestamp `now` (milliseconds) has been seen
+    fn expired(&self, activity: &Activity, now: u64) -> bool;
+}
+
+/// Ends activities after a period without events, and optionally
+/// after a maximum duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleWindow {
+    idle_ms: u64,
+    max_ms: Option<u64>,
+}
+
+impl IdleWindow {
+    /// Constructs a policy that ends activities after `idle_ms`
+    /// milliseconds without events.
+    pub fn new(idle_ms: u64) -> Self {
+        IdleWindow {
+            idle_ms,
+            max_ms: None,
+        }
+    }
+
+    /// Sets the maximum time between the first and the last event
+    /// of an activity.
+    pub fn max_duration(mut self, max_ms: u64) -> Self {
+        self.max_ms = Some(max_ms);
+        self
+    }
+
+    fn fits(&self, activity: &Activity, ts: u64) -> bool {
+        ts.saturating_sub(activity.end().timestamp) <= self.idle_ms
+            && !matches!(self.max_ms,
+                Some(max) if ts.saturating_sub(activity.start().timestamp) > max)
+    }
+}
+
+/// Ends activities after five minutes without events
+impl Default for IdleWindow {
+    fn default() -> Self {
+        Self::new(5 * 60 * 1000)
+    }
+}
+
+impl WindowPolicy for IdleWindow {
+    fn continues(&self, activity: &Activity, ev: &Event) -> bool {
+        self.fits(activity, ev.id.timestamp)
+    }
+
+    fn expired(&self, activity: &Activity, now: u64) -> bool {
+        !self.fits(activity, now)
+    }
+}
+
+/// The fields used to relate an event to an activity
+struct Subject {
+    ses: Option<SessionId>,
+    auid: Option<u32>,
+    pid: Option<i64>,
+    ppid: Option<i64>,
+}
+
+impl Subject {
+    fn of(ev: &Event) -> Self {
+        let num = |k: &str| match ev.find_field(k) {
+            Some(Value::Number(Number::Dec(n))) => Some(*n),
+            _ => None,
+        };
+        let id32 = |k: &str| {
+            num(k)
+                .and_then(|n| u32::try_from(n).ok())
+                .filter(|n| *n != UNSET_ID)
+        };
+        Subject {
+            ses: ev
+                .find_field("ses")
+                .and_then(SessionId::from_value)
+                .filter(SessionId::is_set),
+            auid: id32("auid"),
+            pid: num("pid"),
+            ppid: num("ppid"),
+        }
+    }
+
+    /// Events that belong to a login session are related by `ses`
+    /// (and `auid`), others by process lineage: the event's process
+    /// or its parent must have been seen in the activity.
+    fn relates_to(&self, node: &Option<Vec<u8>>, activity: &Activity) -> bool {
+        if *node != activity.node {
+            return false;
+        }
+        match self.ses {
+            Some(ses) => {
+                activity.ses == Some(ses)
+                    && (self.auid.is_none()
+                        || activity.auid.is_none()
+                        || self.auid == activity.auid)
+            }
+            None => {
+                activity.ses.is_none()
+                    && [self.pid, self.ppid]
+                        .into_iter()
+                        .flatten()
+                        .any(|p| activity.pids.binary_search(&p).is_ok())
+            }
+        }
+    }
+}
+
+/// Clusters [`Event`]s into [`Activity`]s: events from the same
+/// login session, or from the same process lineage for events
+/// outside of login sessions, that follow each other within a
+/// window determined by a [`WindowPolicy`]
+///
+/// This works on a higher level than the [`EventCoalescer`], which
+/// groups records by [`EventID`]. Events are expected in timestamp
+/// order, e.g. from a [`TimelineMerger`].
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// # let events: Vec<Event> = vec![];
+/// for activity in ActivityGrouper::new(IdleWindow::new(60_000)).group(events) {
+///     println!("{}: {} events", activity.start(), activity.events.len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ActivityGrouper<'a, P: WindowPolicy = IdleWindow> {
+    policy: P,
+    open: Vec<Activity<'a>>,
+    now: u64,
+}
+
+impl Default for ActivityGrouper<'_> {
+    fn default() -> Self {
+        Self::new(IdleWindow::default())
+    }
+}
+
+impl<'a, P: WindowPolicy> ActivityGrouper<'a, P> {
+    /// Constructs a grouper that uses `policy`.
+    pub fn new(policy: P) -> Self {
+        ActivityGrouper {
+            policy,
+            open: vec![],
+            now: 0,
+        }
+    }
+
+    /// Adds an event. Returns activities that have ended, ordered by
+    /// their first event.
+    pub fn push(&mut self, ev: Event<'a>) -> Vec<Activity<'a>> {
+        self.now = self.now.max(ev.id.timestamp);
+        let (done, open): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open)
+            .into_iter()
+            .partition(|a| self.policy.expired(a, self.now));
+        self.open = open;
+        let mut done = done;
+
+        let subject = Subject::of(&ev);
+        if let Some(i) = self
+            .open
+            .iter()
+            .rposition(|a| subject.relates_to(&ev.node, a))
+        {
+            if self.policy.continues(&self.open[i], &ev) {
+                self.add(i, &subject, ev);
+                done.sort_by_key(|a| a.start());
+                return done;
+            }
+            done.push(self.open.remove(i));
+        }
+        self.open.push(Activity {
+            node: ev.node.clone(),
+            ses: subject.ses,
+            auid: subject.auid,
+            pids: vec![],
+            events: vec![],
+        });
+        self.add(self.open.len() - 1, &subject, ev);
+        done.sort_by_key(|a| a.start());
+        done
+    }
+
+    fn add(&mut self, i: usize, subject: &Subject, ev: Event<'a>) {
+        let activity = &mut self.open[i];
+        if activity.auid.is_none() {
+            activity.auid = subject.auid;
+        }
+        if let Some(pid) = subject.pid {
+            if let Err(pos) = activity.pids.binary_search(&pid) {
+                activity.pids.insert(pos, pid);
+            }
+        }
+        activity.events.push(ev);
+    }
+
+    /// Returns all remaining activities, ordered by their first
+    /// event.
+    pub fn flush(&mut self) -> Vec<Activity<'a>> {
+        let mut done = std::mem::take(&mut self.open);
+        done.sort_by_key(|a| a.start());
+        done
+    }
+
+    /// Returns the number of activities that have not ended yet.
+    pub fn len(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Returns `true` if there are no activities that have not ended
+    /// yet.
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Turns an iterator over events into an iterator over
+    /// activities.
+    pub fn group<I: IntoIterator<Item = Event<'a>>>(
+        self,
+        events: I,
+    ) -> Activities<'a, I::IntoIter, P> {
+        Activities {
+            grouper: self,
+            events: events.into_iter(),
+            ready: std::collections::VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+/// Iterator over [`Activity`]s, see [`ActivityGrouper::group`]
+#[derive(Debug)]
+pub struct Activities<'a, I, P: WindowPolicy = IdleWindow> {
+    grouper: ActivityGrouper<'a, P>,
+    events: I,
+    ready: std::collections::VecDeque<Activity<'a>>,
+    done: bool,
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>, P: WindowPolicy> Iterator for Activities<'a, I, P> {
+    type Item = Activity<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(a) = self.ready.pop_front() {
+                return Some(a);
+            }
+            if self.done {
+                return None;
+            }
+            match self.events.next() {
+                Some(ev) => self.ready.extend(self.grouper.push(ev)),
+                None => {
+                    self.done = true;
+                    self.ready.extend(self.grouper.flush());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// AppArmor profile mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppArmorMode<'a> {
+    Enforce,
+    Complain,
+    Kill,
+    Unconfined,
+    /// Any other mode
+    Other(&'a [u8]),
+}
+
+impl<'a> From<&'a [u8]> for AppArmorMode<'a> {
+    fn from(s: &'a [u8]) -> Self {
+        match s {
+            b"enforce" => AppArmorMode::Enforce,
+            b"complain" => AppArmorMode::Complain,
+            b"kill" => AppArmorMode::Kill,
+            b"unconfined" => AppArmorMode::Unconfined,
+            _ => AppArmorMode::Other(s),
+        }
+    }
+}
+
+impl Display for AppArmorMode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppArmorMode::Enforce => write!(f, "enforce"),
+            AppArmorMode::Complain => write!(f, "complain"),
+            AppArmorMode::Kill => write!(f, "kill"),
+            AppArmorMode::Unconfined => write!(f, "unconfined"),
+            AppArmorMode::Other(s) => write!(f, "{}", String::from_utf8_lossy(s)),
+        }
+    }
+}
+
+/// An AppArmor label, e.g. `/usr/sbin/ntpd (enforce)`, as found in
+/// `subj`, `profile`, `label` fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AppArmorLabel<'a> {
+    /// The profile name, e.g. `/usr/sbin/ntpd` or `snap.foo.bar`
+    pub label: &'a [u8],
+    /// The mode, if present
+    pub mode: Option<AppArmorMode<'a>>,
+}
+
+impl<'a> AppArmorLabel<'a> {
+    /// Splits `s` into label and mode.
+    ///
+    /// A leading `=` as produced by some kernels in `subj==…` is
+    /// removed. Returns `None` for empty labels.
+    pub fn parse(s: &'a [u8]) -> Option<Self> {
+        let s = s.strip_prefix(b"=").unwrap_or(s);
+        let (label, mode) = match s.strip_suffix(b")") {
+            Some(rest) => match rest.windows(2).rposition(|w| w == b" (") {
+                Some(pos) => (&rest[..pos], Some(AppArmorMode::from(&rest[pos + 2..]))),
+                None => (s, None),
+            },
+            None => (s, None),
+        };
+        if label.is_empty() {
+            return None;
+        }
+        Some(AppArmorLabel { label, mode })
+    }
+}
+
+impl Display for AppArmorLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.label))?;
+        if let Some(mode) = &self.mode {
+            write!(f, " ({mode})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Value<'_> {
+    /// Interprets a string value as AppArmor label, see
+    /// [`AppArmorLabel::parse`].
+    pub fn as_apparmor_label(&self) -> Option<AppArmorLabel<'_>> {
+        match self {
+            Value::Str(s, _) => AppArmorLabel::parse(s),
+            Value::Owned(s) => AppArmorLabel::parse(s),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// Byte order of an architecture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Syscall calling convention, where an architecture has more than
+/// one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// The only convention of the architecture
+    Default,
+    /// ARM EABI
+    ArmEabi,
+    /// ARM OABI: syscall numbers are offset by `0x900000`
+    ArmOabi,
+    /// MIPS o32: syscall numbers are offset by 4000
+    MipsO32,
+    /// MIPS n64: syscall numbers are offset by 5000
+    MipsN64,
+    /// MIPS n32: syscall numbers are offset by 6000
+    MipsN32,
+}
+
+/// `__AUDIT_ARCH_64BIT`
+pub const AUDIT_ARCH_64BIT: u32 = 0x8000_0000;
+/// `__AUDIT_ARCH_LE`
+pub const AUDIT_ARCH_LE: u32 = 0x4000_0000;
+/// `__AUDIT_ARCH_CONVENTION_MIPS64_N32`
+pub const AUDIT_ARCH_CONVENTION_MIPS64_N32: u32 = 0x2000_0000;
+
+const EM_MIPS: u16 = 8;
+const EM_ARM: u16 = 40;
+
+const ARM_OABI_SYSCALL_BASE: i64 = 0x90_0000;
+
+/// Machine names by `EM_*` value, sorted for binary search
+const MACHINES: &[(u16, &str)] = &[
+    (2, "sparc"),
+    (3, "i386"),
+    (4, "m68k"),
+    (8, "mips"),
+    (15, "parisc"),
+    (20, "ppc"),
+    (21, "ppc64"),
+    (22, "s390"),
+    (40, "arm"),
+    (42, "sh"),
+    (43, "sparc64"),
+    (50, "ia64"),
+    (62, "x86_64"),
+    (92, "openrisc"),
+    (94, "xtensa"),
+    (183, "aarch64"),
+    (189, "microblaze"),
+    (243, "riscv"),
+    (252, "csky"),
+    (258, "loongarch"),
+    (0x9026, "alpha"),
+];
+
+/// Decoded `AUDIT_ARCH_*` value, as found in the `arch` field of
+/// `SYSCALL` records
+///
+/// The value consists of the ELF machine number (`EM_*`) in the
+/// lower 16 bits and flags for 64-bit, little-endian, and the MIPS
+/// n32 convention in the upper bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArchInfo {
+    /// ELF machine number (`EM_*`)
+    pub machine: u16,
+    pub is_64bit: bool,
+    pub endianness: Endianness,
+    /// Set for MIPS n32 (`AUDIT_ARCH_MIPS64N32`,
+    /// `AUDIT_ARCH_MIPSEL64N32`)
+    pub mips_n32: bool,
+}
+
+impl ArchInfo {
+    /// Decodes an `AUDIT_ARCH_*` value.
+    pub fn from_audit_arch(arch: u32) -> Self {
+        ArchInfo {
+            machine: arch as u16,
+            is_64bit: arch & AUDIT_ARCH_64BIT != 0,
+            endianness: if arch & AUDIT_ARCH_LE != 0 {
+                Endianness::Little
+            } else {
+                Endianness::Big
+            },
+            mips_n32: arch & AUDIT_ARCH_CONVENTION_MIPS64_N32 != 0,
+        }
+    }
+
+    /// Returns the `AUDIT_ARCH_*` value.
+    pub fn audit_arch(&self) -> u32 {
+        let mut arch = self.machine as u32;
+        if self.is_64bit {
+            arch |= AUDIT_ARCH_64BIT;
+        }
+        if self.endianness == Endianness::Little {
+            arch |= AUDIT_ARCH_LE;
+        }
+        if self.mips_n32 {
+            arch |= AUDIT_ARCH_CONVENTION_MIPS64_N32;
+        }
+        arch
+    }
+
+    /// Returns the name of the machine, without byte order or word
+    /// size, e.g. `mips` for all MIPS variants.
+    pub fn machine_name(&self) -> Option<&'static str> {
+        MACHINES
+            .binary_search_by_key(&self.machine, |(m, _)| *m)
+            .ok()
+            .map(|i| MACHINES[i].1)
+    }
+
+    /// Determines the calling convention of a syscall and returns it
+    /// along with the syscall number relative to that convention's
+    /// table.
+    ///
+    /// On ARM, the same `AUDIT_ARCH_ARM` value is used for EABI and
+    /// OABI; OABI syscalls are recognized by their number. On MIPS,
+    /// the convention is part of the arch value, syscall numbers
+    /// carry a convention-specific offset.
+    pub fn syscall_abi(&self, syscall: i64) -> (Abi, i64) {
+        match self.machine {
+            EM_ARM if syscall >= ARM_OABI_SYSCALL_BASE => {
+                (Abi::ArmOabi, syscall - ARM_OABI_SYSCALL_BASE)
+            }
+            EM_ARM => (Abi::ArmEabi, syscall),
+            EM_MIPS => {
+                let (abi, base) = match (self.is_64bit, self.mips_n32) {
+                    (false, _) => (Abi::MipsO32, 4000),
+                    (true, false) => (Abi::MipsN64, 5000),
+                    (true, true) => (Abi::MipsN32, 6000),
+                };
+                (
+                    abi,
+                    syscall
+                        .checked_sub(base)
+                        .filter(|n| *n >= 0)
+                        .unwrap_or(syscall),
+                )
+            }
+            _ => (Abi::Default, syscall),
+        }
+    }
+}
+
+impl From<u32> for ArchInfo {
+    fn from(arch: u32) -> Self {
+        Self::from_audit_arch(arch)
+    }
+}
+
+/// Formats the architecture the way _ausearch(8)_ does, e.g.
+/// `x86_64`, `i386`, `aarch64`, `armeb`, `mips64el`, `ppc64le`;
+/// unknown machines as hexadecimal `AUDIT_ARCH_*` value.
+impl Display for ArchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(name) = self.machine_name() else {
+            return write!(f, "{:x}", self.audit_arch());
+        };
+        let le = self.endianness == Endianness::Little;
+        match name {
+            "loongarch" | "parisc" if self.is_64bit => write!(f, "{name}64"),
+            "arm" => f.write_str(if le { "arm" } else { "armeb" }),
+            "aarch64" => f.write_str(if le { "aarch64" } else { "aarch64_be" }),
+            "ppc64" => f.write_str(if le { "ppc64le" } else { "ppc64" }),
+            "s390" => f.write_str(if self.is_64bit { "s390x" } else { "s390" }),
+            "riscv" => {
+                write!(f, "{name}{}", if self.is_64bit { "64" } else { "32" })
+            }
+            "mips" => write!(
+                f,
+                "mips{}{}{}",
+                if self.is_64bit { "64" } else { "" },
+                if le { "el" } else { "" },
+                if self.mips_n32 { "n32" } else { "" },
+            ),
+            _ => f.write_str(name),
+        }
+    }
+}
+
+impl SyscallRecord<'_> {
+    /// Returns the decoded `arch` field.
+    pub fn arch_info(&self) -> Option<ArchInfo> {
+        self.arch
+            .and_then(|a| u32::try_from(a).ok())
+            .map(ArchInfo::from_audit_arch)
+    }
+}
@@ -0,0 +1,320 @@
+//! Zero-copy archival of [`Message`](crate::Message) and
+//! [`Event`](crate::Event), using [`rkyv`]
+//!
+//! The types in this module are owned counterparts of
+//! [`crate::Message`] and [`crate::Event`] that derive
+//! [`rkyv::Archive`]. Archived events can be stored, e.g. in
+//! memory-mapped files, and their fields read through the
+//! `Archived*` types without deserializing them first.
+//!
+//! Keys are stored in their serialized form (e.g. `a2[3]`, `UID`),
+//! values in the shape of [`OwnedValue`]. Converting back into
+//! [`crate::Message`] and [`crate::Event`] is lossless with the same
+//! exceptions as for [`proto`](crate::proto): quoting of string
+//! values is not preserved, and [`Key::Vendor`](crate::Key::Vendor)
+//! keys are restored as [`Key::Name`](crate::Key::Name).
+//!
+//! ```
+//! # use linux_audit_parser::*;
+//! let msg = parse(&b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n"[..], false).unwrap();
+//! let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&archive::Message::from(&msg)).unwrap();
+//! let archived = rkyv::access::<archive::ArchivedMessage, rkyv::rancor::Error>(&bytes).unwrap();
+//! assert_eq!(archived.id(), msg.id);
+//! assert_eq!(archived.get("cwd").and_then(|v| v.as_bytes()), Some(&b"/root"[..]));
+//! ```
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::owned::{EventParts, MessageParts};
+use crate::{EventID, MessageType, Number, OwnedValue};
+
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+pub struct Message {
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+    pub sequence: u32,
+    pub node: Option<Vec<u8>>,
+    pub ty: u32,
+    pub body: Vec<Entry>,
+    /// See [`crate::Message::truncated`]
+    pub truncated: bool,
+    /// See [`crate::Message::hex_decoded`]
+    pub hex_decoded: bool,
+    /// See [`crate::Message::type_name`]
+    pub type_name: Option<Vec<u8>>,
+    /// See [`crate::Message::enriched`]
+    pub enriched: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+pub struct Event {
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+    pub sequence: u32,
+    pub node: Option<Vec<u8>>,
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+pub struct Record {
+    pub ty: u32,
+    pub body: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[rkyv(
+    serialize_bounds(
+        __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+        __S::Error: rkyv::rancor::Source,
+    ),
+    deserialize_bounds(__D::Error: rkyv::rancor::Source),
+    bytecheck(bounds(
+        __C: rkyv::validation::ArchiveContext,
+        __C::Error: rkyv::rancor::Source,
+    )),
+)]
+pub struct Entry {
+    pub key: String,
+    #[rkyv(omit_bounds)]
+    pub value: Value,
+}
+
+/// A value, see [`OwnedValue`]
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[rkyv(
+    serialize_bounds(
+        __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+        __S::Error: rkyv::rancor::Source,
+    ),
+    deserialize_bounds(__D::Error: rkyv::rancor::Source),
+    bytecheck(bounds(
+        __C: rkyv::validation::ArchiveContext,
+        __C::Error: rkyv::rancor::Source,
+    )),
+)]
+pub enum Value {
+    Empty,
+    Str(Vec<u8>),
+    Dec(i64),
+    Hex(u64),
+    Oct(u64),
+    List(#[rkyv(omit_bounds)] Vec<Value>),
+    Map(#[rkyv(omit_bounds)] Vec<Entry>),
+    Skipped { args: u64, bytes: u64 },
+}
+
+impl From<OwnedValue> for Value {
+    fn from(v: OwnedValue) -> Self {
+        match v {
+            OwnedValue::Empty => Value::Empty,
+            OwnedValue::Bytes(b) => Value::Str(b),
+            OwnedValue::Number(Number::Dec(n)) => Value::Dec(n),
+            OwnedValue::Number(Number::Hex(n)) => Value::Hex(n),
+            OwnedValue::Number(Number::Oct(n)) => Value::Oct(n),
+            OwnedValue::List(vs) => Value::List(vs.into_iter().map(Value::from).collect()),
+            OwnedValue::Map(vs) => Value::Map(vs.into_iter().map(Entry::from).collect()),
+            OwnedValue::Skipped { args, bytes } => Value::Skipped {
+                args: args as u64,
+                bytes: bytes as u64,
+            },
+        }
+    }
+}
+
+impl From<(String, OwnedValue)> for Entry {
+    fn from((key, value): (String, OwnedValue)) -> Self {
+        Entry {
+            key,
+            value: value.into(),
+        }
+    }
+}
+
+impl From<Value> for OwnedValue {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Empty => OwnedValue::Empty,
+            Value::Str(b) => OwnedValue::Bytes(b),
+            Value::Dec(n) => OwnedValue::Number(Number::Dec(n)),
+            Value::Hex(n) => OwnedValue::Number(Number::Hex(n)),
+            Value::Oct(n) => OwnedValue::Number(Number::Oct(n)),
+            Value::List(vs) => OwnedValue::List(vs.into_iter().map(OwnedValue::from).collect()),
+            Value::Map(es) => OwnedValue::Map(es.into_iter().map(Entry::into).collect()),
+            Value::Skipped { args, bytes } => OwnedValue::Skipped {
+                args: args as usize,
+                bytes: bytes as usize,
+            },
+        }
+    }
+}
+
+impl From<Entry> for (String, OwnedValue) {
+    fn from(e: Entry) -> Self {
+        (e.key, e.value.into())
+    }
+}
+
+impl From<&crate::Message<'_>> for Message {
+    fn from(msg: &crate::Message) -> Self {
+        let p = MessageParts::from(msg);
+        Message {
+            timestamp: p.id.timestamp,
+            sequence: p.id.sequence,
+            node: p.node,
+            ty: p.ty.0,
+            body: p.body.into_iter().map(Entry::from).collect(),
+            truncated: p.truncated,
+            hex_decoded: p.hex_decoded,
+            type_name: p.type_name,
+            enriched: p.enriched,
+        }
+    }
+}
+
+impl From<&crate::Event<'_>> for Event {
+    fn from(ev: &crate::Event) -> Self {
+        let p = EventParts::from(ev);
+        Event {
+            timestamp: p.id.timestamp,
+            sequence: p.id.sequence,
+            node: p.node,
+            records: p
+                .records
+                .into_iter()
+                .map(|(ty, body)| Record {
+                    ty: ty.0,
+                    body: body.into_iter().map(Entry::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Message> for crate::Message<'_> {
+    fn from(msg: Message) -> Self {
+        MessageParts {
+            id: EventID {
+                timestamp: msg.timestamp,
+                sequence: msg.sequence,
+            },
+            node: msg.node,
+            ty: MessageType(msg.ty),
+            body: msg.body.into_iter().map(Entry::into).collect(),
+            truncated: msg.truncated,
+            hex_decoded: msg.hex_decoded,
+            type_name: msg.type_name,
+            enriched: msg.enriched,
+        }
+        .into()
+    }
+}
+
+impl From<Event> for crate::Event<'_> {
+    fn from(ev: Event) -> Self {
+        EventParts {
+            id: EventID {
+                timestamp: ev.timestamp,
+                sequence: ev.sequence,
+            },
+            node: ev.node,
+            records: ev
+                .records
+                .into_iter()
+                .map(|r| {
+                    (
+                        MessageType(r.ty),
+                        r.body.into_iter().map(Entry::into).collect(),
+                    )
+                })
+                .collect(),
+        }
+        .into()
+    }
+}
+
+fn find<'a>(body: &'a [ArchivedEntry], key: &str) -> Option<&'a ArchivedValue> {
+    body.iter()
+        .find(|e| e.key.as_str() == key)
+        .map(|e| &e.value)
+}
+
+impl ArchivedMessage {
+    /// Returns the event identifier.
+    pub fn id(&self) -> EventID {
+        EventID {
+            timestamp: self.timestamp.to_native(),
+            sequence: self.sequence.to_native(),
+        }
+    }
+
+    /// Returns the node name.
+    pub fn node(&self) -> Option<&[u8]> {
+        self.node.as_ref().map(|n| n.as_slice())
+    }
+
+    /// Returns the message type.
+    pub fn ty(&self) -> MessageType {
+        MessageType(self.ty.to_native())
+    }
+
+    /// Returns the symbolic type of messages whose type is not
+    /// known, see [`crate::Message::type_name`].
+    pub fn type_name(&self) -> Option<&[u8]> {
+        self.type_name.as_ref().map(|n| n.as_slice())
+    }
+
+    /// Returns the value of the first field named `key`, in
+    /// serialized form.
+    pub fn get(&self, key: &str) -> Option<&ArchivedValue> {
+        find(&self.body, key)
+    }
+}
+
+impl ArchivedEvent {
+    /// Returns the event identifier.
+    pub fn id(&self) -> EventID {
+        EventID {
+            timestamp: self.timestamp.to_native(),
+            sequence: self.sequence.to_native(),
+        }
+    }
+
+    /// Returns the node name.
+    pub fn node(&self) -> Option<&[u8]> {
+        self.node.as_ref().map(|n| n.as_slice())
+    }
+
+    /// Returns the bodies of all records of type `ty`.
+    pub fn bodies(&self, ty: MessageType) -> impl Iterator<Item = &[ArchivedEntry]> {
+        self.records
+            .iter()
+            .filter(move |r| r.ty.to_native() == ty.0)
+            .map(|r| r.body.as_slice())
+    }
+
+    /// Returns the value of the first field named `key` in the first
+    /// record of type `ty`.
+    pub fn get(&self, ty: MessageType, key: &str) -> Option<&ArchivedValue> {
+        self.bodies(ty).next().and_then(|b| find(b, key))
+    }
+}
+
+impl ArchivedValue {
+    /// Returns the contents of string values.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            ArchivedValue::Str(b) => Some(b.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns numeric values, regardless of radix.
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            ArchivedValue::Dec(n) => Some(Number::Dec(n.to_native())),
+            ArchivedValue::Hex(n) => Some(Number::Hex(n.to_native())),
+            ArchivedValue::Oct(n) => Some(Number::Oct(n.to_native())),
+            _ => None,
+        }
+    }
+}
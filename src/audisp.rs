@@ -0,0 +1,322 @@
+//! Runtime for _audisp_ plugins
+//!
+//! _auditd(8)_ starts the plugins that are configured in
+//! `/etc/audit/plugins.d` and writes the records to their standard
+//! input, in the log format if `format = string` is set. The `args`
+//! of the plugin configuration are passed as command line arguments;
+//! plugins that need more settings conventionally read them from a
+//! configuration file of their own. SIGHUP asks the plugin to reload
+//! its configuration, SIGTERM or the end of input to exit.
+//!
+//! [`Runtime`] implements this protocol: It reads the configuration
+//! at start and on SIGHUP and passes it to the [`Plugin`], parses the
+//! records, groups them into events, and passes them on.
+//!
+//! ```no_run
+//! use linux_audit_parser::audisp::{Config, Plugin, Runtime};
+//! use linux_audit_parser::Event;
+//!
+//! struct Count(usize);
+//!
+//! impl Plugin for Count {
+//!     fn configure(&mut self, config: &Config) -> std::io::Result<()> {
+//!         eprintln!("verbose: {:?}", config.get("verbose"));
+//!         Ok(())
+//!     }
+//!
+//!     fn event(&mut self, _: Event<'static>) -> std::io::Result<()> {
+//!         self.0 += 1;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! Runtime::new()
+//!     .config_file("/etc/audit/count.conf")
+//!     .run(Count(0))
+//!     .unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::os::fd::RawFd;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use crate::{Event, EventCoalescer, MessageReader, ParseError, Parser};
+
+/// Incomplete events are passed on once this many later sequence
+/// numbers have been seen, see [`EventCoalescer::max_sequence_gap`].
+const MAX_SEQUENCE_GAP: u32 = 1000;
+
+pub(crate) static TERMINATE: AtomicBool = AtomicBool::new(false);
+pub(crate) static RELOAD: AtomicBool = AtomicBool::new(false);
+
+/// Both ends of the pipe written to by the signal handler
+static SIGNAL_PIPE: [AtomicI32; 2] = [AtomicI32::new(-1), AtomicI32::new(-1)];
+
+extern "C" fn on_signal(sig: libc::c_int) {
+    match sig {
+        libc::SIGTERM => TERMINATE.store(true, Ordering::SeqCst),
+        libc::SIGHUP => RELOAD.store(true, Ordering::SeqCst),
+        _ => {}
+    }
+    let fd = SIGNAL_PIPE[1].load(Ordering::SeqCst);
+    if fd >= 0 {
+        // SAFETY: write and errno access are async-signal-safe. If
+        // the pipe is full, a wakeup is pending anyway.
+        unsafe {
+            let errno = *libc::__errno_location();
+            libc::write(fd, [0u8].as_ptr().cast(), 1);
+            *libc::__errno_location() = errno;
+        }
+    }
+}
+
+/// Creates the signal pipe and installs handlers for SIGTERM and
+/// SIGHUP. Returns the end of the pipe to wait on.
+fn install_handlers() -> io::Result<RawFd> {
+    if SIGNAL_PIPE[0].load(Ordering::SeqCst) < 0 {
+        let mut fds = [-1; 2];
+        // SAFETY: fds has room for the two descriptors.
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        SIGNAL_PIPE[1].store(fds[1], Ordering::SeqCst);
+        SIGNAL_PIPE[0].store(fds[0], Ordering::SeqCst);
+    }
+    for sig in [libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: sigaction is zero-initializable, the handler only
+        // stores to atomics, which is async-signal-safe.
+        unsafe {
+            let mut sa: libc::sigaction = std::mem::zeroed();
+            sa.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigemptyset(&mut sa.sa_mask);
+            if libc::sigaction(sig, &sa, std::ptr::null_mut()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+    Ok(SIGNAL_PIPE[0].load(Ordering::SeqCst))
+}
+
+/// Standard input, read after waiting for either input or a signal
+///
+/// A signal that arrives after the runtime has checked for signals,
+/// but before it waits for input, still ends the wait: The handler
+/// writes to the signal pipe, which is reported as an interrupted
+/// read.
+struct SignalInput {
+    pipe: RawFd,
+}
+
+impl Read for SignalInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut fds = [libc::STDIN_FILENO, self.pipe].map(|fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        // SAFETY: fds is a valid array of two pollfd structures.
+        if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if fds[1].revents != 0 {
+            let mut drain = [0u8; 64];
+            // SAFETY: the pipe is non-blocking, drain is writable.
+            while unsafe { libc::read(self.pipe, drain.as_mut_ptr().cast(), drain.len()) } > 0 {}
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        // SAFETY: buf is writable for buf.len() bytes.
+        match unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) } {
+            n if n < 0 => Err(io::Error::last_os_error()),
+            n => Ok(n as usize),
+        }
+    }
+}
+
+/// Plugin configuration, passed to [`Plugin::configure`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Command line arguments without the program name, i.e. the
+    /// `args` of the plugin configuration
+    pub args: Vec<String>,
+    /// Settings read from the configuration file, see
+    /// [`Runtime::config_file`]
+    pub settings: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Returns the setting `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.settings.get(key).map(String::as_str)
+    }
+
+    /// Parses settings in the format of _auditd.conf(5)_: one
+    /// `key = value` pair per line, `#` starts a comment.
+    pub fn parse_settings(s: &str) -> io::Result<BTreeMap<String, String>> {
+        let mut settings = BTreeMap::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(l, _)| l).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected key = value", n + 1),
+                ));
+            };
+            settings.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(settings)
+    }
+}
+
+/// Callbacks invoked by the [`Runtime`]
+pub trait Plugin {
+    /// Called before the first record is read.
+    fn configure(&mut self, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called for every complete event.
+    fn event(&mut self, ev: Event<'static>) -> io::Result<()>;
+
+    /// Called for lines that cannot be parsed. Such lines are ignored
+    /// by default. `line` is empty for lines that exceed the
+    /// [maximum length](MessageReader::max_line_length).
+    fn parse_error(&mut self, _line: &[u8], _err: ParseError) {}
+
+    /// Called on SIGHUP with the configuration read again. Calls
+    /// [`configure`](Self::configure) by default.
+    fn reload(&mut self, config: &Config) -> io::Result<()> {
+        self.configure(config)
+    }
+
+    /// Called once before exiting, after the remaining incomplete
+    /// events have been passed to [`event`](Self::event).
+    fn shutdown(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads records, groups them into events, and passes them to a
+/// [`Plugin`]
+pub struct Runtime {
+    parser: Parser,
+    coalescer: EventCoalescer<'static>,
+    args: Vec<String>,
+    config_file: Option<PathBuf>,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runtime {
+    /// Creates a runtime with the default [`Parser`], the command line
+    /// arguments of the process, and an [`EventCoalescer`] that
+    /// passes on incomplete events after 1000 later sequence numbers.
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::default(),
+            coalescer: EventCoalescer::new().max_sequence_gap(MAX_SEQUENCE_GAP),
+            args: std::env::args().skip(1).collect(),
+            config_file: None,
+        }
+    }
+
+    /// Sets the parser used for the records.
+    pub fn parser(mut self, parser: Parser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Sets the coalescer used to group records into events.
+    pub fn coalescer(mut self, coalescer: EventCoalescer<'static>) -> Self {
+        self.coalescer = coalescer;
+        self
+    }
+
+    /// Sets the arguments passed to [`Plugin::configure`] instead of
+    /// the command line arguments.
+    pub fn args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Sets the file that [`Config::settings`] are read from, at start
+    /// and on SIGHUP. A missing file is an error.
+    pub fn config_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    fn config(&self) -> io::Result<Config> {
+        let settings = match &self.config_file {
+            Some(path) => Config::parse_settings(&std::fs::read_to_string(path)?)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Config {
+            args: self.args.clone(),
+            settings,
+        })
+    }
+
+    /// Installs signal handlers and processes records from standard
+    /// input until SIGTERM is received or the input ends.
+    pub fn run<P: Plugin>(self, mut plugin: P) -> io::Result<()> {
+        let pipe = install_handlers()?;
+        self.run_with(SignalInput { pipe }, &mut plugin)
+    }
+
+    /// Processes records from `input` until it ends. SIGTERM and
+    /// SIGHUP are handled if the handlers have been installed by
+    /// [`run`](Self::run). They are noticed when a read on `input` is
+    /// interrupted; unlike standard input in [`run`](Self::run),
+    /// `input` may block without noticing a signal that arrives just
+    /// before the read starts, until more input arrives.
+    pub fn run_with<R: Read, P: Plugin>(mut self, input: R, plugin: &mut P) -> io::Result<()> {
+        plugin.configure(&self.config()?)?;
+        let mut reader = MessageReader::new(input);
+        reader.interruptible = true;
+        loop {
+            if TERMINATE.swap(false, Ordering::SeqCst) {
+                // Lines that have been read are still processed.
+                reader.stop();
+            }
+            if RELOAD.swap(false, Ordering::SeqCst) {
+                plugin.reload(&self.config()?)?;
+            }
+            match reader.next_raw() {
+                None => break,
+                Some(Ok(line)) => self.process(line, plugin)?,
+                Some(Err(ParseError::Io(e))) if e.kind() == io::ErrorKind::Interrupted => {}
+                Some(Err(ParseError::Io(e))) => return Err(e),
+                Some(Err(e)) => plugin.parse_error(&[], e),
+            }
+            for ev in self.coalescer.expire() {
+                plugin.event(ev)?;
+            }
+        }
+        for ev in self.coalescer.flush() {
+            plugin.event(ev)?;
+        }
+        plugin.shutdown()
+    }
+
+    fn process<P: Plugin>(&mut self, line: &[u8], plugin: &mut P) -> io::Result<()> {
+        match self.parser.parse(line) {
+            Ok(msg) => match self.coalescer.push(msg) {
+                Some(ev) => plugin.event(ev),
+                None => Ok(()),
+            },
+            Err(e) => {
+                plugin.parse_error(line, e);
+                Ok(())
+            }
+        }
+    }
+}
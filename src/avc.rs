@@ -0,0 +1,81 @@
+use std::fmt::{self, Display};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use crate::*;
+
+/// Decision reported in an `AVC` record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AvcDecision {
+    Denied,
+    Granted,
+}
+
+/// The set of permissions from an `AVC` record, e.g. `{ read write }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permissions<'a> {
+    /// Whether the permissions have been denied or granted
+    pub decision: AvcDecision,
+    perms: Vec<&'a [u8]>,
+}
+
+impl<'a> Permissions<'a> {
+    /// True if `perm` is part of the set.
+    pub fn contains<P: AsRef<[u8]>>(&self, perm: P) -> bool {
+        self.perms.contains(&perm.as_ref())
+    }
+
+    /// Returns an iterator over the permission names, in the order
+    /// in which they were logged.
+    pub fn iter(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.perms.iter().copied()
+    }
+
+    /// Returns the number of permissions.
+    pub fn len(&self) -> usize {
+        self.perms.len()
+    }
+
+    /// Returns `true` if there are no permissions.
+    pub fn is_empty(&self) -> bool {
+        self.perms.is_empty()
+    }
+}
+
+impl Display for Permissions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for p in &self.perms {
+            write!(f, " {}", String::from_utf8_lossy(p))?;
+        }
+        write!(f, " }}")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Permissions<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.perms.iter().map(|p| String::from_utf8_lossy(p)))
+    }
+}
+
+impl Body<'_> {
+    /// Returns the permission set of an `AVC` record, i.e. the
+    /// `denied` or `granted` list.
+    pub fn permissions(&self) -> Option<Permissions<'_>> {
+        let (decision, vs) = match (self.get("denied"), self.get("granted")) {
+            (Some(Value::List(vs)), _) => (AvcDecision::Denied, vs),
+            (_, Some(Value::List(vs))) => (AvcDecision::Granted, vs),
+            _ => return None,
+        };
+        let perms = vs
+            .iter()
+            .filter_map(|v| match v {
+                Value::Str(s, _) => Some(*s),
+                _ => None,
+            })
+            .collect();
+        Some(Permissions { decision, perms })
+    }
+}
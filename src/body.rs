@@ -1,5 +1,6 @@
 use std::fmt::{self, Debug};
 use std::ops::Range;
+use std::sync::Arc;
 
 #[cfg(feature = "serde")]
 use serde::{ser::SerializeMap, Serialize, Serializer};
@@ -9,7 +10,9 @@ use crate::*;
 /// Parsed body of an Audit message, consisting of [`Key`]/[`Value`] pairs.
 pub struct Body<'a> {
     elems: Vec<(Key, Value<'a>)>,
-    arena: Vec<Vec<u8>>,
+    arena: Vec<Arc<Vec<u8>>>,
+    /// Start positions of the sections added by [`Body::extend`]
+    sections: Vec<usize>,
     _pin: std::marker::PhantomPinned,
 }
 
@@ -21,9 +24,11 @@ impl<'a> PartialEq<Body<'a>> for Body<'a> {
 
 impl Default for Body<'_> {
     fn default() -> Self {
+        // Empty bodies, e.g. of EOE records, do not allocate.
         Body {
-            elems: Vec::with_capacity(8),
+            elems: Vec::new(),
             arena: vec![],
+            sections: vec![],
             _pin: std::marker::PhantomPinned,
         }
     }
@@ -51,6 +56,54 @@ impl Serialize for Body<'_> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Body<'_> {
+    /// Returns a wrapper that serializes the body using `format` for
+    /// hexadecimal and octal numbers, e.g. for downstream schemas
+    /// that expect numeric columns.
+    pub fn with_number_format(&self, format: NumberFormat) -> WithNumberFormat<'_, Self> {
+        WithNumberFormat {
+            inner: self,
+            format,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithNumberFormat<'_, Body<'_>> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.inner.elems.len()))?;
+        for (k, v) in self.inner {
+            map.serialize_entry(k, &v.with_number_format(self.format))?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Body<'_> {
+    /// Returns a wrapper that serializes the body with keys named
+    /// according to `naming`, including keys of `msg='…'` maps,
+    /// without modifying the body.
+    pub fn with_key_naming<'r>(&self, naming: KeyNaming<'r>) -> WithKeyNaming<'_, 'r, Self> {
+        WithKeyNaming {
+            inner: self,
+            naming,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithKeyNaming<'_, '_, Body<'_>> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.inner.elems.len()))?;
+        for (k, v) in self.inner {
+            map.serialize_entry(&self.naming.name(k), &v.with_key_naming(self.naming))?;
+        }
+        map.end()
+    }
+}
+
 impl Body<'_> {
     /// Constructs a new, empty `Body`.
     pub fn new() -> Self {
@@ -66,6 +119,19 @@ impl Body<'_> {
         }
     }
 
+    /// Constructs a new, empty `Body` with capacity for `len` entries
+    /// and `bytes` bytes of string values.
+    pub(crate) fn with_capacity_bytes(len: usize, bytes: usize) -> Self {
+        Self {
+            elems: Vec::with_capacity(len),
+            arena: match bytes {
+                0 => vec![],
+                n => vec![Arc::new(Vec::with_capacity(n))],
+            },
+            ..Self::default()
+        }
+    }
+
     fn add_slice<'a, 'i>(&mut self, input: &'i [u8]) -> &'a [u8]
     where
         'a: 'i,
@@ -82,8 +148,9 @@ impl Body<'_> {
                 return unsafe { &*s };
             }
         }
-        for buf in self.arena.iter_mut() {
-            if buf.capacity() - buf.len() > ilen {
+        // Buffers shared with clones are not modified.
+        for buf in self.arena.iter_mut().filter_map(Arc::get_mut) {
+            if buf.capacity() - buf.len() >= ilen {
                 let e = buf.len();
                 buf.extend(input);
                 let s = std::ptr::slice_from_raw_parts(buf[e..].as_ptr(), ilen);
@@ -91,9 +158,10 @@ impl Body<'_> {
             }
         }
         self.arena
-            .push(Vec::with_capacity(1014 * (1 + (ilen / 1024))));
+            .push(Arc::new(Vec::with_capacity(1014 * (1 + (ilen / 1024)))));
         let i = self.arena.len() - 1;
-        let new_buf = &mut self.arena[i];
+        // safety: The buffer has just been created.
+        let new_buf = Arc::get_mut(&mut self.arena[i]).unwrap();
         new_buf.extend(input);
         let s = std::ptr::slice_from_raw_parts(new_buf[..].as_ptr(), ilen);
         unsafe { &*s }
@@ -133,13 +201,52 @@ impl Body<'_> {
         self.elems.push((k, v));
     }
 
+    /// Appends a string value. The bytes are copied into the `Body`.
+    pub fn push_str<K: Into<Key>>(&mut self, key: K, value: &[u8]) {
+        self.push((key.into(), Value::Str(value, Quote::None)));
+    }
+
+    /// Appends a decimal number.
+    pub fn push_num<K: Into<Key>>(&mut self, key: K, value: i64) {
+        self.push((key.into(), Value::Number(Number::Dec(value))));
+    }
+
+    /// Appends a hexadecimal number.
+    pub fn push_hex<K: Into<Key>>(&mut self, key: K, value: u64) {
+        self.push((key.into(), Value::Number(Number::Hex(value))));
+    }
+
+    /// Appends a map, as found in `msg='…'`. String values are copied
+    /// into the `Body`.
+    pub fn push_map<'i, K, I, MK, MV>(&mut self, key: K, pairs: I)
+    where
+        K: Into<Key>,
+        I: IntoIterator<Item = (MK, MV)>,
+        MK: Into<Key>,
+        MV: Into<Value<'i>>,
+    {
+        let map = pairs
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        self.push((key.into(), Value::Map(map)));
+    }
+
     /// Returns the number of elements in the `Body`.
     pub fn len(&self) -> usize {
         self.elems.len()
     }
 
-    /// Extends Body with the elements of another `Body`.
+    /// Extends Body with the elements of another `Body`. Unless the
+    /// `Body` is new, the elements form a new section, see
+    /// [`Body::sections`].
     pub fn extend(&mut self, other: Self) {
+        let offset = self.elems.len();
+        if offset > 0 || !self.sections.is_empty() {
+            self.sections.push(offset);
+        }
+        self.sections
+            .extend(other.sections.iter().map(|s| s + offset));
         self.arena.extend(other.arena);
         self.elems.reserve(other.elems.len());
         for (k, v) in other.elems {
@@ -153,11 +260,126 @@ impl Body<'_> {
     }
 
     /// Retrieves the first value found for a given `key`.
-    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&Value> {
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&Value<'_>> {
         let key = key.as_ref();
         self.elems.iter().find(|(k, _)| k == key).map(|(_, v)| v)
     }
 
+    /// Retrieves the first value found for `key`, comparing keys
+    /// directly instead of by name. Use with [`key!`](crate::key!).
+    pub fn get_key(&self, key: &Key) -> Option<&Value<'_>> {
+        self.elems.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Builds an index for repeated lookups by key.
+    ///
+    /// [`get`](Self::get) scans all entries. For records with many
+    /// fields, e.g. enriched `SYSCALL` records, building the index
+    /// once and doing lookups through it is faster if more than a
+    /// handful of keys are looked up.
+    pub fn index(&self) -> BodyIndex<'_> {
+        let mut keys: Vec<_> = self
+            .elems
+            .iter()
+            .enumerate()
+            .map(|(i, (k, _))| {
+                let k = match k {
+                    Key::Name(r) | Key::NameUID(r) | Key::NameGID(r) => r.to_vec(),
+                    k => k.to_string().into_bytes(),
+                };
+                (k, i)
+            })
+            .collect();
+        keys.sort();
+        BodyIndex {
+            elems: &self.elems,
+            keys,
+        }
+    }
+
+    /// Returns a view restricted to the entries whose key is one of
+    /// `keys`, in their original order.
+    ///
+    /// No values are copied, so this is a cheap way to build
+    /// projections of the same record for different destinations,
+    /// e.g. one with identity fields and one with file fields.
+    pub fn slice<K: AsRef<[u8]>>(&self, keys: &[K]) -> BodySlice<'_> {
+        let positions = self
+            .elems
+            .iter()
+            .enumerate()
+            .filter(|(_, (k, _))| keys.iter().any(|key| k == key.as_ref()))
+            .map(|(i, _)| i)
+            .collect();
+        BodySlice {
+            body: self,
+            positions,
+        }
+    }
+
+    /// Returns the syscall arguments `a0` … `a3` of a `SYSCALL`
+    /// record in order.
+    pub fn args(&self) -> [Option<u64>; 4] {
+        let mut args = [None; 4];
+        for (k, v) in &self.elems {
+            if let (Key::Arg(n @ 0..=3, None), Value::Number(num)) = (k, v) {
+                args[*n as usize] = num.as_u64();
+            }
+        }
+        args
+    }
+
+    /// Returns the command line arguments of an `EXECVE` record in
+    /// order. Arguments that have been split into several `aX[Y]`
+    /// fields are joined; values that have been replaced with
+    /// [`Value::Skipped`] are returned as empty strings.
+    pub fn execve_args(&self) -> Vec<Vec<u8>> {
+        let mut args: Vec<Vec<u8>> = vec![];
+        self.collect_execve_args(&mut args);
+        args
+    }
+
+    /// Appends the command line arguments of an `EXECVE` record to
+    /// `args`, see [`Event::execve_args`].
+    pub(crate) fn collect_execve_args(&self, args: &mut Vec<Vec<u8>>) {
+        for (k, v) in &self.elems {
+            let n = match (k, v) {
+                (Key::Arg(_, _), Value::Number(_)) => continue,
+                (Key::Arg(n, _), _) => *n as usize,
+                _ => continue,
+            };
+            if args.len() <= n {
+                args.resize(n + 1, vec![]);
+            }
+            if let Ok(b) = Vec::<u8>::try_from(v.clone()) {
+                args[n].extend(b);
+            }
+        }
+    }
+
+    /// Returns the number of heap bytes allocated by the `Body`: the
+    /// entry table and the arena holding string values. Allocations
+    /// made by [`Value::Owned`], [`Value::List`], [`Value::Map`] are
+    /// not included. Storage shared with clones is counted for every
+    /// clone.
+    pub fn memory_usage(&self) -> usize {
+        self.elems.capacity() * std::mem::size_of::<(Key, Value)>()
+            + self.arena.iter().map(|b| b.capacity()).sum::<usize>()
+    }
+
+    /// Returns the number of bytes used in the arena.
+    pub(crate) fn arena_bytes(&self) -> usize {
+        self.arena.iter().map(|b| b.len()).sum()
+    }
+
+    /// Returns the capacity for entries and the capacities of the
+    /// arena buffers.
+    #[cfg(test)]
+    pub(crate) fn capacity(&self) -> (usize, Vec<usize>) {
+        let arena = self.arena.iter().map(|b| b.capacity()).collect();
+        (self.elems.capacity(), arena)
+    }
+
     /// Reserves capacity for at least `additional` more elements.
     pub fn reserve(&mut self, additional: usize) {
         self.elems.reserve(additional);
@@ -166,21 +388,238 @@ impl Body<'_> {
 
 impl<'a> Body<'a> {
     /// Retains only the elements specified by the predicate.
-    pub fn retain<F>(&mut self, f: F)
+    pub fn retain<F>(&mut self, mut f: F)
     where
         F: FnMut(&(Key, Value<'a>)) -> bool,
     {
-        self.elems.retain(f)
+        if self.sections.is_empty() {
+            return self.elems.retain(f);
+        }
+        let mut kept = Vec::with_capacity(self.elems.len());
+        self.elems.retain(|e| {
+            let keep = f(e);
+            kept.push(keep);
+            keep
+        });
+        self.update_sections(&kept);
+    }
+
+    /// Moves section boundaries after elements have been removed.
+    /// `kept` has an entry for every former element.
+    fn update_sections(&mut self, kept: &[bool]) {
+        let mut removed = 0;
+        let mut pos = 0;
+        for start in self.sections.iter_mut() {
+            removed += kept[pos..*start].iter().filter(|k| !**k).count();
+            pos = *start;
+            *start -= removed;
+        }
+    }
+
+    /// Returns the elements grouped into contiguous sections: one for
+    /// the original elements and one for each `Body` added by
+    /// [`Body::extend`], in order. Sections can be empty. This allows
+    /// to reconstruct per-record grouping after the bodies of
+    /// several records have been combined.
+    pub fn sections(&self) -> impl Iterator<Item = &[(Key, Value<'a>)]> {
+        let starts = std::iter::once(0).chain(self.sections.iter().copied());
+        let ends = self
+            .sections
+            .iter()
+            .copied()
+            .chain(std::iter::once(self.elems.len()));
+        starts.zip(ends).map(|(start, end)| &self.elems[start..end])
+    }
+
+    /// Transforms or removes elements in one pass. Keys and values
+    /// returned by `f` replace the original element; `None` removes
+    /// it.
+    ///
+    /// String values that already refer to the `Body`'s storage are
+    /// kept in place, other strings are copied.
+    pub fn map_entries<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Key, Value<'a>) -> Option<(Key, Value<'a>)>,
+    {
+        let elems = std::mem::take(&mut self.elems);
+        let track = !self.sections.is_empty();
+        let mut kept = vec![];
+        self.elems = elems
+            .into_iter()
+            .filter_map(|(k, v)| {
+                let e = f(k, v);
+                if track {
+                    kept.push(e.is_some());
+                }
+                e
+            })
+            .map(|(k, v)| (k, self.add_value(v)))
+            .collect();
+        if track {
+            self.update_sections(&kept);
+        }
+    }
+}
+
+impl<'a> Body<'a> {
+    /// Returns the body with `'static` lifetime, e.g. to send it to
+    /// another thread or to keep it in a long-lived data structure.
+    ///
+    /// String values are copied into [`Value::Owned`], so that they
+    /// remain valid when they are taken out of the body.
+    pub fn into_owned(self) -> Body<'static> {
+        Body {
+            elems: self
+                .elems
+                .into_iter()
+                .map(|(k, v)| (k, v.into_owned()))
+                .collect(),
+            arena: vec![],
+            sections: self.sections,
+            _pin: std::marker::PhantomPinned,
+        }
+    }
+
+    /// Converts the body into another representation, see
+    /// [`FromAuditBody`].
+    pub fn convert<'b, T: FromAuditBody<'b>>(&'b self) -> T {
+        T::from_audit_body(self)
+    }
+}
+
+/// Conversion from a borrowed [`Body`] into other representations,
+/// e.g. the extended value types of
+/// [Laurel](https://github.com/threathunters-io/laurel)
+///
+/// The result may borrow keys and string values from the `Body`
+/// instead of copying them. Types that need to own their data can
+/// copy values as the implementation for `Vec<(String, OwnedValue)>`
+/// does, or convert a body that has been detached from the input
+/// using [`Body::into_owned`].
+pub trait FromAuditBody<'b>: Sized {
+    fn from_audit_body(body: &'b Body<'b>) -> Self;
+}
+
+/// Borrows all entries.
+impl<'b> FromAuditBody<'b> for Vec<(&'b Key, &'b Value<'b>)> {
+    fn from_audit_body(body: &'b Body<'b>) -> Self {
+        body.elems.iter().map(|(k, v)| (k, v)).collect()
+    }
+}
+
+/// Copies all entries, see [`Body::to_owned_pairs`].
+impl<'b> FromAuditBody<'b> for Vec<(String, OwnedValue)> {
+    fn from_audit_body(body: &'b Body<'b>) -> Self {
+        body.to_owned_pairs()
+    }
+}
+
+/// Index over the keys of a [`Body`], see [`Body::index`]
+pub struct BodyIndex<'b> {
+    elems: &'b [(Key, Value<'b>)],
+    keys: Vec<(Vec<u8>, usize)>,
+}
+
+impl Debug for BodyIndex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyIndex")
+            .field("len", &self.keys.len())
+            .finish()
+    }
+}
+
+impl<'b> BodyIndex<'b> {
+    /// Retrieves the first value found for a given `key`, like
+    /// [`Body::get`].
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&'b Value<'b>> {
+        let key = key.as_ref();
+        let i = self.keys.partition_point(|(k, _)| k.as_slice() < key);
+        match self.keys.get(i) {
+            Some((k, pos)) if k == key => Some(&self.elems[*pos].1),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the body contains `key`.
+    pub fn contains<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+/// View of selected entries of a [`Body`], see [`Body::slice`]
+#[derive(Clone)]
+pub struct BodySlice<'b> {
+    body: &'b Body<'b>,
+    positions: Vec<usize>,
+}
+
+impl Debug for BodySlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut seq = f.debug_struct("BodySlice");
+        for (k, v) in self.iter() {
+            seq.field(&k.to_string(), &v);
+        }
+        seq.finish()
     }
 }
 
+impl<'b> BodySlice<'b> {
+    /// Returns the entries in the view, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &'b (Key, Value<'b>)> + '_ {
+        self.positions.iter().map(|i| &self.body.elems[*i])
+    }
+
+    /// Retrieves the first value found for a given `key`, like
+    /// [`Body::get`].
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&'b Value<'b>> {
+        let key = key.as_ref();
+        self.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the number of entries in the view.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if the view contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns a [`Body`] with the entries in the view. String
+    /// values are not copied, the storage is shared with the
+    /// original `Body`.
+    pub fn to_body(&self) -> Body<'b> {
+        Body {
+            elems: self.iter().cloned().collect(),
+            arena: self.body.arena.clone(),
+            sections: vec![],
+            _pin: std::marker::PhantomPinned,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for BodySlice<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.len()))?;
+        for (k, v) in self.iter() {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+}
+
+/// Cloning is cheap: String values are not copied, the storage is
+/// shared between clones.
 impl Clone for Body<'_> {
     fn clone(&self) -> Self {
-        let mut new = Body::default();
-        self.into_iter()
-            .cloned()
-            .for_each(|(k, v)| new.push((k, v)));
-        new
+        Body {
+            elems: self.elems.clone(),
+            arena: self.arena.clone(),
+            sections: self.sections.clone(),
+            _pin: std::marker::PhantomPinned,
+        }
     }
 }
 
@@ -194,7 +633,7 @@ impl<'a> IntoIterator for &'a Body<'a> {
 
 pub struct BodyIterator<'a> {
     iter: std::vec::IntoIter<(Key, Value<'a>)>,
-    _arena: Vec<Vec<u8>>,
+    _arena: Vec<Arc<Vec<u8>>>,
     _pin: std::marker::PhantomPinned,
 }
 
@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "spill")]
+use std::io;
+
+use crate::*;
+
+/// Node name and identifier of an event
+pub(crate) type EventKey = (Option<Vec<u8>>, EventID);
+
+/// Decides how [`Message`]s are grouped into [`Event`]s by the
+/// [`EventCoalescer`]
+pub trait MultipartPolicy {
+    /// True if messages of type `ty` are part of events that consist
+    /// of multiple records and are collected until the event ends
+    fn is_multipart(&self, ty: MessageType) -> bool;
+    /// True if a message of type `ty` marks the end of an event. The
+    /// message itself is not added to the event.
+    fn ends_event(&self, ty: MessageType) -> bool;
+}
+
+/// Grouping as done by _auparse(3)_: Records of the types covered by
+/// [`MessageType::is_multipart`] are collected until `EOE`, all
+/// other records form single-record events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuparsePolicy;
+
+impl MultipartPolicy for AuparsePolicy {
+    fn is_multipart(&self, ty: MessageType) -> bool {
+        ty.is_multipart()
+    }
+
+    fn ends_event(&self, ty: MessageType) -> bool {
+        ty == MessageType::EOE
+    }
+}
+
+/// Strict grouping: All records are collected until `EOE`,
+/// regardless of their type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EoeOnlyPolicy;
+
+impl MultipartPolicy for EoeOnlyPolicy {
+    fn is_multipart(&self, _: MessageType) -> bool {
+        true
+    }
+
+    fn ends_event(&self, ty: MessageType) -> bool {
+        ty == MessageType::EOE
+    }
+}
+
+/// Callbacks invoked by the [`EventCoalescer`], e.g. for metrics or
+/// for storing incomplete events
+///
+/// The implementing type carries the embedder's state, see
+/// [`EventCoalescer::hooks`]. All methods do nothing by default; `()`
+/// is used if no hooks are set.
+pub trait CoalescerHooks {
+    /// Called for every complete event before it is returned
+    fn on_complete(&mut self, _ev: &Event) {}
+    /// Called for every incomplete event returned by
+    /// [`EventCoalescer::expire`] or [`EventCoalescer::flush`]
+    fn on_expired(&mut self, _ev: &Event) {}
+    /// Called for a record that ends an event (`EOE`) which has not
+    /// been seen
+    fn on_orphan_record(&mut self, _msg: &Message) {}
+    /// Called if reading or writing the [`SpillStore`] fails. The
+    /// records involved are kept in memory if possible.
+    #[cfg(feature = "spill")]
+    fn on_spill_error(&mut self, _err: &io::Error) {}
+}
+
+impl CoalescerHooks for () {}
+
+/// Groups [`Message`]s that share node name and [`EventID`] into
+/// [`Event`]s
+#[derive(Debug, Default)]
+pub struct EventCoalescer<'a, P: MultipartPolicy = AuparsePolicy, H: CoalescerHooks = ()> {
+    policy: P,
+    inflight: HashMap<(Option<Vec<u8>>, EventID), Event<'a>, EventIDBuildHasher>,
+    resolve_paths: bool,
+    hooks: H,
+    #[cfg(feature = "spill")]
+    spill: Option<Spill>,
+    timeout: Option<u64>,
+    max_gap: Option<u32>,
+    /// Highest timestamp and sequence number seen per node
+    latest: HashMap<Option<Vec<u8>>, (u64, u32)>,
+}
+
+#[cfg(feature = "spill")]
+#[derive(Debug)]
+struct Spill {
+    store: SpillStore,
+    max_inflight: usize,
+    events: HashSet<EventKey>,
+    /// Last message pushed, for [`Checkpoint::last`]
+    last: Option<EventID>,
+    /// Once a checkpoint has been taken, segments of events that
+    /// have been read back are only removed at the next checkpoint.
+    checkpointed: bool,
+    removed: Vec<EventKey>,
+}
+
+#[cfg(feature = "spill")]
+impl Spill {
+    /// Moves the records of `ev` to the store.
+    fn append_event(&mut self, key: EventKey, ev: &Event) -> io::Result<()> {
+        if let Some(i) = self.removed.iter().position(|k| *k == key) {
+            self.store.remove(&key.0, key.1)?;
+            self.removed.swap_remove(i);
+        }
+        self.store.append_event(ev)?;
+        self.events.insert(key);
+        Ok(())
+    }
+}
+
+impl<'a> EventCoalescer<'a> {
+    /// Constructs a coalescer that uses [`AuparsePolicy`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a, P: MultipartPolicy> EventCoalescer<'a, P> {
+    /// Constructs a coalescer that uses `policy`.
+    pub fn with_policy(policy: P) -> Self {
+        EventCoalescer {
+            policy,
+            inflight: HashMap::default(),
+            resolve_paths: false,
+            hooks: (),
+            #[cfg(feature = "spill")]
+            spill: None,
+            timeout: None,
+            max_gap: None,
+            latest: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, P: MultipartPolicy, H: CoalescerHooks> EventCoalescer<'a, P, H> {
+    /// Replaces the [`CoalescerHooks`]. Incomplete events are kept.
+    pub fn with_hooks<H2: CoalescerHooks>(self, hooks: H2) -> EventCoalescer<'a, P, H2> {
+        EventCoalescer {
+            policy: self.policy,
+            inflight: self.inflight,
+            resolve_paths: self.resolve_paths,
+            hooks,
+            #[cfg(feature = "spill")]
+            spill: self.spill,
+            timeout: self.timeout,
+            max_gap: self.max_gap,
+            latest: self.latest,
+        }
+    }
+
+    /// Returns the hooks, e.g. to read collected metrics.
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    /// Returns the hooks.
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// If set, [`Event::resolve_paths`] is applied to all events
+    /// that are returned.
+    pub fn resolve_paths(mut self, resolve_paths: bool) -> Self {
+        self.resolve_paths = resolve_paths;
+        self
+    }
+
+    /// Sets the time in milliseconds after which an incomplete event
+    /// is returned by [`expire`](Self::expire). Time is measured by
+    /// the timestamps of the messages seen from the same node.
+    pub fn timeout(mut self, timeout_ms: u64) -> Self {
+        self.timeout = Some(timeout_ms);
+        self
+    }
+
+    /// Sets the number of sequence numbers after which an incomplete
+    /// event is returned by [`expire`](Self::expire), i.e. once a
+    /// message from the same node whose sequence number is higher by
+    /// more than `max_gap` has been seen.
+    pub fn max_sequence_gap(mut self, max_gap: u32) -> Self {
+        self.max_gap = Some(max_gap);
+        self
+    }
+
+    /// Moves incomplete events to `store` while more than
+    /// `max_inflight` events are kept in memory, oldest first.
+    /// Further records of such events are appended to the store, the
+    /// events are read back when they are complete or flushed.
+    ///
+    /// Events that are found in `store` are treated as incomplete
+    /// events, so that events that were incomplete when a previous
+    /// process ended are recovered. To resume at a [`Checkpoint`]
+    /// instead, use [`resume`](Self::resume).
+    #[cfg(feature = "spill")]
+    pub fn spill(mut self, store: SpillStore, max_inflight: usize) -> io::Result<Self> {
+        let events = store.events()?.into_iter().collect();
+        self.spill = Some(Spill {
+            store,
+            max_inflight,
+            events,
+            last: None,
+            checkpointed: false,
+            removed: vec![],
+        });
+        Ok(self)
+    }
+
+    /// Like [`spill`](Self::spill), but first brings `store` back to
+    /// the state of `checkpoint`. Reading the input has to resume at
+    /// [`Checkpoint::offset`].
+    #[cfg(feature = "spill")]
+    pub fn resume(
+        self,
+        store: SpillStore,
+        max_inflight: usize,
+        checkpoint: &Checkpoint,
+    ) -> io::Result<Self> {
+        store.restore(checkpoint)?;
+        let mut c = self.spill(store, max_inflight)?;
+        if let Some(spill) = &mut c.spill {
+            spill.last = checkpoint.last;
+            spill.checkpointed = true;
+        }
+        Ok(c)
+    }
+
+    /// Moves all incomplete events to the [`SpillStore`], flushes the
+    /// store to disk, and returns a [`Checkpoint`] for `offset`, the
+    /// position in the input after the last message that has been
+    /// pushed.
+    ///
+    /// Once the events returned so far have been handled, the
+    /// checkpoint can be saved. After a crash, no message before
+    /// `offset` is lost or returned twice if the coalescer is set up
+    /// using [`resume`](Self::resume). To make this possible, segments
+    /// of events that are read back from the store are kept until the
+    /// next checkpoint.
+    ///
+    /// Fails if no store has been set using [`spill`](Self::spill) or
+    /// if writing to the store fails; events that have not been
+    /// written are kept in memory.
+    #[cfg(feature = "spill")]
+    pub fn checkpoint(&mut self, offset: u64) -> io::Result<Checkpoint> {
+        let Some(spill) = &mut self.spill else {
+            return Err(io::Error::other("no spill store"));
+        };
+        let keys: Vec<_> = self.inflight.keys().cloned().collect();
+        for key in keys {
+            spill.append_event(key.clone(), &self.inflight[&key])?;
+            self.inflight.remove(&key);
+        }
+        while let Some((node, id)) = spill.removed.last() {
+            spill.store.remove(node, *id)?;
+            spill.removed.pop();
+        }
+        spill.checkpointed = true;
+        Ok(Checkpoint {
+            offset,
+            last: spill.last,
+            segments: spill.store.sync_segments(&spill.events)?,
+        })
+    }
+
+    #[cfg(feature = "spill")]
+    fn spill_oldest(&mut self) {
+        let Some(spill) = &mut self.spill else {
+            return;
+        };
+        while self.inflight.len() > spill.max_inflight {
+            let Some(key) = self
+                .inflight
+                .keys()
+                .min_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)))
+                .cloned()
+            else {
+                return;
+            };
+            // On failure, the segment has been restored, so the event
+            // stays in memory and records stored before are kept.
+            if let Err(e) = spill.append_event(key.clone(), &self.inflight[&key]) {
+                self.hooks.on_spill_error(&e);
+                return;
+            }
+            self.inflight.remove(&key);
+        }
+    }
+
+    #[cfg(feature = "spill")]
+    fn take_spilled(&mut self, key: &EventKey) -> Option<Event<'a>> {
+        let spill = self.spill.as_mut()?;
+        if !spill.events.remove(key) {
+            return None;
+        }
+        let ev = if spill.checkpointed {
+            spill.removed.push(key.clone());
+            spill.store.read(&key.0, key.1)
+        } else {
+            spill.store.take(&key.0, key.1)
+        };
+        match ev {
+            Ok(ev) => ev,
+            Err(e) => {
+                self.hooks.on_spill_error(&e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn take_spilled(&mut self, _key: &EventKey) -> Option<Event<'a>> {
+        None
+    }
+
+    /// Removes an incomplete event, merging the parts in the store
+    /// and in memory.
+    fn take(&mut self, key: &EventKey) -> Option<Event<'a>> {
+        match (self.take_spilled(key), self.inflight.remove(key)) {
+            (Some(mut ev), Some(rest)) => {
+                ev.records.extend(rest.records);
+                Some(ev)
+            }
+            (ev, None) | (None, ev) => ev,
+        }
+    }
+
+    fn resolve(&self, mut ev: Event<'a>) -> Event<'a> {
+        if self.resolve_paths {
+            ev.resolve_paths();
+        }
+        ev
+    }
+
+    fn finish(&mut self, ev: Event<'a>) -> Event<'a> {
+        let ev = self.resolve(ev);
+        self.hooks.on_complete(&ev);
+        ev
+    }
+
+    /// Adds a message. Returns the event the message belongs to if
+    /// it is complete.
+    pub fn push(&mut self, msg: Message<'a>) -> Option<Event<'a>> {
+        let key = (msg.node.clone(), msg.id);
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &mut self.spill {
+            spill.last = Some(msg.id);
+        }
+        if self.timeout.is_some() || self.max_gap.is_some() {
+            let latest = self.latest.entry(msg.node.clone()).or_default();
+            latest.0 = latest.0.max(msg.id.timestamp);
+            latest.1 = latest.1.max(msg.id.sequence);
+        }
+        if self.policy.ends_event(msg.ty) {
+            let Some(ev) = self.take(&key) else {
+                self.hooks.on_orphan_record(&msg);
+                return None;
+            };
+            return Some(self.finish(ev));
+        }
+        if !self.policy.is_multipart(msg.ty) {
+            let mut ev = Event::new(msg.id, msg.node.clone());
+            ev.push(msg);
+            return Some(self.finish(ev));
+        }
+        // Records of events that have been moved to the store are
+        // appended there, unless earlier records are still in memory
+        // after a failed write.
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            if spill.events.contains(&key) && !self.inflight.contains_key(&key) {
+                match spill.store.append(&msg.node, msg.id, msg.ty, &msg.body) {
+                    Ok(()) => return None,
+                    Err(e) => self.hooks.on_spill_error(&e),
+                }
+            }
+        }
+        self.inflight
+            .entry(key)
+            .or_insert_with(|| Event::new(msg.id, msg.node.clone()))
+            .push(msg);
+        #[cfg(feature = "spill")]
+        self.spill_oldest();
+        None
+    }
+
+    /// Parses `raw` using `parser` and adds the resulting message, see
+    /// [`push`](Self::push).
+    pub fn push_line(
+        &mut self,
+        parser: &Parser,
+        raw: &[u8],
+    ) -> Result<Option<Event<'a>>, ParseError> {
+        Ok(self.push(parser.parse(raw)?))
+    }
+
+    fn keys(&self) -> HashSet<EventKey> {
+        let keys = self.inflight.keys().cloned();
+        #[cfg(feature = "spill")]
+        let keys = keys.chain(self.spill.iter().flat_map(|s| s.events.iter().cloned()));
+        keys.collect()
+    }
+
+    fn take_all(&mut self, keys: impl IntoIterator<Item = EventKey>) -> Vec<Event<'a>> {
+        let mut events = vec![];
+        for key in keys {
+            if let Some(ev) = self.take(&key) {
+                events.push(self.resolve(ev));
+            }
+        }
+        events.sort_by(|a, b| (a.id, &a.node).cmp(&(b.id, &b.node)));
+        for ev in &events {
+            self.hooks.on_expired(ev);
+        }
+        events
+    }
+
+    /// Returns incomplete events that have exceeded the
+    /// [`timeout`](Self::timeout) or the
+    /// [`max_sequence_gap`](Self::max_sequence_gap), ordered by
+    /// [`EventID`]. This should be called regularly, e.g. after every
+    /// [`push`](Self::push); without timeout and gap, nothing is
+    /// returned.
+    ///
+    /// ```
+    /// # use linux_audit_parser::*;
+    /// let parser = Parser::default();
+    /// let mut c = EventCoalescer::new().max_sequence_gap(1);
+    /// let lines = [
+    ///     &b"type=SYSCALL msg=audit(1.000:10): syscall=59\n"[..],
+    ///     &b"type=SYSCALL msg=audit(1.000:11): syscall=59\n"[..],
+    ///     &b"type=SYSCALL msg=audit(1.000:12): syscall=59\n"[..],
+    /// ];
+    /// for line in lines {
+    ///     assert!(c.push_line(&parser, line).unwrap().is_none());
+    /// }
+    /// let expired = c.expire();
+    /// assert_eq!(expired.len(), 1);
+    /// assert_eq!(expired[0].id.sequence, 10);
+    /// assert_eq!(c.len(), 2);
+    /// ```
+    pub fn expire(&mut self) -> Vec<Event<'a>> {
+        if self.timeout.is_none() && self.max_gap.is_none() {
+            return vec![];
+        }
+        let keys: Vec<_> = self
+            .keys()
+            .into_iter()
+            .filter(|(node, id)| {
+                let Some((ts, seq)) = self.latest.get(node) else {
+                    return false;
+                };
+                self.timeout
+                    .is_some_and(|t| ts.saturating_sub(id.timestamp) > t)
+                    || self
+                        .max_gap
+                        .is_some_and(|g| seq.saturating_sub(id.sequence) > g)
+            })
+            .collect();
+        self.take_all(keys)
+    }
+
+    /// Returns all incomplete events, ordered by [`EventID`].
+    pub fn flush(&mut self) -> Vec<Event<'a>> {
+        let keys = self.keys();
+        self.take_all(keys)
+    }
+
+    /// Returns the number of incomplete events, including events
+    /// that have been moved to the `SpillStore`.
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            // After a failed write, records of an event can be both in
+            // the store and in memory.
+            let spilled = spill.events.iter();
+            return self.inflight.len()
+                + spilled.filter(|k| !self.inflight.contains_key(*k)).count();
+        }
+        self.inflight.len()
+    }
+
+    /// Returns `true` if there are no incomplete events.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,136 @@
+use std::borrow::Cow;
+
+use crate::{parse_header, MessageType};
+
+/// Free-text words that precede the fields of old records, e.g.
+/// `user pid=…`, `login pid=…`, `… by auid=…`
+const FILLER_WORDS: &[&[u8]] = &[b"user", b"login", b"by"];
+
+/// Splits `s` at spaces outside of single or double quotes.
+fn tokens(s: &[u8]) -> Vec<&[u8]> {
+    let mut tokens = vec![];
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.iter().enumerate() {
+        match (quote, c) {
+            (None, b'"' | b'\'') => quote = Some(*c),
+            (Some(q), c) if q == *c => quote = None,
+            (None, b' ') => {
+                if start < i {
+                    tokens.push(&s[start..i]);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < s.len() {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
+/// Returns `true` for `key=value` tokens.
+fn is_kv(tok: &[u8]) -> bool {
+    match tok.iter().position(|c| *c == b'=') {
+        Some(n) if n > 0 => {
+            tok[0].is_ascii_alphabetic()
+                && tok[..n]
+                    .iter()
+                    .all(|c| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'-')
+        }
+        _ => false,
+    }
+}
+
+/// Rewrites the contents of a `msg='…'` string: Leading text such as
+/// `PAM: authentication` becomes an `op=` field, separators (`:`),
+/// parentheses, and commas around fields are dropped.
+fn rewrite_msg(s: &[u8]) -> Vec<u8> {
+    let mut op = vec![];
+    let mut fields: Vec<&[u8]> = vec![];
+    for tok in tokens(s) {
+        let mut tok = tok.strip_prefix(b"(").unwrap_or(tok);
+        if is_kv(tok) {
+            tok = tok.strip_suffix(b")").unwrap_or(tok);
+            tok = tok.strip_suffix(b",").unwrap_or(tok);
+            tok = tok.strip_suffix(b":").unwrap_or(tok);
+        }
+        if tok.is_empty() || tok == b":" {
+            continue;
+        }
+        if fields.is_empty() && !is_kv(tok) {
+            if !op.is_empty() && !op.ends_with(b":") {
+                op.push(b'_');
+            }
+            op.extend(tok);
+        } else {
+            fields.push(tok);
+        }
+    }
+    if fields.is_empty() {
+        return s.to_vec();
+    }
+    let mut out = vec![];
+    if !op.is_empty() {
+        out.extend(b"op=");
+        out.extend(op);
+    }
+    for tok in fields {
+        if !out.is_empty() {
+            out.push(b' ');
+        }
+        out.extend(tok);
+    }
+    out
+}
+
+/// Rewrites a line in the formats written by audit 1.x and RHEL
+/// 5/6-era kernels into the current format, see [`Parser::compat`].
+/// Returns `None` if nothing needed to be changed.
+///
+/// [`Parser::compat`]: crate::Parser::compat
+pub(crate) fn rewrite_legacy(raw: &[u8]) -> Option<Vec<u8>> {
+    let (_, ty, _, offset) = parse_header(raw).ok()?;
+    // Free-text messages from SELinux-aware programs
+    let split_msg = ty != MessageType::USER_AVC && ty != MessageType::USER_SELINUX_ERR;
+    let (header, body) = raw.split_at(offset);
+    let (body, nl) = match body.strip_suffix(b"\n") {
+        Some(body) => (body, &b"\n"[..]),
+        None => (body, &b""[..]),
+    };
+    let toks = tokens(body);
+    let mut out: Vec<Cow<[u8]>> = Vec::with_capacity(toks.len());
+    let mut changed = false;
+    let mut i = 0;
+    while i < toks.len() {
+        let tok = toks[i];
+        let next_kv = toks.get(i + 1).is_some_and(|t| is_kv(t));
+        if next_kv && FILLER_WORDS.contains(&tok) {
+            changed = true;
+        } else if next_kv && (tok == b"old" || tok == b"new") {
+            let next = toks[i + 1];
+            out.push(match tok {
+                b"old" => Cow::Owned([b"old-", next].concat()),
+                _ => Cow::Borrowed(next),
+            });
+            changed = true;
+            i += 1;
+        } else if let Some(msg) = tok
+            .strip_prefix(b"msg='")
+            .and_then(|s| s.strip_suffix(b"'"))
+            .filter(|_| split_msg)
+        {
+            let rewritten = rewrite_msg(msg);
+            changed |= rewritten != msg;
+            out.push(Cow::Owned([b"msg='", &rewritten[..], b"'"].concat()));
+        } else {
+            out.push(Cow::Borrowed(tok));
+        }
+        i += 1;
+    }
+    if !changed {
+        return None;
+    }
+    Some([header, &out.join(&b' ')[..], nl].concat())
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::*;
+
+/// A line from a corpus that could not be parsed
+#[derive(Debug)]
+pub struct CorpusFailure {
+    /// The file containing the line
+    pub path: PathBuf,
+    /// Line number, starting at 1
+    pub line: usize,
+    pub error: ParseError,
+}
+
+/// Result of [`Parser::check_corpus`]
+#[derive(Debug, Default)]
+pub struct CorpusReport {
+    /// Number of files read
+    pub files: usize,
+    /// Number of non-empty lines parsed
+    pub lines: usize,
+    pub failures: Vec<CorpusFailure>,
+}
+
+impl CorpusReport {
+    /// True if all lines have been parsed successfully.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Parser {
+    /// Parses every line in every file below `path`, which may be a
+    /// single file or a directory, and collects parse failures.
+    ///
+    /// This is intended for testing the parser against collections of
+    /// sample logs, such as those shipped with _audit-userspace_.
+    pub fn check_corpus<P: AsRef<Path>>(&self, path: P) -> io::Result<CorpusReport> {
+        let mut report = CorpusReport::default();
+        self.check_corpus_path(path.as_ref(), &mut report)?;
+        Ok(report)
+    }
+
+    fn check_corpus_path(&self, path: &Path, report: &mut CorpusReport) -> io::Result<()> {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.sort();
+            for entry in entries {
+                self.check_corpus_path(&entry, report)?;
+            }
+            return Ok(());
+        }
+        let buf = fs::read(path)?;
+        report.files += 1;
+        for (n, line) in buf.split_inclusive(|c| *c == b'\n').enumerate() {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            report.lines += 1;
+            if let Err(error) = self.parse(line) {
+                report.failures.push(CorpusFailure {
+                    path: path.to_path_buf(),
+                    line: n + 1,
+                    error,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::*;
+
+/// View on records written by _auditd(8)_ about its own state, such
+/// as `DAEMON_START` or `DAEMON_END`.
+///
+/// These records are timestamped by the audit daemon, not by the
+/// kernel: the [`EventID`] timestamp is the daemon's own clock
+/// reading. Comparing it to the timestamps of surrounding records is
+/// a cheap way to spot clock changes around daemon restarts, see
+/// [`ClockMonitor`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DaemonRecord<'a> {
+    /// Identifier of the record
+    pub id: EventID,
+    /// A second timestamp (`time=…`) in milliseconds since the
+    /// epoch, UTC. The parser accepts seconds since the epoch and
+    /// RFC 3339 dates with UTC offset.
+    ///
+    /// The audit daemon shipped with audit-userspace (up to and
+    /// including 4.x) does not write this field; it is only present
+    /// in logs from other producers that add their own clock reading,
+    /// e.g. forwarders that re-stamp records. For records written by
+    /// _auditd(8)_ it is `None`.
+    pub time: Option<i64>,
+    /// One of the `DAEMON_*` message types
+    pub ty: MessageType,
+    /// Operation, e.g. `start`, `terminate`, `rotate-logs`
+    pub op: Option<&'a [u8]>,
+    /// Version of the audit daemon (`ver=…`, `DAEMON_START` only)
+    pub version: Option<&'a [u8]>,
+    /// Log format (`format=…`, `DAEMON_START` only)
+    pub format: Option<&'a [u8]>,
+    /// Kernel release (`kernel=…`, `DAEMON_START` only)
+    pub kernel: Option<&'a [u8]>,
+    /// Process ID of the audit daemon or of the process that caused
+    /// the state change
+    pub pid: Option<i64>,
+    /// Result, usually `success` or `failed`
+    pub res: Option<&'a [u8]>,
+    /// Configuration state after reloading (`state=…`,
+    /// `DAEMON_CONFIG` only), e.g. `changed` or `no-change`
+    pub state: Option<&'a [u8]>,
+    /// Error description: a `reason=…` field, or the free text in
+    /// front of the key/value pairs that older versions of the audit
+    /// daemon write, e.g. `auditd error halt`
+    pub reason: Option<&'a [u8]>,
+}
+
+impl<'a> DaemonRecord<'a> {
+    /// Builds a view on `msg`, returns `None` if `msg` is not a
+    /// `DAEMON_*` record.
+    pub fn from_message(msg: &'a Message) -> Option<Self> {
+        if !msg.ty.is_daemon() {
+            return None;
+        }
+        let body = &msg.body;
+        Some(DaemonRecord {
+            id: msg.id,
+            time: match body.get("time") {
+                Some(Value::Number(Number::Dec(n))) => Some(*n),
+                _ => None,
+            },
+            ty: msg.ty,
+            op: body.get("op").and_then(value_bytes),
+            version: body.get("ver").and_then(value_bytes),
+            format: body.get("format").and_then(value_bytes),
+            kernel: body.get("kernel").and_then(value_bytes),
+            pid: match body.get("pid") {
+                Some(Value::Number(Number::Dec(n))) => Some(*n),
+                _ => None,
+            },
+            res: body.get("res").and_then(value_bytes),
+            state: body.get("state").and_then(value_bytes),
+            reason: body.get("reason").and_then(value_bytes),
+        })
+    }
+
+    /// True for `DAEMON_ABORT` and `DAEMON_ERR` records, and for
+    /// records with `res=failed`.
+    pub fn is_failure(&self) -> bool {
+        matches!(self.ty, MessageType::DAEMON_ABORT | MessageType::DAEMON_ERR)
+            || self.res == Some(b"failed")
+    }
+
+    /// Difference between the [`EventID`] timestamp and
+    /// [`time`](Self::time) in milliseconds, positive if `time` is
+    /// behind, negative if it is ahead.
+    /// Returns `None` if the record has no `time=` field.
+    pub fn drift_ms(&self) -> Option<i64> {
+        Some(signed_ms(self.id.timestamp).saturating_sub(self.time?))
+    }
+}
+
+/// The timestamp a record's [`EventID`] is compared against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockReference {
+    /// The previous record
+    Previous(EventID),
+    /// The `time=` field of a `DAEMON_*` record, see
+    /// [`DaemonRecord::time`]
+    Daemon(i64),
+}
+
+impl ClockReference {
+    /// Returns the timestamp in milliseconds since the epoch.
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            ClockReference::Previous(id) => signed_ms(id.timestamp),
+            ClockReference::Daemon(ts) => *ts,
+        }
+    }
+}
+
+/// A discontinuity between the timestamp of a record and that of the
+/// previous record or the daemon's own clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockJump {
+    pub reference: ClockReference,
+    pub current: EventID,
+}
+
+impl ClockJump {
+    /// Difference between the two timestamps in milliseconds,
+    /// negative if the clock went backwards.
+    pub fn delta_ms(&self) -> i64 {
+        signed_ms(self.current.timestamp).saturating_sub(self.reference.timestamp())
+    }
+
+    /// True if the clock went backwards.
+    pub fn is_backward(&self) -> bool {
+        self.delta_ms() < 0
+    }
+}
+
+/// Detects clock jumps between consecutive records of a log, and
+/// drift between [`EventID`] and `time=` timestamps of `DAEMON_*`
+/// records.
+///
+/// Records in a log file are not strictly ordered by timestamp since
+/// kernel and userspace records are interleaved, hence the
+/// tolerance for backward steps.
+#[derive(Debug, Clone)]
+pub struct ClockMonitor {
+    /// Backward steps up to this many milliseconds are not reported.
+    /// Default: 1000
+    pub backward_tolerance: u64,
+    /// Forward steps of more than this many milliseconds are
+    /// reported. Default: 3600000 (1 hour)
+    pub forward_threshold: u64,
+    /// Differences between [`EventID`] and `time=` timestamp of up to
+    /// this many milliseconds are not reported. Default: 1000
+    pub drift_tolerance: u64,
+    last: Option<EventID>,
+}
+
+impl Default for ClockMonitor {
+    fn default() -> Self {
+        Self {
+            backward_tolerance: 1000,
+            forward_threshold: 3600 * 1000,
+            drift_tolerance: 1000,
+            last: None,
+        }
+    }
+}
+
+impl ClockMonitor {
+    /// Records `id` as the most recently seen record identifier and
+    /// returns a [`ClockJump`] if its timestamp is out of the
+    /// tolerated range.
+    pub fn observe(&mut self, id: EventID) -> Option<ClockJump> {
+        let previous = self.last.replace(id)?;
+        let jump = ClockJump {
+            reference: ClockReference::Previous(previous),
+            current: id,
+        };
+        let delta = jump.delta_ms();
+        if (delta < 0 && delta.unsigned_abs() > self.backward_tolerance)
+            || (delta > 0 && delta as u64 > self.forward_threshold)
+        {
+            Some(jump)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a [`ClockJump`] if the [`EventID`] of `rec` and its
+    /// [`time`](DaemonRecord::time) differ by more than
+    /// [`drift_tolerance`](Self::drift_tolerance).
+    pub fn observe_daemon(&self, rec: &DaemonRecord) -> Option<ClockJump> {
+        let jump = ClockJump {
+            reference: ClockReference::Daemon(rec.time?),
+            current: rec.id,
+        };
+        (jump.delta_ms().unsigned_abs() > self.drift_tolerance).then_some(jump)
+    }
+
+    /// Applies [`observe`](Self::observe) and, for `DAEMON_*`
+    /// records, [`observe_daemon`](Self::observe_daemon) to `msg`.
+    ///
+    /// ```
+    /// # use linux_audit_parser::*;
+    /// let msg = parse(&b"type=DAEMON_START msg=audit(1724970459.096:1): op=start ver=3.1.2 format=raw time=2024-08-30T00:27:39.096+02:00 auid=0 pid=1 res=success\n"[..], false).unwrap();
+    /// assert!(ClockMonitor::default().observe_message(&msg).is_empty());
+    /// let msg = parse(&b"type=DAEMON_START msg=audit(1724970459.096:1): op=start ver=3.1.2 format=raw time=2024-08-29T22:27:39.096+02:00 auid=0 pid=1 res=success\n"[..], false).unwrap();
+    /// let jumps = ClockMonitor::default().observe_message(&msg);
+    /// assert_eq!(jumps[0].delta_ms(), 7200 * 1000);
+    /// ```
+    pub fn observe_message(&mut self, msg: &Message) -> Vec<ClockJump> {
+        let drift = DaemonRecord::from_message(msg).and_then(|rec| self.observe_daemon(&rec));
+        self.observe(msg.id).into_iter().chain(drift).collect()
+    }
+
+    /// Returns the most recently seen record identifier.
+    pub fn last(&self) -> Option<EventID> {
+        self.last
+    }
+}
+
+/// Converts an [`EventID`] timestamp for signed arithmetic.
+fn signed_ms(ts: u64) -> i64 {
+    i64::try_from(ts).unwrap_or(i64::MAX)
+}
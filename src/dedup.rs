@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+
+use crate::*;
+
+/// 64-bit FNV-1a, fed incrementally
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    fn new() -> Self {
+        Fingerprint(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        for b in data {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Writes a length prefix, so that e.g. `["ab", "c"]` and
+    /// `["a", "bc"]` differ.
+    fn write_prefixed(&mut self, tag: u8, data: &[u8]) {
+        self.write(&[tag]);
+        self.write(&(data.len() as u64).to_le_bytes());
+        self.write(data);
+    }
+
+    fn write_value(&mut self, v: &Value) {
+        match v {
+            Value::Empty => self.write(&[0]),
+            Value::Number(Number::Dec(n)) => self.write_prefixed(1, &n.to_le_bytes()),
+            Value::Number(Number::Hex(n)) => self.write_prefixed(2, &n.to_le_bytes()),
+            Value::Number(Number::Oct(n)) => self.write_prefixed(3, &n.to_le_bytes()),
+            Value::List(vs) | Value::StringifiedList(vs) => {
+                self.write_prefixed(4, &(vs.len() as u64).to_le_bytes());
+                vs.iter().for_each(|v| self.write_value(v));
+            }
+            Value::Map(kv) => {
+                self.write_prefixed(5, &(kv.len() as u64).to_le_bytes());
+                for (k, v) in kv {
+                    self.write_key(k);
+                    self.write_value(v);
+                }
+            }
+            Value::Skipped((args, bytes)) => {
+                self.write_prefixed(6, &(*args as u64).to_le_bytes());
+                self.write(&(*bytes as u64).to_le_bytes());
+            }
+            Value::Str(s, _) => self.write_prefixed(7, s),
+            Value::Owned(s) => self.write_prefixed(7, s),
+            Value::Literal(s) => self.write_prefixed(7, s.as_bytes()),
+            Value::Segments(segs) => self.write_prefixed(7, &segs.concat()),
+        }
+    }
+
+    fn write_key(&mut self, k: &Key) {
+        self.write_prefixed(8, k.to_string().as_bytes());
+    }
+
+    fn write_record(&mut self, ty: MessageType, body: &Body) {
+        self.write(&ty.0.to_le_bytes());
+        self.write(&(body.len() as u64).to_le_bytes());
+        for (k, v) in body {
+            self.write_key(k);
+            self.write_value(v);
+        }
+    }
+}
+
+impl Message<'_> {
+    /// Returns a fingerprint of node name, message type, and body,
+    /// i.e. of everything except the [`EventID`]. Messages that only
+    /// differ in the quoting of string values have the same
+    /// fingerprint. Fingerprints are stable across Rust versions and
+    /// platforms.
+    pub fn fingerprint(&self) -> u64 {
+        let mut fp = Fingerprint::new();
+        fp.write_prefixed(9, self.node.as_deref().unwrap_or_default());
+        fp.write_record(self.ty, &self.body);
+        fp.0
+    }
+}
+
+impl Event<'_> {
+    /// Returns a fingerprint of node name and records, see
+    /// [`Message::fingerprint`].
+    pub fn fingerprint(&self) -> u64 {
+        let mut fp = Fingerprint::new();
+        fp.write_prefixed(9, self.node.as_deref().unwrap_or_default());
+        for (ty, body) in &self.records {
+            fp.write_record(*ty, body);
+        }
+        fp.0
+    }
+}
+
+/// Recognizes repeated records: messages whose type and body are
+/// identical to a recent message with a different [`EventID`]
+///
+/// Such repetitions are caused by kernel bugs or by logs that are
+/// replayed. Messages are compared by their
+/// [`fingerprint`](Message::fingerprint).
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let lines = [
+///     &b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n"[..],
+///     &b"type=CWD msg=audit(1.000:2): cwd=\"/root\"\n"[..],
+///     &b"type=CWD msg=audit(1.000:3): cwd=\"/tmp\"\n"[..],
+/// ];
+/// let msgs = lines.iter().map(|l| parse(l, false).unwrap());
+/// let seqs: Vec<_> = Dedup::new(8).filter(msgs).map(|m| m.id.sequence).collect();
+/// assert_eq!(seqs, [1, 3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dedup {
+    window: usize,
+    max_age_ms: Option<u64>,
+    recent: VecDeque<(u64, EventID)>,
+    duplicates: u64,
+}
+
+impl Dedup {
+    /// Constructs a filter that compares each message to the last
+    /// `window` messages. A window of 1 only recognizes consecutive
+    /// repetitions.
+    pub fn new(window: usize) -> Self {
+        Dedup {
+            window,
+            max_age_ms: None,
+            recent: VecDeque::with_capacity(window),
+            duplicates: 0,
+        }
+    }
+
+    /// Sets the maximum time in milliseconds between a message and
+    /// its repetition.
+    pub fn max_age(mut self, max_age_ms: u64) -> Self {
+        self.max_age_ms = Some(max_age_ms);
+        self
+    }
+
+    /// Records `msg`. Returns the identifier of the earlier message
+    /// that `msg` repeats, if any.
+    pub fn check(&mut self, msg: &Message) -> Option<EventID> {
+        if self.window == 0 {
+            return None;
+        }
+        let fp = msg.fingerprint();
+        let max_age = self.max_age_ms;
+        let found = self
+            .recent
+            .iter()
+            .rev()
+            .find(|(f, id)| {
+                *f == fp
+                    && *id != msg.id
+                    && !matches!(max_age, Some(max) if msg.id.timestamp.abs_diff(id.timestamp) > max)
+            })
+            .map(|(_, id)| *id);
+        if found.is_some() {
+            self.duplicates += 1;
+        }
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((fp, msg.id));
+        found
+    }
+
+    /// Returns the number of repetitions found so far.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Drops repetitions from `messages`.
+    pub fn filter<'a, I: IntoIterator<Item = Message<'a>>>(
+        mut self,
+        messages: I,
+    ) -> impl Iterator<Item = Message<'a>> {
+        messages
+            .into_iter()
+            .filter(move |msg| self.check(msg).is_none())
+    }
+
+    /// Pairs each message with the identifier of the earlier message
+    /// it repeats, if any.
+    pub fn mark<'a, I: IntoIterator<Item = Message<'a>>>(
+        mut self,
+        messages: I,
+    ) -> impl Iterator<Item = (Message<'a>, Option<EventID>)> {
+        messages.into_iter().map(move |msg| {
+            let dup = self.check(&msg);
+            (msg, dup)
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str;
+
+use crate::*;
+
+/// Which side of a connection an [`Endpoint`] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointRole {
+    /// `laddr`/`lport`
+    Local,
+    /// `addr`/`rport`
+    Remote,
+    /// `saddr`/`sport`, e.g. in `NETFILTER_PKT` records
+    Source,
+    /// `daddr`/`dport`, e.g. in `NETFILTER_PKT` records
+    Destination,
+}
+
+/// Address fields and the matching port fields
+const PAIRS: &[(&str, &str, EndpointRole)] = &[
+    ("laddr", "lport", EndpointRole::Local),
+    ("addr", "rport", EndpointRole::Remote),
+    ("saddr", "sport", EndpointRole::Source),
+    ("daddr", "dport", EndpointRole::Destination),
+];
+
+/// An IP address combined with the corresponding port, if the record
+/// contains one, see [`Body::socket_addrs`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    pub role: EndpointRole,
+    pub ip: IpAddr,
+    pub port: Option<u16>,
+}
+
+impl Endpoint {
+    /// Returns the socket address if the port is known.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        self.port.map(|port| SocketAddr::new(self.ip, port))
+    }
+}
+
+impl Body<'_> {
+    /// Combines address fields and port fields (`laddr`/`lport`,
+    /// `addr`/`rport`, `saddr`/`sport`, `daddr`/`dport`) into
+    /// [`Endpoint`]s. Fields are looked up in the body and in the
+    /// key/value pairs of `msg='…'`; ports in `msg='…'` are only
+    /// recognized if [`Parser::msg_numbers`] is set. Addresses that are
+    /// not IP addresses, e.g. `addr=?` or host names, are skipped.
+    pub fn socket_addrs(&self) -> Vec<Endpoint> {
+        let get = |k: &str| {
+            self.get(k).or_else(|| match self.get("msg") {
+                Some(Value::Map(m)) => m.iter().find(|(mk, _)| *mk == *k).map(|(_, v)| v),
+                _ => None,
+            })
+        };
+        PAIRS
+            .iter()
+            .filter_map(|(addr, port, role)| {
+                let ip = get(addr)
+                    .and_then(value_bytes)
+                    .and_then(|s| str::from_utf8(s).ok())
+                    .and_then(|s| s.parse().ok())?;
+                let port = get(port)
+                    .and_then(Value::to_u64)
+                    .and_then(|p| u16::try_from(p).ok());
+                Some(Endpoint {
+                    role: *role,
+                    ip,
+                    port,
+                })
+            })
+            .collect()
+    }
+}
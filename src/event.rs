@@ -0,0 +1,206 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// A set of records that share the same [`EventID`], e.g. `SYSCALL`,
+/// `EXECVE`, `CWD`, `PATH`, `PROCTITLE`
+#[derive(Debug, Clone, Default)]
+pub struct Event<'a> {
+    /// The identifier shared by all records
+    pub id: EventID,
+    /// The optional node name
+    pub node: Option<Vec<u8>>,
+    /// Message types and bodies of the records, in the order in which
+    /// they were added
+    pub records: Vec<(MessageType, Body<'a>)>,
+}
+
+impl<'a> Event<'a> {
+    /// Constructs an empty `Event`.
+    pub fn new(id: EventID, node: Option<Vec<u8>>) -> Self {
+        Event {
+            id,
+            node,
+            records: vec![],
+        }
+    }
+
+    /// Returns the event with `'static` lifetime, see
+    /// [`Body::into_owned`].
+    pub fn into_owned(self) -> Event<'static> {
+        Event {
+            id: self.id,
+            node: self.node,
+            records: self
+                .records
+                .into_iter()
+                .map(|(ty, body)| (ty, body.into_owned()))
+                .collect(),
+        }
+    }
+
+    /// Appends the body of `msg` to the event. The message's
+    /// identifier and node name are not checked.
+    pub fn push(&mut self, msg: Message<'a>) {
+        self.records.push((msg.ty, msg.body));
+    }
+
+    /// Returns the bodies of all records of type `ty`.
+    pub fn bodies(&self, ty: MessageType) -> impl Iterator<Item = &Body<'a>> {
+        self.records
+            .iter()
+            .filter(move |(t, _)| *t == ty)
+            .map(|(_, b)| b)
+    }
+
+    /// Returns the body of the first record of type `ty`.
+    pub fn body(&self, ty: MessageType) -> Option<&Body<'a>> {
+        self.bodies(ty).next()
+    }
+
+    /// Returns the fields of all records, annotated with the record
+    /// they came from.
+    pub fn flatten(&self) -> FlatBody<'_> {
+        let mut entries = vec![];
+        for (n, (ty, body)) in self.records.iter().enumerate() {
+            let index = self.records[..n].iter().filter(|(t, _)| t == ty).count();
+            let rec = RecordRef { ty: *ty, index };
+            entries.extend(body.into_iter().map(|(k, v)| (rec, k, v)));
+        }
+        FlatBody { entries }
+    }
+}
+
+/// Reference to a record within an [`Event`]: message type and
+/// index among the records of that type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordRef {
+    pub ty: MessageType,
+    pub index: usize,
+}
+
+impl Display for RecordRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.ty, self.index)
+    }
+}
+
+/// Flattened view on the fields of all records in an [`Event`] that
+/// keeps track of the record each field came from, so that
+/// `PATH[0].name` can be distinguished from `PATH[1].name`.
+#[derive(Debug, Clone)]
+pub struct FlatBody<'e> {
+    entries: Vec<(RecordRef, &'e Key, &'e Value<'e>)>,
+}
+
+impl<'e> FlatBody<'e> {
+    /// Returns all values for `key`, along with the record they came
+    /// from.
+    pub fn find<'s, K: AsRef<[u8]> + 's>(
+        &'s self,
+        key: K,
+    ) -> impl Iterator<Item = (RecordRef, &'e Value<'e>)> + 's {
+        self.entries
+            .iter()
+            .filter(move |(_, k, _)| *k == key.as_ref())
+            .map(|(r, _, v)| (*r, *v))
+    }
+
+    /// Retrieves the value for `key` from a specific record.
+    pub fn get<K: AsRef<[u8]>>(&self, rec: RecordRef, key: K) -> Option<&'e Value<'e>> {
+        self.entries
+            .iter()
+            .find(|(r, k, _)| *r == rec && *k == key.as_ref())
+            .map(|(_, _, v)| *v)
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over all `(RecordRef, Key, Value)` triplets.
+    pub fn iter(&self) -> impl Iterator<Item = (RecordRef, &'e Key, &'e Value<'e>)> + '_ {
+        self.entries.iter().copied()
+    }
+}
+
+impl Event<'_> {
+    /// Returns the command line arguments from the `EXECVE` records
+    /// of the event in order.
+    ///
+    /// The kernel splits long command lines across several `EXECVE`
+    /// records; only the first one carries `argc`. Arguments are
+    /// collected from all records, pieces of arguments that have
+    /// been split into `aX[Y]` fields are joined even if they span
+    /// records. If `argc` is present, the result has exactly `argc`
+    /// elements: arguments that are missing, e.g. because records
+    /// have been lost, are returned as empty strings, surplus ones
+    /// are dropped. See also [`Body::execve_args`].
+    pub fn execve_args(&self) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        let mut argc = None;
+        for body in self.bodies(MessageType::EXECVE) {
+            if argc.is_none() {
+                argc = match body.get("argc") {
+                    Some(Value::Number(n)) => n.as_u64(),
+                    _ => None,
+                };
+            }
+            body.collect_execve_args(&mut args);
+        }
+        if let Some(argc) = argc.and_then(|n| usize::try_from(n).ok()) {
+            args.resize(argc, vec![]);
+        }
+        args
+    }
+
+    /// Adds an `abs_name` field to every `PATH` record, containing
+    /// the absolute form of its `name`. Relative names are resolved
+    /// against the `cwd` of the event's `CWD` record; leading `./`
+    /// components are removed, `..` is kept. Absolute names are
+    /// copied as they are.
+    ///
+    /// Records without `name` (e.g. `name=(null)`), records with a
+    /// relative name if the event has no `CWD` record, and records
+    /// that already have an `abs_name` are left unchanged.
+    pub fn resolve_paths(&mut self) {
+        let cwd = self
+            .body(MessageType::CWD)
+            .and_then(|b| b.get("cwd"))
+            .and_then(value_bytes)
+            .map(|c| c.to_vec());
+        for (ty, body) in self.records.iter_mut() {
+            if *ty != MessageType::PATH || body.get("abs_name").is_some() {
+                continue;
+            }
+            let Some(name) = body.get("name").and_then(value_bytes) else {
+                continue;
+            };
+            let abs = if name.starts_with(b"/") {
+                name.to_vec()
+            } else if let Some(cwd) = &cwd {
+                let mut name = name;
+                while let Some(rest) = name.strip_prefix(b"./") {
+                    name = rest;
+                }
+                let mut abs = cwd.clone();
+                if !abs.ends_with(b"/") {
+                    abs.push(b'/');
+                }
+                if name != b"." {
+                    abs.extend(name);
+                }
+                abs
+            } else {
+                continue;
+            };
+            body.push((Key::Name(b"abs_name"[..].into()), Value::Owned(abs)));
+        }
+    }
+}
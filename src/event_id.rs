@@ -2,12 +2,26 @@
 use serde::{Serialize, Serializer};
 
 use std::fmt::{self, Display};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// The identifier of an audit event, corresponding to the
 /// `msg=audit(…)` part of every Linux Audit log line.
 ///
 /// The event ID can reasonably be expected to be unique per system.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+///
+/// The [`Display`] and [`FromStr`] implementations use the format
+/// found in log lines, e.g. `1615114232.375:15558`, and are
+/// guaranteed to round-trip. This format, as well as the one produced
+/// by [`EventID::to_compact_string`], is stable and can be used in
+/// checkpoint files and storage keys.
+///
+/// The [`Hash`] implementation feeds a single `u128`, see
+/// [`EventID::as_u128`], to the hasher. For maps with many in-flight
+/// events, [`EventIDBuildHasher`] is considerably faster than the
+/// standard library's default hasher.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
 pub struct EventID {
     /// Unix epoch-based timestamp, with mullisecond-precision
     pub timestamp: u64,
@@ -15,6 +29,173 @@ pub struct EventID {
     pub sequence: u32,
 }
 
+impl EventID {
+    /// Returns the timestamp as [`SystemTime`]. Audit timestamps are
+    /// seconds since the Unix epoch, i.e. always UTC.
+    pub fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+}
+
+impl EventID {
+    /// Combines timestamp and sequence number into a single integer
+    /// that sorts like the `EventID`. The inverse is
+    /// [`EventID::from_u128`].
+    pub const fn as_u128(&self) -> u128 {
+        (self.timestamp as u128) << 32 | self.sequence as u128
+    }
+
+    /// Splits an integer produced by [`EventID::as_u128`]. Bits above
+    /// the 96 used by `as_u128` are ignored.
+    pub const fn from_u128(n: u128) -> Self {
+        EventID {
+            timestamp: (n >> 32) as u64,
+            sequence: n as u32,
+        }
+    }
+}
+
+impl From<EventID> for u128 {
+    fn from(id: EventID) -> Self {
+        id.as_u128()
+    }
+}
+
+impl Hash for EventID {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u128(self.as_u128());
+    }
+}
+
+/// Fast, non-cryptographic [`Hasher`] for keys that contain
+/// [`EventID`]s, see [`EventIDBuildHasher`]
+///
+/// Every write is mixed in with a multiply-rotate step, `finish`
+/// applies a final avalanche so that sequence numbers, which often
+/// differ in the low bits only, are spread over all bits of the hash.
+/// Unlike the standard library's default hasher, it is not resistant
+/// against inputs chosen to produce collisions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventIDHasher(u64);
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl EventIDHasher {
+    #[inline]
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for EventIDHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut buf = [0; 8];
+            buf[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(buf));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, n: u8) {
+        self.add(n.into());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, n: u32) {
+        self.add(n.into());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, n: u128) {
+        self.add(n as u64);
+        self.add((n >> 64) as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        // finalizer from MurmurHash3
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        h ^ (h >> 33)
+    }
+}
+
+/// [`BuildHasher`](std::hash::BuildHasher) for maps and sets keyed by
+/// [`EventID`], or by tuples containing one
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use linux_audit_parser::*;
+/// let mut m: HashMap<EventID, usize, EventIDBuildHasher> = HashMap::default();
+/// m.insert("1615114232.375:15558".parse().unwrap(), 1);
+/// ```
+pub type EventIDBuildHasher = BuildHasherDefault<EventIDHasher>;
+
+impl EventID {
+    /// Returns a representation that is safe to use in file names,
+    /// e.g. `1615114232.375-15558`. The inverse is
+    /// [`EventID::from_compact_str`].
+    pub fn to_compact_string(&self) -> String {
+        let sec = self.timestamp / 1000;
+        let msec = self.timestamp % 1000;
+        let seq = self.sequence;
+        format!("{sec}.{msec:03}-{seq}")
+    }
+
+    /// Parses the representation produced by
+    /// [`EventID::to_compact_string`].
+    pub fn from_compact_str(s: &str) -> Result<Self, &'static str> {
+        Self::parse_with_separator(s, '-')
+    }
+
+    fn parse_with_separator(s: &str, sep: char) -> Result<Self, &'static str> {
+        let (ts, seq) = s.split_once(sep).ok_or("missing sequence number")?;
+        let (sec, msec) = ts.split_once('.').ok_or("missing milliseconds")?;
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit());
+        if !digits(sec) || !digits(seq) || msec.len() != 3 || !digits(msec) {
+            return Err("invalid event ID");
+        }
+        let sec: u64 = sec.parse().map_err(|_| "invalid seconds")?;
+        let msec: u64 = msec.parse().map_err(|_| "invalid milliseconds")?;
+        let sequence = seq.parse().map_err(|_| "invalid sequence number")?;
+        let timestamp = sec
+            .checked_mul(1000)
+            .and_then(|t| t.checked_add(msec))
+            .ok_or("timestamp out of range")?;
+        Ok(EventID {
+            timestamp,
+            sequence,
+        })
+    }
+}
+
+impl FromStr for EventID {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_separator(s, ':')
+    }
+}
+
 impl Display for EventID {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let sec = self.timestamp / 1000;
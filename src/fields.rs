@@ -0,0 +1,257 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+
+/// Typed fields of a `SYSCALL` record
+///
+/// This can be deserialized from the serialized form of a [`Body`],
+/// including in `#[serde(flatten)]` position. Byte strings may be
+/// represented as strings, byte arrays, or sequences of integers;
+/// numbers as integers or as strings with `0x`/`0o` prefix, see
+/// [`NumberFormat`]. Missing or empty fields are `None`; other
+/// fields are ignored.
+///
+/// [`Body`]: crate::Body
+/// [`NumberFormat`]: crate::NumberFormat
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SyscallFields {
+    #[serde(default, deserialize_with = "number")]
+    pub arch: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub syscall: Option<i64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub success: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "number")]
+    pub exit: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub a0: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub a1: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub a2: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub a3: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub items: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub ppid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub pid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub auid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub uid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub gid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub euid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub suid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub fsuid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub egid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub sgid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub fsgid: Option<i64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub tty: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "number")]
+    pub ses: Option<i64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub comm: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub exe: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub subj: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub key: Option<Vec<u8>>,
+}
+
+/// Typed fields of a `PATH` record, see [`SyscallFields`] for the
+/// accepted representations
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PathFields {
+    #[serde(default, deserialize_with = "number")]
+    pub item: Option<i64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub name: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "number")]
+    pub inode: Option<u64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub dev: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "number")]
+    pub mode: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub ouid: Option<i64>,
+    #[serde(default, deserialize_with = "number")]
+    pub ogid: Option<i64>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub rdev: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub obj: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "bytes")]
+    pub nametype: Option<Vec<u8>>,
+    #[serde(default, deserialize_with = "number")]
+    pub cap_fp: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub cap_fi: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub cap_fe: Option<u64>,
+    #[serde(default, deserialize_with = "number")]
+    pub cap_fver: Option<u64>,
+}
+
+/// Typed fields of an `EXECVE` record, see [`SyscallFields`] for the
+/// accepted representations
+///
+/// `args` contains the values of `a0`, `a1`, … in order; gaps are
+/// filled with empty arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecveFields {
+    pub argc: Option<i64>,
+    pub args: Vec<Vec<u8>>,
+}
+
+impl<'de> Deserialize<'de> for ExecveFields {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = ExecveFields;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("EXECVE fields")
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = ExecveFields::default();
+                while let Some(k) = map.next_key::<String>()? {
+                    if k == "argc" {
+                        fields.argc = map.next_value::<Num<i64>>()?.0;
+                        continue;
+                    }
+                    let Some(n) = k
+                        .strip_prefix('a')
+                        .filter(|n| n.bytes().all(|c| c.is_ascii_digit()))
+                        .and_then(|n| n.parse::<usize>().ok())
+                    else {
+                        map.next_value::<de::IgnoredAny>()?;
+                        continue;
+                    };
+                    let v = map.next_value::<Bytes>()?.0.unwrap_or_default();
+                    if fields.args.len() <= n {
+                        fields.args.resize(n + 1, vec![]);
+                    }
+                    fields.args[n] = v;
+                }
+                Ok(fields)
+            }
+        }
+        d.deserialize_map(V)
+    }
+}
+
+fn number<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    Ok(Num::deserialize(d)?.0)
+}
+
+fn bytes<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+    Ok(Bytes::deserialize(d)?.0)
+}
+
+/// Number, optionally represented as string with radix prefix
+struct Num<T>(Option<T>);
+
+impl<'de, T: TryFrom<i128>> Deserialize<'de> for Num<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct V<T>(PhantomData<T>);
+        impl<T: TryFrom<i128>> V<T> {
+            fn convert<E: de::Error>(n: i128) -> Result<Num<T>, E> {
+                T::try_from(n)
+                    .map(|n| Num(Some(n)))
+                    .map_err(|_| E::custom(format!("number out of range: {n}")))
+            }
+        }
+        impl<'de, T: TryFrom<i128>> Visitor<'de> for V<T> {
+            type Value = Num<T>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number")
+            }
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+                Self::convert(n.into())
+            }
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+                Self::convert(n.into())
+            }
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                let n = if let Some(h) = s.strip_prefix("0x") {
+                    i128::from_str_radix(h, 16)
+                } else if let Some(o) = s.strip_prefix("0o") {
+                    i128::from_str_radix(o, 8)
+                } else {
+                    s.parse()
+                };
+                Self::convert(n.map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))?)
+            }
+            fn visit_bytes<E: de::Error>(self, b: &[u8]) -> Result<Self::Value, E> {
+                match std::str::from_utf8(b) {
+                    Ok(s) => self.visit_str(s),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(b), &self)),
+                }
+            }
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Num(None))
+            }
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Num(None))
+            }
+            fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(self)
+            }
+        }
+        d.deserialize_any(V(PhantomData))
+    }
+}
+
+/// Byte string, represented as string, bytes, or sequence of integers
+struct Bytes(Option<Vec<u8>>);
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = Bytes;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte string")
+            }
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(Bytes(Some(s.as_bytes().to_vec())))
+            }
+            fn visit_bytes<E: de::Error>(self, b: &[u8]) -> Result<Self::Value, E> {
+                Ok(Bytes(Some(b.to_vec())))
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(c) = seq.next_element::<u8>()? {
+                    buf.push(c);
+                }
+                Ok(Bytes(Some(buf)))
+            }
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Bytes(None))
+            }
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Bytes(None))
+            }
+            fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(self)
+            }
+        }
+        d.deserialize_any(V)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::*;
+
+/// File type, derived from the `mode` field of a `PATH` record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    Regular,
+    Directory,
+    Symlink,
+    /// Devices, FIFOs, sockets
+    Other,
+}
+
+impl FileKind {
+    /// Determines the file type from a `st_mode` value.
+    pub fn from_mode(mode: u64) -> Self {
+        match mode & 0o170000 {
+            0o100000 => FileKind::Regular,
+            0o040000 => FileKind::Directory,
+            0o120000 => FileKind::Symlink,
+            _ => FileKind::Other,
+        }
+    }
+}
+
+/// A file system operation, derived from the `PATH` records of an
+/// [`Event`], see [`Event::file_ops`]
+///
+/// Names are taken from `abs_name` if present (see
+/// [`Event::resolve_paths`]), from `name` otherwise. `kind` is `None`
+/// if the record carries no `mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOp<'e> {
+    /// A file, directory, symlink, … was created.
+    Create {
+        name: &'e [u8],
+        kind: Option<FileKind>,
+    },
+    /// A file or directory was removed.
+    Unlink {
+        name: &'e [u8],
+        kind: Option<FileKind>,
+    },
+    /// A file or directory was renamed.
+    Rename {
+        from: &'e [u8],
+        to: &'e [u8],
+        kind: Option<FileKind>,
+    },
+    /// An existing file was replaced, e.g. by renaming another file
+    /// onto it.
+    Overwrite {
+        name: &'e [u8],
+        kind: Option<FileKind>,
+    },
+}
+
+/// Name and type of a `PATH` record with `nametype=CREATE` or
+/// `nametype=DELETE`
+type PathEntry<'e> = (&'e [u8], Option<FileKind>);
+
+impl<'a> Event<'a> {
+    /// Classifies the file system operations recorded in the event's
+    /// `PATH` records, based on their `nametype` fields.
+    ///
+    /// The kernel logs `DELETE` entries for removed names and
+    /// `CREATE` entries for new names, along with `PARENT` entries
+    /// for the containing directories:
+    ///
+    /// - A `CREATE` entry whose name also occurs in a `DELETE` entry
+    ///   means that an existing file was replaced
+    ///   ([`FileOp::Overwrite`]).
+    /// - Other `DELETE` entries are paired with `CREATE` entries in
+    ///   order and form [`FileOp::Rename`] operations, as logged for
+    ///   _rename(2)_.
+    /// - Remaining `DELETE` and `CREATE` entries result in
+    ///   [`FileOp::Unlink`] and [`FileOp::Create`] operations.
+    ///
+    /// Symlinks and directories can be recognized by their
+    /// [`FileKind`].
+    pub fn file_ops(&self) -> Vec<FileOp<'_>> {
+        let mut creates: Vec<PathEntry> = vec![];
+        let mut deletes: Vec<PathEntry> = vec![];
+        for body in self.bodies(MessageType::PATH) {
+            let Some(name) = body
+                .get("abs_name")
+                .or_else(|| body.get("name"))
+                .and_then(value_bytes)
+            else {
+                continue;
+            };
+            let kind = match body.get("mode") {
+                Some(Value::Number(n)) => n.as_u64().map(FileKind::from_mode),
+                _ => None,
+            };
+            match body.get("nametype").and_then(value_bytes) {
+                Some(b"CREATE") => creates.push((name, kind)),
+                Some(b"DELETE") => deletes.push((name, kind)),
+                _ => (),
+            }
+        }
+
+        let mut overwritten = vec![];
+        deletes.retain(|(name, kind)| {
+            if creates.iter().any(|(n, _)| n == name) {
+                overwritten.push((*name, *kind));
+                false
+            } else {
+                true
+            }
+        });
+        let mut ops = vec![];
+        let mut creates = creates.into_iter();
+        for (from, kind) in deletes {
+            match creates.next() {
+                Some((to, k)) => ops.push(FileOp::Rename {
+                    from,
+                    to,
+                    kind: kind.or(k),
+                }),
+                None => ops.push(FileOp::Unlink { name: from, kind }),
+            }
+        }
+        ops.extend(
+            overwritten
+                .iter()
+                .map(|(name, kind)| FileOp::Overwrite { name, kind: *kind }),
+        );
+        ops.extend(
+            creates
+                .filter(|(name, _)| !overwritten.iter().any(|(n, _)| n == name))
+                .map(|(name, kind)| FileOp::Create { name, kind }),
+        );
+        ops
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+use thiserror::Error;
+
+use crate::*;
+
+/// A sample line and the fields expected after parsing it
+///
+/// Fixture files contain one JSON object per line:
+///
+/// ```json
+/// {"raw": "type=CWD msg=audit(1.000:1): cwd=\"/root\"", "expected": {"type": "CWD", "cwd": "/root"}}
+/// ```
+///
+/// `expected` is optional. Its keys are field names, plus `type` for
+/// the message type, `node`, and `id` for the event identifier as
+/// formatted by [`EventID`]'s `Display`. Values are compared with
+/// the parsed field as converted by [`Value::to_json`]; numbers may
+/// also be given as strings, e.g. `"0x1f"` for a hexadecimal field.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Fixture {
+    pub raw: String,
+    #[serde(default)]
+    pub expected: Map<String, JsonValue>,
+}
+
+/// A reason why a [`Fixture`] does not match
+#[derive(Debug, Error)]
+pub enum FixtureError {
+    #[error("invalid fixture: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error("{key}: expected {expected}, got {}", .actual.as_ref().map(|v| v.to_string()).unwrap_or("nothing".into()))]
+    Mismatch {
+        key: String,
+        expected: JsonValue,
+        actual: Option<JsonValue>,
+    },
+}
+
+fn json_matches(expected: &JsonValue, actual: &JsonValue) -> bool {
+    match (expected, actual) {
+        (JsonValue::String(e), JsonValue::Number(a)) => *e == a.to_string(),
+        (e, a) => e == a,
+    }
+}
+
+impl Fixture {
+    /// Parses [`Fixture::raw`] using `parser` and compares the result
+    /// with [`Fixture::expected`]. Returns all mismatches, ordered by
+    /// key.
+    pub fn check(&self, parser: &Parser) -> Result<(), Vec<FixtureError>> {
+        let mut raw = self.raw.clone().into_bytes();
+        if !raw.ends_with(b"\n") {
+            raw.push(b'\n');
+        }
+        let msg = parser.parse(&raw).map_err(|e| vec![e.into()])?;
+        let errors = self
+            .expected
+            .iter()
+            .filter_map(|(key, expected)| {
+                let actual = match key.as_str() {
+                    "type" => Some(msg.ty.to_string().into()),
+                    "id" => Some(msg.id.to_string().into()),
+                    "node" => msg.node.as_ref().map(|n| String::from_utf8_lossy(n).into()),
+                    k => msg.body.get(k).map(Value::to_json),
+                };
+                match &actual {
+                    Some(a) if json_matches(expected, a) => None,
+                    _ => Some(FixtureError::Mismatch {
+                        key: key.clone(),
+                        expected: expected.clone(),
+                        actual,
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Reads fixtures, one JSON object per line. Empty lines are skipped.
+/// Returns the line number, starting at 1, along with each fixture.
+pub fn read_fixtures<R: BufRead>(
+    r: R,
+) -> impl Iterator<Item = io::Result<(usize, Result<Fixture, serde_json::Error>)>> {
+    r.lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|(n, line)| line.map(|l| (n + 1, serde_json::from_str(&l))))
+}
+
+/// A fixture that did not match
+#[derive(Debug)]
+pub struct FixtureFailure {
+    /// The file containing the fixture
+    pub path: PathBuf,
+    /// Line number, starting at 1
+    pub line: usize,
+    pub errors: Vec<FixtureError>,
+}
+
+/// Result of [`Parser::check_fixtures`]
+#[derive(Debug, Default)]
+pub struct FixtureReport {
+    /// Number of files read
+    pub files: usize,
+    /// Number of fixtures checked
+    pub fixtures: usize,
+    pub failures: Vec<FixtureFailure>,
+}
+
+impl FixtureReport {
+    /// True if all fixtures have matched.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Parser {
+    /// Checks every [`Fixture`] in every file below `path`, which may
+    /// be a single file or a directory, and collects failures.
+    ///
+    /// This is intended for importing sample events from other tools
+    /// into conformance tests.
+    pub fn check_fixtures<P: AsRef<Path>>(&self, path: P) -> io::Result<FixtureReport> {
+        let mut report = FixtureReport::default();
+        self.check_fixtures_path(path.as_ref(), &mut report)?;
+        Ok(report)
+    }
+
+    fn check_fixtures_path(&self, path: &Path, report: &mut FixtureReport) -> io::Result<()> {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.sort();
+            for entry in entries {
+                self.check_fixtures_path(&entry, report)?;
+            }
+            return Ok(());
+        }
+        report.files += 1;
+        for item in read_fixtures(io::BufReader::new(fs::File::open(path)?)) {
+            let (line, fixture) = item?;
+            report.fixtures += 1;
+            let result = match fixture {
+                Ok(f) => f.check(self),
+                Err(e) => Err(vec![e.into()]),
+            };
+            if let Err(errors) = result {
+                report.failures.push(FixtureFailure {
+                    path: path.to_path_buf(),
+                    line,
+                    errors,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt::{self, Display};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use crate::*;
+
+/// Semantic classification of user and group ID fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdentityKind {
+    /// `auid`: login user ID, not changed by setuid or sudo
+    LoginUid,
+    /// `old-auid`: previous login user ID (`LOGIN` records)
+    OldLoginUid,
+    /// `uid`: real user ID
+    Uid,
+    /// `euid`: effective user ID
+    EffectiveUid,
+    /// `suid`: saved set-user-ID
+    SavedUid,
+    /// `fsuid`: file system user ID
+    FsUid,
+    /// `ouid`, `inode_uid`, `obj_uid`, `iuid`: owner of a file or
+    /// other object
+    OwnerUid,
+    /// `oauid`: login user ID of the object
+    ObjectLoginUid,
+    /// `sauid`: login user ID of the sender of a userspace message
+    SenderLoginUid,
+    /// `gid`: real group ID
+    Gid,
+    /// `egid`: effective group ID
+    EffectiveGid,
+    /// `sgid`: saved set-group-ID
+    SavedGid,
+    /// `fsgid`: file system group ID
+    FsGid,
+    /// `ogid`, `inode_gid`, `obj_gid`, `igid`: group owner of a file
+    /// or other object
+    OwnerGid,
+    /// `new_gid`: group ID being assigned
+    NewGid,
+    /// Any other user ID field
+    OtherUid,
+    /// Any other group ID field
+    OtherGid,
+}
+
+const IDENTITY_KINDS: &[(&str, IdentityKind)] = &[
+    ("auid", IdentityKind::LoginUid),
+    ("egid", IdentityKind::EffectiveGid),
+    ("euid", IdentityKind::EffectiveUid),
+    ("fsgid", IdentityKind::FsGid),
+    ("fsuid", IdentityKind::FsUid),
+    ("gid", IdentityKind::Gid),
+    ("igid", IdentityKind::OwnerGid),
+    ("inode_gid", IdentityKind::OwnerGid),
+    ("inode_uid", IdentityKind::OwnerUid),
+    ("iuid", IdentityKind::OwnerUid),
+    ("new_gid", IdentityKind::NewGid),
+    ("oauid", IdentityKind::ObjectLoginUid),
+    ("obj_gid", IdentityKind::OwnerGid),
+    ("obj_uid", IdentityKind::OwnerUid),
+    ("ogid", IdentityKind::OwnerGid),
+    ("old-auid", IdentityKind::OldLoginUid),
+    ("ouid", IdentityKind::OwnerUid),
+    ("sauid", IdentityKind::SenderLoginUid),
+    ("sgid", IdentityKind::SavedGid),
+    ("suid", IdentityKind::SavedUid),
+    ("uid", IdentityKind::Uid),
+];
+
+impl IdentityKind {
+    /// True for group IDs
+    pub fn is_group(&self) -> bool {
+        matches!(
+            self,
+            IdentityKind::Gid
+                | IdentityKind::EffectiveGid
+                | IdentityKind::SavedGid
+                | IdentityKind::FsGid
+                | IdentityKind::OwnerGid
+                | IdentityKind::NewGid
+                | IdentityKind::OtherGid
+        )
+    }
+}
+
+impl Key {
+    /// Returns the semantic classification for user and group ID
+    /// keys ([`Key::NameUID`], [`Key::NameGID`]).
+    pub fn identity_kind(&self) -> Option<IdentityKind> {
+        let (name, other) = match self {
+            Key::NameUID(r) => (r, IdentityKind::OtherUid),
+            Key::NameGID(r) => (r, IdentityKind::OtherGid),
+            _ => return None,
+        };
+        if name.as_slice() == b"uuid" {
+            return None;
+        }
+        Some(identity_kind_by_name(name).unwrap_or(other))
+    }
+}
+
+/// Looks up the classification for well-known user and group ID
+/// field names.
+pub(crate) fn identity_kind_by_name(name: &[u8]) -> Option<IdentityKind> {
+    IDENTITY_KINDS
+        .binary_search_by_key(&name, |(s, _)| s.as_bytes())
+        .ok()
+        .map(|i| IDENTITY_KINDS[i].1)
+}
+
+/// The user and group IDs found in a record: those of the subject,
+/// of the object, e.g. the owner of a file, and of the sender of a
+/// userspace message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Identities {
+    pub auid: Option<u32>,
+    pub uid: Option<u32>,
+    pub euid: Option<u32>,
+    pub suid: Option<u32>,
+    pub fsuid: Option<u32>,
+    pub gid: Option<u32>,
+    pub egid: Option<u32>,
+    pub sgid: Option<u32>,
+    pub fsgid: Option<u32>,
+    pub ses: Option<SessionId>,
+    /// Owner of the object, see [`IdentityKind::OwnerUid`]
+    pub ouid: Option<u32>,
+    /// Group owner of the object, see [`IdentityKind::OwnerGid`]
+    pub ogid: Option<u32>,
+    /// Login user ID of the object (`oauid`)
+    pub oauid: Option<u32>,
+    /// Login user ID of the sender of a userspace message (`sauid`)
+    pub sauid: Option<u32>,
+}
+
+/// The value used by the kernel for user IDs, group IDs, and
+/// session IDs that have not been set
+pub const UNSET_ID: u32 = u32::MAX;
+
+impl Identities {
+    /// Replaces IDs that have the "unset" value [`UNSET_ID`] with
+    /// `None`.
+    pub fn without_unset(self) -> Self {
+        let f = |id: Option<u32>| id.filter(|id| *id != UNSET_ID);
+        Identities {
+            auid: f(self.auid),
+            uid: f(self.uid),
+            euid: f(self.euid),
+            suid: f(self.suid),
+            fsuid: f(self.fsuid),
+            gid: f(self.gid),
+            egid: f(self.egid),
+            sgid: f(self.sgid),
+            fsgid: f(self.fsgid),
+            ses: self.ses.filter(SessionId::is_set),
+            ouid: f(self.ouid),
+            ogid: f(self.ogid),
+            oauid: f(self.oauid),
+            sauid: f(self.sauid),
+        }
+    }
+
+    /// True if both login user ID and effective user ID are present
+    /// and differ, e.g. after `sudo` or a setuid binary.
+    pub fn login_uid_mismatch(&self) -> bool {
+        matches!((self.auid, self.euid), (Some(a), Some(e)) if a != e)
+    }
+}
+
+/// Collects the user and group IDs from `body`, including those in
+/// `msg='…'` maps of userspace messages if [`Parser::msg_numbers`] is
+/// set. The first occurrence of each ID is used.
+pub fn identities(body: &Body) -> Identities {
+    let mut ids = Identities::default();
+    for (k, v) in body {
+        match v {
+            Value::Map(m) => m.iter().for_each(|(k, v)| ids.collect(k, v)),
+            v => ids.collect(k, v),
+        }
+    }
+    ids
+}
+
+impl Identities {
+    fn collect(&mut self, k: &Key, v: &Value) {
+        if *k == Key::Common(Common::Ses) {
+            self.ses = self.ses.or_else(|| SessionId::from_value(v));
+            return;
+        }
+        let id = match v {
+            Value::Number(Number::Dec(-1)) => UNSET_ID,
+            Value::Number(Number::Dec(n)) => match u32::try_from(*n) {
+                Ok(n) => n,
+                Err(_) => return,
+            },
+            _ => return,
+        };
+        let slot = match k.identity_kind() {
+            Some(IdentityKind::LoginUid) => &mut self.auid,
+            Some(IdentityKind::Uid) => &mut self.uid,
+            Some(IdentityKind::EffectiveUid) => &mut self.euid,
+            Some(IdentityKind::SavedUid) => &mut self.suid,
+            Some(IdentityKind::FsUid) => &mut self.fsuid,
+            Some(IdentityKind::Gid) => &mut self.gid,
+            Some(IdentityKind::EffectiveGid) => &mut self.egid,
+            Some(IdentityKind::SavedGid) => &mut self.sgid,
+            Some(IdentityKind::FsGid) => &mut self.fsgid,
+            Some(IdentityKind::OwnerUid) => &mut self.ouid,
+            Some(IdentityKind::OwnerGid) => &mut self.ogid,
+            Some(IdentityKind::ObjectLoginUid) => &mut self.oauid,
+            Some(IdentityKind::SenderLoginUid) => &mut self.sauid,
+            _ => return,
+        };
+        slot.get_or_insert(id);
+    }
+}
+
+/// Login session ID, as found in `ses` fields
+///
+/// Processes that are not part of a login session, e.g. daemons, have
+/// the session ID [`SessionId::UNSET`], which is written as
+/// `4294967295` or `-1`. It is displayed as `unset` and serialized as
+/// `null`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId(pub u32);
+
+impl SessionId {
+    /// The session ID of processes outside of login sessions
+    pub const UNSET: SessionId = SessionId(UNSET_ID);
+
+    /// Converts a `ses` value. Both representations of the unset
+    /// value are accepted; `None` is returned for values that are not
+    /// numbers, including [`Value::Empty`] as produced by
+    /// [`Parser::unset_ids`].
+    pub fn from_value(v: &Value) -> Option<Self> {
+        match v {
+            Value::Number(Number::Dec(-1)) => Some(Self::UNSET),
+            Value::Number(Number::Dec(n)) => u32::try_from(*n).ok().map(SessionId),
+            _ => None,
+        }
+    }
+
+    /// True unless this is [`SessionId::UNSET`].
+    pub fn is_set(&self) -> bool {
+        *self != Self::UNSET
+    }
+
+    /// Returns the session ID, or `None` if it is unset.
+    pub fn get(&self) -> Option<u32> {
+        self.is_set().then_some(self.0)
+    }
+}
+
+impl Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get() {
+            Some(n) => write!(f, "{n}"),
+            None => write!(f, "unset"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SessionId {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self.get() {
+            Some(n) => s.serialize_u32(n),
+            None => s.serialize_none(),
+        }
+    }
+}
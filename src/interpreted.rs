@@ -0,0 +1,219 @@
+use std::str;
+
+use nom::{
+    branch::*, bytes::complete::*, character::complete::*, combinator::*, sequence::*, IResult,
+};
+
+use nom::character::complete::u32 as dec_u32;
+
+use crate::parser::{
+    parse_key, parse_key_a_x, parse_key_a_x_len, parse_key_a_xy, parse_node, parse_type,
+};
+use crate::*;
+
+/// Parse a single line of interpreted output as produced by
+/// `ausearch -i`, e.g.
+///
+/// ```text
+/// type=SYSCALL msg=audit(03/07/2021 11:50:32.375:15558) : arch=x86_64 syscall=execve …
+/// ```
+///
+/// This is a best-effort parser for interpreted excerpts, e.g. pasted
+/// into tickets. Resolved values (user names, syscall names, …) are
+/// kept as strings, since the raw values cannot be recovered.
+/// Unquoted values that contain spaces extend to the next `key=`.
+///
+/// _ausearch(8)_ prints timestamps in local time, using the date
+/// format of the current locale. `MM/DD/YY`, `MM/DD/YYYY`,
+/// `YYYY-MM-DD`, and `DD.MM.YYYY` are recognized. `utc_offset` (in
+/// seconds east of UTC) is subtracted to compute the [`EventID`].
+pub fn parse_interpreted<'a>(raw: &[u8], utc_offset: i64) -> Result<Message<'a>, ParseError> {
+    let line = raw.strip_suffix(b"\n").unwrap_or(raw);
+
+    let (rest, (node, ty, id)) = parse_interpreted_header(line, utc_offset)
+        .map_err(|_| ParseError::MalformedHeader(raw.to_vec()))?;
+
+    let kv =
+        parse_interpreted_body(rest, ty).ok_or_else(|| ParseError::MalformedBody(rest.to_vec()))?;
+
+    let mut body = Body::with_capacity(kv.len());
+    for (k, v) in kv {
+        body.push((k, v));
+    }
+
+    Ok(Message {
+        id,
+        node: node.map(|s| s.to_vec()),
+        ty,
+        body,
+        truncated: false,
+        hex_decoded: false,
+        type_name: None,
+        enriched: false,
+    })
+}
+
+/// Recognize the header: node, type, human-readable event identifier
+#[allow(clippy::type_complexity)]
+fn parse_interpreted_header(
+    input: &[u8],
+    utc_offset: i64,
+) -> IResult<&[u8], (Option<&[u8]>, MessageType, EventID)> {
+    tuple((
+        opt(terminated(parse_node, space1)),
+        terminated(parse_type, space1),
+        map_opt(
+            delimited(
+                tag("msg=audit("),
+                tuple((
+                    terminated(parse_date, space1),
+                    parse_time,
+                    preceded(tag(":"), dec_u32),
+                )),
+                tuple((tag(")"), space0, tag(":"), space0)),
+            ),
+            move |(days, ms, sequence)| {
+                let timestamp = days * 86_400_000 + ms - utc_offset * 1000;
+                Some(EventID {
+                    timestamp: u64::try_from(timestamp).ok()?,
+                    sequence,
+                })
+            },
+        ),
+    ))(input)
+}
+
+/// Recognize a date, return the number of days since the epoch
+fn parse_date(input: &[u8]) -> IResult<&[u8], i64> {
+    map_opt(
+        alt((
+            map(
+                tuple((dec_u32, tag("/"), dec_u32, tag("/"), dec_u32)),
+                |(m, _, d, _, y)| (y, m, d),
+            ),
+            map(
+                tuple((dec_u32, tag("-"), dec_u32, tag("-"), dec_u32)),
+                |(y, _, m, _, d)| (y, m, d),
+            ),
+            map(
+                tuple((dec_u32, tag("."), dec_u32, tag("."), dec_u32)),
+                |(d, _, m, _, y)| (y, m, d),
+            ),
+        )),
+        |(y, m, d)| {
+            let y = if y < 100 { y + 2000 } else { y };
+            if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+                return None;
+            }
+            Some(days_from_civil(y.into(), m.into(), d.into()))
+        },
+    )(input)
+}
+
+/// Recognize a time of day `HH:MM:SS.mmm`, return milliseconds
+fn parse_time(input: &[u8]) -> IResult<&[u8], i64> {
+    map_opt(
+        tuple((
+            terminated(dec_u32, tag(":")),
+            terminated(dec_u32, tag(":")),
+            terminated(dec_u32, tag(".")),
+            dec_u32,
+        )),
+        |(h, m, s, ms)| {
+            if h > 23 || m > 59 || s > 60 || ms > 999 {
+                return None;
+            }
+            Some(((i64::from(h) * 60 + i64::from(m)) * 60 + i64::from(s)) * 1000 + i64::from(ms))
+        },
+    )(input)
+}
+
+/// Number of days between 1970-01-01 and the given date in the
+/// proleptic Gregorian calendar
+pub(crate) fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn parse_interpreted_body(input: &[u8], ty: MessageType) -> Option<Vec<(Key, Value<'_>)>> {
+    let mut kv = vec![];
+    let mut rest = input.trim_ascii();
+    while !rest.is_empty() {
+        let (r, key) = parse_interpreted_key(rest, ty).ok()?;
+        let (r, value) = parse_interpreted_value(r, &key, ty)?;
+        kv.push((key, value));
+        rest = r.trim_ascii_start();
+    }
+    Some(kv)
+}
+
+fn parse_interpreted_key(input: &[u8], ty: MessageType) -> IResult<&[u8], Key> {
+    match ty {
+        MessageType::EXECVE if !input.starts_with(b"argc") => terminated(
+            alt((parse_key_a_x_len, parse_key_a_xy, parse_key_a_x, parse_key)),
+            tag("="),
+        )(input),
+        MessageType::SYSCALL => terminated(alt((parse_key_a_x, parse_key)), tag("="))(input),
+        _ => terminated(parse_key, tag("="))(input),
+    }
+}
+
+/// Recognize a value. Quoted values end at the closing quote,
+/// unquoted values at the next `key=`.
+fn parse_interpreted_value<'a>(
+    input: &'a [u8],
+    key: &Key,
+    ty: MessageType,
+) -> Option<(&'a [u8], Value<'a>)> {
+    if let Some(inner) = input.strip_prefix(b"'") {
+        let end = inner.iter().position(|c| *c == b'\'')?;
+        let (s, rest) = (&inner[..end], &inner[end + 1..]);
+        let value = match key {
+            Key::Common(Common::Msg) => match parse_interpreted_body(s, ty) {
+                Some(kv) if !kv.is_empty() => Value::Map(kv),
+                _ => Value::Str(s, Quote::Single),
+            },
+            _ => Value::Str(s, Quote::Single),
+        };
+        return Some((rest, value));
+    }
+    if let Some(inner) = input.strip_prefix(b"\"") {
+        let end = inner.iter().position(|c| *c == b'"')?;
+        return Some((&inner[end + 1..], Value::Str(&inner[..end], Quote::Double)));
+    }
+
+    let mut end = input.len();
+    for (i, c) in input.iter().enumerate() {
+        if *c == b' ' && is_key_ahead(input[i..].trim_ascii_start()) {
+            end = i;
+            break;
+        }
+    }
+    let (s, rest) = input.split_at(end);
+    let value = match s {
+        b"" | b"?" | b"(null)" => Value::Empty,
+        _ => {
+            let ps = str::from_utf8(s).unwrap_or_default();
+            if let Some(n) = ps
+                .strip_prefix("0x")
+                .and_then(|h| u64::from_str_radix(h, 16).ok())
+            {
+                Value::Number(Number::Hex(n))
+            } else if let Ok(n) = ps.parse::<i64>() {
+                Value::Number(Number::Dec(n))
+            } else {
+                Value::Str(s, Quote::None)
+            }
+        }
+    };
+    Some((rest, value))
+}
+
+fn is_key_ahead(input: &[u8]) -> bool {
+    let r: IResult<&[u8], Key> = terminated(parse_key, tag("="))(input);
+    r.is_ok()
+}
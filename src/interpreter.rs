@@ -0,0 +1,745 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// Human-readable form of a value, see [`Interpreters`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interpreted {
+    /// A symbolic name, e.g. `x86_64`, `EACCES`, `SIGSEGV`
+    Name(String),
+    /// A set of flags or names, e.g. capabilities
+    Flags(Vec<String>),
+    /// Free-form text
+    Text(String),
+}
+
+/// Flags are joined with `|`.
+impl Display for Interpreted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interpreted::Name(s) | Interpreted::Text(s) => f.write_str(s),
+            Interpreted::Flags(fs) => f.write_str(&fs.join("|")),
+        }
+    }
+}
+
+/// Context in which a value is interpreted
+#[derive(Debug, Clone, Copy)]
+pub struct InterpretContext<'b> {
+    /// Type of the record the value belongs to
+    pub ty: MessageType,
+    /// The record's body, if available, e.g. for values whose meaning
+    /// depends on `arch`
+    pub body: Option<&'b Body<'b>>,
+}
+
+/// Turns raw values into human-readable ones, see [`Interpreters`]
+pub trait Interpreter: Send + Sync {
+    /// Name by which the interpreter can be selected
+    fn name(&self) -> &str;
+    /// Returns the interpretation of `value` in the field `key`, or
+    /// `None` if the interpreter is not responsible for the field or
+    /// does not recognize the value.
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted>;
+}
+
+/// Returns the names of the built-in interpreters, in the order in
+/// which they are applied by [`Interpreters::default`]
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// assert_eq!(builtin_interpreters()[..2], ["arch", "caps"]);
+/// ```
+pub fn builtin_interpreters() -> Vec<String> {
+    Interpreters::default().names().map(String::from).collect()
+}
+
+/// `arch` fields: architecture names as printed by _ausearch(8)_, see
+/// [`ArchInfo`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArchInterpreter;
+
+impl Interpreter for ArchInterpreter {
+    fn name(&self) -> &str {
+        "arch"
+    }
+
+    fn interpret(&self, _: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        if key != "arch" {
+            return None;
+        }
+        let arch = u32::try_from(value.to_u64()?).ok()?;
+        Some(Interpreted::Name(ArchInfo::from(arch).to_string()))
+    }
+}
+
+/// Capabilities, as defined in `linux/capability.h`
+const CAPABILITIES: &[&str] = &[
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+/// Returns the symbolic name of capability `cap`, e.g.
+/// `CAP_NET_RAW` for 13.
+pub fn capability_name(cap: u32) -> Option<&'static str> {
+    CAPABILITIES.get(cap as usize).copied()
+}
+
+/// Returns the names of the capabilities in the set `mask`, as found
+/// in `cap_fp`, `cap_pe`, … fields, ordered by capability number.
+/// Bits that do not correspond to a known capability are skipped.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// assert_eq!(capabilities(0x3000), ["CAP_NET_ADMIN", "CAP_NET_RAW"]);
+/// ```
+pub fn capabilities(mask: u64) -> Vec<&'static str> {
+    (0..64)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .filter_map(capability_name)
+        .collect()
+}
+
+/// Capability sets (`cap_fp`, `fp`, `old_pp`, `new_pe`, `pa`, …,
+/// e.g. in `PATH` and `BPRM_FCAPS` records) and single capabilities
+/// (`capability`, e.g. in `AVC` records). Sets are expanded into
+/// capability names; unknown bits are given as numbers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CapsInterpreter;
+
+impl Interpreter for CapsInterpreter {
+    fn name(&self) -> &str {
+        "caps"
+    }
+
+    fn interpret(&self, _: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        let name = key.to_string();
+        match name.as_str() {
+            "capability" => {
+                let cap = u32::try_from(value.to_u64()?).ok()?;
+                capability_name(cap).map(|s| Interpreted::Name(s.into()))
+            }
+            "cap_fp" | "cap_fi" | "cap_pp" | "cap_pi" | "cap_pe" | "cap_pa" | "fp" | "fi"
+            | "pp" | "pi" | "pe" | "pa" | "old_pp" | "old_pi" | "old_pe" | "old_pa" | "new_pp"
+            | "new_pi" | "new_pe" => {
+                let Value::Number(Number::Hex(mask)) = value else {
+                    return None;
+                };
+                Some(Interpreted::Flags(
+                    (0..64)
+                        .filter(|bit| mask & (1 << bit) != 0)
+                        .map(|bit| match capability_name(bit) {
+                            Some(s) => s.into(),
+                            None => bit.to_string(),
+                        })
+                        .collect(),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Error numbers, as defined in `asm-generic/errno.h`. Alpha, MIPS,
+/// PA-RISC, and SPARC use different values.
+const ERRNOS: &[(i64, &str)] = &[
+    (1, "EPERM"),
+    (2, "ENOENT"),
+    (3, "ESRCH"),
+    (4, "EINTR"),
+    (5, "EIO"),
+    (6, "ENXIO"),
+    (7, "E2BIG"),
+    (8, "ENOEXEC"),
+    (9, "EBADF"),
+    (10, "ECHILD"),
+    (11, "EAGAIN"),
+    (12, "ENOMEM"),
+    (13, "EACCES"),
+    (14, "EFAULT"),
+    (15, "ENOTBLK"),
+    (16, "EBUSY"),
+    (17, "EEXIST"),
+    (18, "EXDEV"),
+    (19, "ENODEV"),
+    (20, "ENOTDIR"),
+    (21, "EISDIR"),
+    (22, "EINVAL"),
+    (23, "ENFILE"),
+    (24, "EMFILE"),
+    (25, "ENOTTY"),
+    (26, "ETXTBSY"),
+    (27, "EFBIG"),
+    (28, "ENOSPC"),
+    (29, "ESPIPE"),
+    (30, "EROFS"),
+    (31, "EMLINK"),
+    (32, "EPIPE"),
+    (33, "EDOM"),
+    (34, "ERANGE"),
+    (35, "EDEADLK"),
+    (36, "ENAMETOOLONG"),
+    (37, "ENOLCK"),
+    (38, "ENOSYS"),
+    (39, "ENOTEMPTY"),
+    (40, "ELOOP"),
+    (42, "ENOMSG"),
+    (43, "EIDRM"),
+    (44, "ECHRNG"),
+    (45, "EL2NSYNC"),
+    (46, "EL3HLT"),
+    (47, "EL3RST"),
+    (48, "ELNRNG"),
+    (49, "EUNATCH"),
+    (50, "ENOCSI"),
+    (51, "EL2HLT"),
+    (52, "EBADE"),
+    (53, "EBADR"),
+    (54, "EXFULL"),
+    (55, "ENOANO"),
+    (56, "EBADRQC"),
+    (57, "EBADSLT"),
+    (59, "EBFONT"),
+    (60, "ENOSTR"),
+    (61, "ENODATA"),
+    (62, "ETIME"),
+    (63, "ENOSR"),
+    (64, "ENONET"),
+    (65, "ENOPKG"),
+    (66, "EREMOTE"),
+    (67, "ENOLINK"),
+    (68, "EADV"),
+    (69, "ESRMNT"),
+    (70, "ECOMM"),
+    (71, "EPROTO"),
+    (72, "EMULTIHOP"),
+    (73, "EDOTDOT"),
+    (74, "EBADMSG"),
+    (75, "EOVERFLOW"),
+    (76, "ENOTUNIQ"),
+    (77, "EBADFD"),
+    (78, "EREMCHG"),
+    (79, "ELIBACC"),
+    (80, "ELIBBAD"),
+    (81, "ELIBSCN"),
+    (82, "ELIBMAX"),
+    (83, "ELIBEXEC"),
+    (84, "EILSEQ"),
+    (85, "ERESTART"),
+    (86, "ESTRPIPE"),
+    (87, "EUSERS"),
+    (88, "ENOTSOCK"),
+    (89, "EDESTADDRREQ"),
+    (90, "EMSGSIZE"),
+    (91, "EPROTOTYPE"),
+    (92, "ENOPROTOOPT"),
+    (93, "EPROTONOSUPPORT"),
+    (94, "ESOCKTNOSUPPORT"),
+    (95, "EOPNOTSUPP"),
+    (96, "EPFNOSUPPORT"),
+    (97, "EAFNOSUPPORT"),
+    (98, "EADDRINUSE"),
+    (99, "EADDRNOTAVAIL"),
+    (100, "ENETDOWN"),
+    (101, "ENETUNREACH"),
+    (102, "ENETRESET"),
+    (103, "ECONNABORTED"),
+    (104, "ECONNRESET"),
+    (105, "ENOBUFS"),
+    (106, "EISCONN"),
+    (107, "ENOTCONN"),
+    (108, "ESHUTDOWN"),
+    (109, "ETOOMANYREFS"),
+    (110, "ETIMEDOUT"),
+    (111, "ECONNREFUSED"),
+    (112, "EHOSTDOWN"),
+    (113, "EHOSTUNREACH"),
+    (114, "EALREADY"),
+    (115, "EINPROGRESS"),
+    (116, "ESTALE"),
+    (117, "EUCLEAN"),
+    (118, "ENOTNAM"),
+    (119, "ENAVAIL"),
+    (120, "EISNAM"),
+    (121, "EREMOTEIO"),
+    (122, "EDQUOT"),
+    (123, "ENOMEDIUM"),
+    (124, "EMEDIUMTYPE"),
+    (125, "ECANCELED"),
+    (126, "ENOKEY"),
+    (127, "EKEYEXPIRED"),
+    (128, "EKEYREVOKED"),
+    (129, "EKEYREJECTED"),
+    (130, "EOWNERDEAD"),
+    (131, "ENOTRECOVERABLE"),
+    (132, "ERFKILL"),
+    (133, "EHWPOISON"),
+];
+
+/// Returns the symbolic name of the error number `errno`, e.g.
+/// `EACCES` for 13.
+pub fn errno_name(errno: i64) -> Option<&'static str> {
+    ERRNOS
+        .binary_search_by_key(&errno, |(n, _)| *n)
+        .ok()
+        .map(|i| ERRNOS[i].1)
+}
+
+/// `exit` fields of failed `SYSCALL` records: negative values are
+/// error numbers, e.g. `-13` becomes `EACCES`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ErrnoInterpreter;
+
+impl Interpreter for ErrnoInterpreter {
+    fn name(&self) -> &str {
+        "errno"
+    }
+
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        if ctx.ty != MessageType::SYSCALL || key != "exit" {
+            return None;
+        }
+        if let Some(body) = ctx.body {
+            if body.get("success").and_then(value_bytes) == Some(b"yes") {
+                return None;
+            }
+        }
+        match value {
+            Value::Number(Number::Dec(n)) if *n < 0 => {
+                errno_name(-n).map(|s| Interpreted::Name(s.into()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Memory protection flags, as defined in `asm-generic/mman-common.h`
+const PROT_FLAGS: &[(u64, &str)] = &[
+    (0x1, "PROT_READ"),
+    (0x2, "PROT_WRITE"),
+    (0x4, "PROT_EXEC"),
+    (0x8, "PROT_SEM"),
+    (0x0100_0000, "PROT_GROWSDOWN"),
+    (0x0200_0000, "PROT_GROWSUP"),
+];
+
+/// Mapping types, the lowest four bits of mapping flags
+const MAP_TYPES: &[(u64, &str)] = &[
+    (0x1, "MAP_SHARED"),
+    (0x2, "MAP_PRIVATE"),
+    (0x3, "MAP_SHARED_VALIDATE"),
+    (0x8, "MAP_DROPPABLE"),
+];
+
+/// Mapping flags, as defined in `asm-generic/mman-common.h` and
+/// `asm-generic/mman.h`. Alpha, MIPS, PA-RISC, and SPARC use
+/// different values.
+const MAP_FLAGS: &[(u64, &str)] = &[
+    (0x10, "MAP_FIXED"),
+    (0x20, "MAP_ANONYMOUS"),
+    (0x40, "MAP_32BIT"),
+    (0x100, "MAP_GROWSDOWN"),
+    (0x800, "MAP_DENYWRITE"),
+    (0x1000, "MAP_EXECUTABLE"),
+    (0x2000, "MAP_LOCKED"),
+    (0x4000, "MAP_NORESERVE"),
+    (0x8000, "MAP_POPULATE"),
+    (0x1_0000, "MAP_NONBLOCK"),
+    (0x2_0000, "MAP_STACK"),
+    (0x4_0000, "MAP_HUGETLB"),
+    (0x8_0000, "MAP_SYNC"),
+    (0x10_0000, "MAP_FIXED_NOREPLACE"),
+    (0x400_0000, "MAP_UNINITIALIZED"),
+];
+
+/// Expands `mask` into the names found in `table`; remaining bits
+/// are given as one hexadecimal number.
+fn flag_names(table: &[(u64, &str)], mask: u64) -> Vec<String> {
+    let mut names = vec![];
+    let mut rest = mask;
+    for (bit, name) in table {
+        if mask & bit != 0 {
+            names.push(name.to_string());
+            rest &= !bit;
+        }
+    }
+    if rest != 0 {
+        names.push(format!("0x{rest:x}"));
+    }
+    names
+}
+
+/// Returns the names of the memory protection flags in `prot`, e.g.
+/// `PROT_READ`, `PROT_EXEC` for 5, or `PROT_NONE` for 0.
+pub fn prot_flags(prot: u64) -> Vec<String> {
+    match prot {
+        0 => vec!["PROT_NONE".into()],
+        _ => flag_names(PROT_FLAGS, prot),
+    }
+}
+
+/// Returns the names of the mapping type and flags in `flags`, e.g.
+/// `MAP_PRIVATE`, `MAP_ANONYMOUS` for 0x22.
+pub fn map_flags(flags: u64) -> Vec<String> {
+    let ty = flags & 0xf;
+    let mut names = match MAP_TYPES.iter().find(|(n, _)| *n == ty) {
+        Some((_, name)) => vec![name.to_string()],
+        None if ty == 0 => vec![],
+        None => vec![format!("0x{ty:x}")],
+    };
+    names.extend(flag_names(MAP_FLAGS, flags & !0xf));
+    names
+}
+
+/// Memory mapping and protection flags: `flags` in `MMAP` records,
+/// and the `prot` and `flags` arguments in `SYSCALL` records of
+/// `mmap`, `mprotect`, and `pkey_mprotect`
+///
+/// The syscall is recognized by the enriched `SYSCALL` field, or by
+/// its number on x86_64, i386, and aarch64.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MmapInterpreter;
+
+impl MmapInterpreter {
+    fn syscall(body: &Body) -> Option<&'static str> {
+        if let Some(name) = body.get("SYSCALL").and_then(value_bytes) {
+            return ["mmap", "mmap2", "mprotect", "pkey_mprotect"]
+                .into_iter()
+                .find(|s| s.as_bytes() == name);
+        }
+        let arch = ArchInfo::from(u32::try_from(body.get("arch")?.to_u64()?).ok()?);
+        let syscall = match body.get("syscall")? {
+            Value::Number(n) => n.as_i64()?,
+            _ => return None,
+        };
+        match (arch.machine_name()?, syscall) {
+            ("x86_64", 9) | ("aarch64", 222) => Some("mmap"),
+            ("i386", 192) => Some("mmap2"),
+            ("x86_64", 10) | ("i386", 125) | ("aarch64", 226) => Some("mprotect"),
+            ("x86_64", 329) | ("i386", 380) | ("aarch64", 288) => Some("pkey_mprotect"),
+            _ => None,
+        }
+    }
+}
+
+impl Interpreter for MmapInterpreter {
+    fn name(&self) -> &str {
+        "mmap"
+    }
+
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        let mask = value.to_u64()?;
+        match (ctx.ty, key) {
+            (MessageType::MMAP, _) if key == "flags" => Some(Interpreted::Flags(map_flags(mask))),
+            (MessageType::SYSCALL, Key::Arg(n, None)) => match (Self::syscall(ctx.body?)?, n) {
+                (_, 2) => Some(Interpreted::Flags(prot_flags(mask))),
+                ("mmap" | "mmap2", 3) => Some(Interpreted::Flags(map_flags(mask))),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// File modes, see [`FileMode`]: `mode` fields, e.g. in `PATH`,
+/// `IPC`, and `MQ_OPEN` records, and the mode argument in `SYSCALL`
+/// records of `chmod`, `fchmod`, `fchmodat`, and `fchmodat2`
+///
+/// The syscall is recognized by the enriched `SYSCALL` field, or by
+/// its number on x86_64, i386, and aarch64.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModeInterpreter;
+
+impl ModeInterpreter {
+    /// Returns the position of the mode argument.
+    fn mode_arg(body: &Body) -> Option<u32> {
+        if let Some(name) = body.get("SYSCALL").and_then(value_bytes) {
+            return match name {
+                b"chmod" | b"fchmod" => Some(1),
+                b"fchmodat" | b"fchmodat2" => Some(2),
+                _ => None,
+            };
+        }
+        let arch = ArchInfo::from(u32::try_from(body.get("arch")?.to_u64()?).ok()?);
+        let syscall = match body.get("syscall")? {
+            Value::Number(n) => n.as_i64()?,
+            _ => return None,
+        };
+        match (arch.machine_name()?, syscall) {
+            ("x86_64", 90 | 91) | ("i386", 15 | 94) | ("aarch64", 52) => Some(1),
+            ("x86_64", 268) | ("i386", 306) | ("aarch64", 53) => Some(2),
+            ("x86_64" | "i386" | "aarch64", 452) => Some(2),
+            _ => None,
+        }
+    }
+}
+
+impl Interpreter for ModeInterpreter {
+    fn name(&self) -> &str {
+        "mode"
+    }
+
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        let is_mode = match (ctx.ty, key) {
+            (MessageType::SYSCALL, Key::Arg(n, None)) => Self::mode_arg(ctx.body?) == Some(*n),
+            _ => key == "mode",
+        };
+        if !is_mode {
+            return None;
+        }
+        Some(Interpreted::Text(value.file_mode()?.to_string()))
+    }
+}
+
+/// Signal numbers, as defined in `asm-generic/signal.h`
+const SIGNALS: &[&str] = &[
+    "SIGHUP",
+    "SIGINT",
+    "SIGQUIT",
+    "SIGILL",
+    "SIGTRAP",
+    "SIGABRT",
+    "SIGBUS",
+    "SIGFPE",
+    "SIGKILL",
+    "SIGUSR1",
+    "SIGSEGV",
+    "SIGUSR2",
+    "SIGPIPE",
+    "SIGALRM",
+    "SIGTERM",
+    "SIGSTKFLT",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGSTOP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGXCPU",
+    "SIGXFSZ",
+    "SIGVTALRM",
+    "SIGPROF",
+    "SIGWINCH",
+    "SIGIO",
+    "SIGPWR",
+    "SIGSYS",
+];
+
+/// Returns the symbolic name of signal `sig`, e.g. `SIGSEGV` for 11.
+/// Real-time signals are returned as `SIGRTMIN+n`.
+pub fn signal_name(sig: i64) -> Option<String> {
+    match sig {
+        1..=31 => Some(SIGNALS[sig as usize - 1].into()),
+        32 => Some("SIGRTMIN".into()),
+        33..=64 => Some(format!("SIGRTMIN+{}", sig - 32)),
+        _ => None,
+    }
+}
+
+/// `sig` fields, e.g. in `ANOM_ABEND`, `OBJ_PID`, `SECCOMP` records
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignalInterpreter;
+
+impl Interpreter for SignalInterpreter {
+    fn name(&self) -> &str {
+        "signal"
+    }
+
+    fn interpret(&self, _: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        if key != "sig" {
+            return None;
+        }
+        match value {
+            Value::Number(n) => signal_name(n.as_i64()?).map(Interpreted::Name),
+            _ => None,
+        }
+    }
+}
+
+/// `saddr` fields of `SOCKADDR` records, shown like the `SADDR` field
+/// of enriched logs, see [`SockAddr`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SockAddrInterpreter;
+
+impl Interpreter for SockAddrInterpreter {
+    fn name(&self) -> &str {
+        "sockaddr"
+    }
+
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        if ctx.ty != MessageType::SOCKADDR || key != "saddr" {
+            return None;
+        }
+        let addr = SockAddr::try_from(value).ok()?;
+        Some(Interpreted::Text(addr.to_string()))
+    }
+}
+
+/// A set of [`Interpreter`]s
+///
+/// Interpretation is not done by the parser, so that parsing stays
+/// fast; it is applied on demand to single values or whole records.
+/// Interpreters are tried in the order in which they have been added,
+/// the first result wins.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let interpreters = Interpreters::default();
+/// let msg = parse(&b"type=ANOM_ABEND msg=audit(1.000:1): pid=1000 comm=\"x\" sig=11\n"[..], false).unwrap();
+/// let sig = msg.body.get("sig").unwrap();
+/// let key = Key::Literal("sig");
+/// assert_eq!(interpreters.apply(msg.ty, &key, sig).unwrap().to_string(), "SIGSEGV");
+/// ```
+pub struct Interpreters {
+    list: Vec<Box<dyn Interpreter>>,
+}
+
+impl fmt::Debug for Interpreters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.list.iter().map(|i| i.name()))
+            .finish()
+    }
+}
+
+/// All built-in interpreters, see [`builtin_interpreters`]
+impl Default for Interpreters {
+    fn default() -> Self {
+        let interpreters = Interpreters::new()
+            .register(ArchInterpreter)
+            .register(CapsInterpreter)
+            .register(ErrnoInterpreter)
+            .register(MmapInterpreter)
+            .register(ModeInterpreter)
+            .register(SignalInterpreter)
+            .register(SockAddrInterpreter);
+        #[cfg(feature = "syscalls")]
+        let interpreters = interpreters.register(SyscallInterpreter);
+        interpreters
+    }
+}
+
+impl Interpreters {
+    /// Constructs an empty set.
+    pub fn new() -> Self {
+        Interpreters { list: vec![] }
+    }
+
+    /// Adds an interpreter.
+    pub fn register<I: Interpreter + 'static>(mut self, interpreter: I) -> Self {
+        self.list.push(Box::new(interpreter));
+        self
+    }
+
+    /// Keeps only the interpreters named in `names`.
+    pub fn select(mut self, names: &[&str]) -> Self {
+        self.list.retain(|i| names.contains(&i.name()));
+        self
+    }
+
+    /// Removes the interpreter called `name`.
+    pub fn without(mut self, name: &str) -> Self {
+        self.list.retain(|i| i.name() != name);
+        self
+    }
+
+    /// Returns the names of the interpreters, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(|i| i.name())
+    }
+
+    fn apply_ctx(&self, ctx: &InterpretContext, key: &Key, value: &Value) -> Option<Interpreted> {
+        self.list.iter().find_map(|i| i.interpret(ctx, key, value))
+    }
+
+    /// Interprets a single value of a record of type `ty`. Interpreters
+    /// that need other fields of the record do not see them; use
+    /// [`apply_body`](Self::apply_body) where possible.
+    pub fn apply(&self, ty: MessageType, key: &Key, value: &Value) -> Option<Interpreted> {
+        self.apply_ctx(&InterpretContext { ty, body: None }, key, value)
+    }
+
+    /// Appends the interpretations of the values of a record of type
+    /// `ty` as fields named like the original in upper case, the way
+    /// _auditd(8)_ adds resolved values with `log_format=ENRICHED`,
+    /// e.g. `CAP_FP=CAP_CHOWN|CAP_KILL` for `cap_fp=21`. Fields that
+    /// are already present, e.g. `ARCH` in enriched logs, are not
+    /// added again. Returns the number of fields added.
+    ///
+    /// ```
+    /// # use linux_audit_parser::*;
+    /// let mut msg = parse(&b"type=BPRM_FCAPS msg=audit(1.000:1): fver=2 fp=3000 fe=1 pe=3000 frootid=0\n"[..], false).unwrap();
+    /// let caps = Interpreters::default().select(&["caps"]);
+    /// assert_eq!(caps.annotate(msg.ty, &mut msg.body), 2);
+    /// assert_eq!(&*msg.body.get("FP").unwrap().as_bytes().unwrap(), b"CAP_NET_ADMIN|CAP_NET_RAW");
+    /// ```
+    pub fn annotate(&self, ty: MessageType, body: &mut Body) -> usize {
+        let keys: Vec<String> = (&*body)
+            .into_iter()
+            .map(|(k, _)| k.to_string().to_ascii_uppercase())
+            .collect();
+        let added: Vec<_> = self
+            .apply_body(ty, body)
+            .into_iter()
+            .filter(|(n, _)| body.get(&keys[*n]).is_none())
+            .map(|(n, i)| (keys[n].clone(), i.to_string()))
+            .collect();
+        for (k, v) in &added {
+            body.push_str(k.as_bytes(), v.as_bytes());
+        }
+        added.len()
+    }
+
+    /// Interprets all values of a record of type `ty`, returning the
+    /// position of each interpreted entry along with the result.
+    pub fn apply_body<'b>(&self, ty: MessageType, body: &'b Body<'b>) -> Vec<(usize, Interpreted)> {
+        let ctx = InterpretContext {
+            ty,
+            body: Some(body),
+        };
+        body.into_iter()
+            .enumerate()
+            .filter_map(|(n, (k, v))| Some((n, self.apply_ctx(&ctx, k, v)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,81 @@
+use serde_json::{Map, Value as JsonValue};
+
+use crate::*;
+
+impl Value<'_> {
+    /// Converts the value to a [`serde_json::Value`], using
+    /// [`ByteEncoding::Lossy`] for byte strings.
+    pub fn to_json(&self) -> JsonValue {
+        self.to_json_with(ByteEncoding::default())
+    }
+
+    /// Converts the value to a [`serde_json::Value`], using `enc` for
+    /// byte strings.
+    ///
+    /// The result has the same shape as the output of the `Serialize`
+    /// implementation.
+    pub fn to_json_with(&self, enc: ByteEncoding) -> JsonValue {
+        match self {
+            Value::Empty => JsonValue::Null,
+            Value::Str(r, Quote::Braces) => {
+                let mut buf = Vec::with_capacity(r.len() + 2);
+                buf.push(b'{');
+                buf.extend(*r);
+                buf.push(b'}');
+                JsonValue::String(enc.encode(&buf).into_owned())
+            }
+            Value::Str(r, _) => JsonValue::String(enc.encode(r).into_owned()),
+            Value::Owned(v) => JsonValue::String(enc.encode(v).into_owned()),
+            Value::Segments(segs) => JsonValue::String(enc.encode(&segs.concat()).into_owned()),
+            Value::Number(Number::Dec(n)) => JsonValue::from(*n),
+            Value::Number(n) => JsonValue::String(n.to_string()),
+            Value::List(vs) => JsonValue::Array(vs.iter().map(|v| v.to_json_with(enc)).collect()),
+            Value::StringifiedList(vs) => match join_stringified(vs) {
+                Ok(buf) => JsonValue::String(enc.encode(&buf).into_owned()),
+                Err(_) => JsonValue::Array(vs.iter().map(|v| v.to_json_with(enc)).collect()),
+            },
+            Value::Map(vs) => JsonValue::Object(
+                vs.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_json_with(enc)))
+                    .collect(),
+            ),
+            Value::Skipped((args, bytes)) => {
+                let mut map = Map::new();
+                map.insert("skipped_args".into(), JsonValue::from(*args));
+                map.insert("skipped_bytes".into(), JsonValue::from(*bytes));
+                JsonValue::Object(map)
+            }
+            Value::Literal(s) => JsonValue::String(s.to_string()),
+        }
+    }
+}
+
+impl Body<'_> {
+    /// Converts the body to a JSON object, using
+    /// [`ByteEncoding::Lossy`] for byte strings.
+    pub fn to_json(&self) -> JsonValue {
+        self.to_json_with(ByteEncoding::default())
+    }
+
+    /// Converts the body to a JSON object, using `enc` for byte
+    /// strings. If a key occurs more than once, the last value wins.
+    pub fn to_json_with(&self, enc: ByteEncoding) -> JsonValue {
+        JsonValue::Object(
+            self.into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_json_with(enc)))
+                .collect(),
+        )
+    }
+}
+
+impl From<&Value<'_>> for JsonValue {
+    fn from(value: &Value<'_>) -> Self {
+        value.to_json()
+    }
+}
+
+impl From<&Body<'_>> for JsonValue {
+    fn from(body: &Body<'_>) -> Self {
+        body.to_json()
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "serde")]
+use std::collections::HashMap;
 use std::fmt::{self, Debug, Display};
 use std::str;
 
@@ -94,6 +96,7 @@ pub(crate) type NVec = tinyvec::TinyVec<[u8; 14]>;
 ///
 /// [`Body`]: crate::Body
 #[derive(PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Key {
     /// regular ASCII-only name as returned by parser
     Name(NVec),
@@ -111,6 +114,12 @@ pub enum Key {
     Arg(u32, Option<u16>),
     /// `a0_len` as found in `EXECVE` lines
     ArgLen(u32),
+    /// Custom field with a registered vendor prefix, e.g.
+    /// `acme_foo_bar` → (`acme`, `foo_bar`), see
+    /// [`Parser::vendor_prefixes`]
+    ///
+    /// [`Parser::vendor_prefixes`]: crate::Parser::vendor_prefixes
+    Vendor(NVec, NVec),
     /// Not returned by parser
     Literal(&'static str),
 }
@@ -121,6 +130,158 @@ impl Default for Key {
     }
 }
 
+/// Classification of a key name, computed by [`key!`] at compile time
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum KeyKind {
+    Common(Common),
+    Uid,
+    Gid,
+    Name,
+    Arg(u32),
+    ArgLen(u32),
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn ends_with(s: &[u8], suffix: &[u8]) -> bool {
+    if s.len() < suffix.len() {
+        return false;
+    }
+    let off = s.len() - suffix.len();
+    let mut i = 0;
+    while i < suffix.len() {
+        if s[off + i] != suffix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Parses the decimal number in `s[start..end]`, if any.
+const fn dec_u32(s: &[u8], start: usize, end: usize) -> Option<u32> {
+    if start >= end {
+        return None;
+    }
+    let mut n: u32 = 0;
+    let mut i = start;
+    while i < end {
+        if !s[i].is_ascii_digit() {
+            return None;
+        }
+        n = match n.checked_mul(10) {
+            Some(n) => match n.checked_add((s[i] - b'0') as u32) {
+                Some(n) => n,
+                None => return None,
+            },
+            None => return None,
+        };
+        i += 1;
+    }
+    Some(n)
+}
+
+impl Key {
+    /// Classifies `name` like the parser does: common keys, names
+    /// ending in `uid` or `gid`, and argument keys (`a0`, `a1_len`)
+    /// as found in `SYSCALL` and `EXECVE` records. Panics if `name`
+    /// is not a valid key, which fails the build when evaluated in a
+    /// `const` context.
+    #[doc(hidden)]
+    pub const fn classify(name: &str) -> KeyKind {
+        let s = name.as_bytes();
+        if s.is_empty() || !s[0].is_ascii_alphabetic() {
+            panic!("key must start with a letter");
+        }
+        let mut i = 1;
+        while i < s.len() {
+            if !(s[i].is_ascii_alphanumeric() || s[i] == b'-' || s[i] == b'_') {
+                panic!("key may only contain letters, digits, '-', and '_'");
+            }
+            i += 1;
+        }
+        if s[0] == b'a' {
+            if let Some(n) = dec_u32(s, 1, s.len()) {
+                return KeyKind::Arg(n);
+            }
+            if ends_with(s, b"_len") {
+                if let Some(n) = dec_u32(s, 1, s.len() - 4) {
+                    return KeyKind::ArgLen(n);
+                }
+            }
+        }
+        let mut i = 0;
+        while i < COMMON.len() {
+            if bytes_eq(COMMON[i].0.as_bytes(), s) {
+                return KeyKind::Common(COMMON[i].1);
+            }
+            i += 1;
+        }
+        if ends_with(s, b"uid") {
+            KeyKind::Uid
+        } else if ends_with(s, b"gid") {
+            KeyKind::Gid
+        } else {
+            KeyKind::Name
+        }
+    }
+
+    /// Builds a key from the result of [`Key::classify`].
+    #[doc(hidden)]
+    pub fn from_kind(kind: KeyKind, name: &str) -> Self {
+        match kind {
+            KeyKind::Common(c) => Key::Common(c),
+            KeyKind::Uid => Key::NameUID(NVec::from(name.as_bytes())),
+            KeyKind::Gid => Key::NameGID(NVec::from(name.as_bytes())),
+            KeyKind::Name => Key::Name(NVec::from(name.as_bytes())),
+            KeyKind::Arg(n) => Key::Arg(n, None),
+            KeyKind::ArgLen(n) => Key::ArgLen(n),
+        }
+    }
+}
+
+/// Constructs a [`Key`] from a string literal, classified at compile
+/// time the way the parser classifies keys
+///
+/// Common keys become [`Key::Common`], names ending in `uid` or `gid`
+/// become [`Key::NameUID`] or [`Key::NameGID`], `a0` and `a0_len`
+/// become [`Key::Arg`] and [`Key::ArgLen`]. The result compares equal
+/// to keys returned by the parser, which is not the case for
+/// `Key::from("euid")`. Names that the parser would not accept as
+/// keys are rejected at build time.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// assert_eq!(key!("euid"), Key::NameUID("euid".as_bytes().into()));
+/// assert_eq!(key!("pid"), Key::Common(Common::Pid));
+/// assert_eq!(key!("a1"), Key::Arg(1, None));
+/// ```
+///
+/// ```compile_fail
+/// # use linux_audit_parser::*;
+/// let k = key!("1euid");
+/// ```
+#[macro_export]
+macro_rules! key {
+    ($name:literal) => {{
+        const KIND: $crate::KeyKind = $crate::Key::classify($name);
+        $crate::Key::from_kind(KIND, $name)
+    }};
+}
+
 impl Debug for Key {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.to_string())
@@ -144,35 +305,90 @@ impl Display for Key {
                 let s = unsafe { str::from_utf8_unchecked(r) };
                 f.write_str(&str::to_ascii_uppercase(s))
             }
+            Key::Vendor(p, r) => {
+                // safety: The parser guarantees ASCII-only keys.
+                let (p, r) = unsafe { (str::from_utf8_unchecked(p), str::from_utf8_unchecked(r)) };
+                write!(f, "{p}_{r}")
+            }
             Key::Literal(s) => f.write_str(s),
         }
     }
 }
 
+/// Uses the [`Display`] implementation, so that serialized keys and
+/// `to_string()` never diverge.
 #[cfg(feature = "serde")]
 impl Serialize for Key {
     #[inline(always)]
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+/// Naming of keys in serialized output, see
+/// [`Body::with_key_naming`](crate::Body::with_key_naming)
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Default)]
+pub enum KeyNaming<'r> {
+    /// Keys as they appear in the log
+    #[default]
+    Original,
+    /// Enriched keys (`UID`, `SYSCALL`, …) in lower case. Note that
+    /// they can collide with the raw fields (`uid`, `syscall`).
+    LowercaseEnriched,
+    /// Keys are looked up by their original name; keys that are not
+    /// found are kept.
+    Map(&'r HashMap<String, String>),
+    /// Keys are passed to a function that returns the new name, or
+    /// `None` to keep the key. This also works for [`Key::Arg`] and
+    /// [`Key::ArgLen`] keys, e.g. to turn `a1[2]` into `arg_1_2`.
+    Rename(&'r dyn Fn(&Key) -> Option<String>),
+}
+
+#[cfg(feature = "serde")]
+impl Debug for KeyNaming<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Key::Arg(x, Some(y)) => s.collect_str(&format_args!("a{x}[{y}]")),
-            Key::Arg(x, None) => s.collect_str(&format_args!("a{x}")),
-            Key::ArgLen(x) => s.collect_str(&format_args!("a{x}_len")),
-            Key::Name(r) | Key::NameUID(r) | Key::NameGID(r) => {
-                // safety: The parser guarantees an ASCII-only key.
-                s.collect_str(unsafe { str::from_utf8_unchecked(r) })
-            }
-            Key::Common(c) => s.collect_str(c),
-            Key::NameTranslated(r) => {
-                // safety: The parser guarantees an ASCII-only key.
-                s.collect_str(&str::to_ascii_uppercase(unsafe {
-                    str::from_utf8_unchecked(r)
-                }))
+            KeyNaming::Original => f.write_str("Original"),
+            KeyNaming::LowercaseEnriched => f.write_str("LowercaseEnriched"),
+            KeyNaming::Map(m) => f.debug_tuple("Map").field(m).finish(),
+            KeyNaming::Rename(_) => f.write_str("Rename(..)"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl KeyNaming<'_> {
+    /// Returns the name of `key` in serialized output.
+    pub fn name(&self, key: &Key) -> String {
+        match self {
+            KeyNaming::Original => key.to_string(),
+            KeyNaming::LowercaseEnriched => match key {
+                Key::Name(r) if crate::validate::is_enriched_name(r) => {
+                    key.to_string().to_ascii_lowercase()
+                }
+                Key::NameTranslated(_) => key.to_string().to_ascii_lowercase(),
+                _ => key.to_string(),
+            },
+            KeyNaming::Map(m) => {
+                let name = key.to_string();
+                m.get(&name).cloned().unwrap_or(name)
             }
-            Key::Literal(l) => s.collect_str(l),
+            KeyNaming::Rename(f) => f(key).unwrap_or_else(|| key.to_string()),
         }
     }
 }
 
+/// Wrapper that serializes a [`Value`](crate::Value) or
+/// [`Body`](crate::Body) with keys named according to a
+/// [`KeyNaming`], see [`Body::with_key_naming`](crate::Body::with_key_naming)
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy)]
+pub struct WithKeyNaming<'a, 'r, T: ?Sized> {
+    pub inner: &'a T,
+    pub naming: KeyNaming<'r>,
+}
+
 impl PartialEq<str> for Key {
     fn eq(&self, other: &str) -> bool {
         self == other.as_bytes()
@@ -1,18 +1,134 @@
+mod activity;
+mod apparmor;
+mod arch;
+#[cfg(feature = "rkyv")]
+pub mod archive;
+#[cfg(feature = "audisp")]
+pub mod audisp;
+mod avc;
 mod body;
+mod coalescer;
+mod compat;
 mod constants;
+mod corpus;
+mod daemon;
+mod dedup;
+mod endpoint;
+mod event;
 mod event_id;
+#[cfg(feature = "serde")]
+mod fields;
+mod fileop;
+#[cfg(feature = "json")]
+mod fixture;
+mod identity;
+mod interpreted;
+mod interpreter;
+#[cfg(feature = "json")]
+mod json;
 mod key;
+mod logdir;
+mod matching;
 mod message;
 mod message_type;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod mode;
+#[cfg(feature = "json")]
+mod ndjson;
+mod object;
+mod op;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod owned;
+mod parsed;
 mod parser;
+mod partition;
+mod pid;
+pub mod prelude;
+mod pretty;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(feature = "pseudonym")]
+mod pseudonym;
+mod reader;
+#[cfg(feature = "regex")]
+mod regex_rules;
+pub mod rules;
+mod seccomp;
+mod selinux;
+mod severity;
+mod sockaddr;
+mod spec;
+#[cfg(feature = "spill")]
+mod spill;
+mod syscall;
+#[cfg(feature = "syscalls")]
+mod syscall_table;
+mod timeline;
+mod tty;
+mod unknown;
+mod validate;
 mod value;
 
+pub use activity::*;
+pub use apparmor::*;
+pub use arch::*;
+pub use avc::*;
 pub use body::*;
+pub use coalescer::*;
+pub use constants::FieldType;
+pub use corpus::*;
+pub use daemon::*;
+pub use dedup::*;
+pub use endpoint::*;
+pub use event::*;
 pub use event_id::*;
+#[cfg(feature = "serde")]
+pub use fields::*;
+pub use fileop::*;
+#[cfg(feature = "json")]
+pub use fixture::*;
+pub use identity::*;
+pub use interpreted::*;
+pub use interpreter::*;
 pub use key::*;
+pub use logdir::*;
+pub use matching::*;
 pub use message::*;
 pub use message_type::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+pub use mode::*;
+#[cfg(feature = "json")]
+pub use ndjson::*;
+pub use object::*;
+pub use op::*;
+pub use owned::*;
+pub use parsed::*;
 pub use parser::*;
+pub use partition::*;
+pub use pid::*;
+pub use pretty::*;
+#[cfg(feature = "pseudonym")]
+pub use pseudonym::*;
+pub use reader::*;
+#[cfg(feature = "regex")]
+pub use regex_rules::*;
+pub use seccomp::*;
+pub use selinux::*;
+pub use severity::*;
+pub use sockaddr::*;
+pub use spec::*;
+#[cfg(feature = "spill")]
+pub use spill::*;
+pub use syscall::*;
+#[cfg(feature = "syscalls")]
+pub use syscall_table::*;
+pub use timeline::*;
+pub use tty::*;
+pub use unknown::*;
+pub use validate::*;
 pub use value::*;
 
 #[cfg(test)]
@@ -0,0 +1,204 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::*;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+
+/// Opens a log file for reading. Files compressed using _gzip_ or
+/// _zstd_ are detected by their magic number, regardless of the file
+/// name, and decompressed transparently.
+///
+/// Decompression requires the `gzip` and `zstd` features,
+/// respectively; without them, an error of kind
+/// [`io::ErrorKind::Unsupported`] is returned for compressed files.
+pub fn open_log<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead + Send>> {
+    let mut r = BufReader::new(File::open(path)?);
+    let head = r.fill_buf()?;
+    if head.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(r),
+        )));
+        #[cfg(not(feature = "gzip"))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "gzip support not enabled",
+        ));
+    }
+    if head.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok(Box::new(BufReader::new(
+            ruzstd::decoding::StreamingDecoder::new(r)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        )));
+        #[cfg(not(feature = "zstd"))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "zstd support not enabled",
+        ));
+    }
+    Ok(Box::new(r))
+}
+
+/// Error returned by [`LogDirectoryReader`]
+#[derive(Debug, Error)]
+pub enum LogReadError {
+    /// A file could not be opened or read.
+    #[error("{}: {error}", .path.display())]
+    Io { path: PathBuf, error: io::Error },
+    /// A line could not be parsed.
+    #[error("{}:{line}: {error}", .path.display())]
+    Parse {
+        path: PathBuf,
+        /// Line number, starting at 1
+        line: usize,
+        error: ParseError,
+    },
+}
+
+/// Position of a log file in the rotation history, oldest first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Rotation {
+    /// `audit.log.N`, as rotated by _auditd(8)_
+    Numbered(std::cmp::Reverse<u32>),
+    /// `audit.log-YYYYMMDD…`, as rotated by _logrotate(8)_ with `dateext`
+    Dated(String),
+    /// `audit.log`
+    Current,
+}
+
+fn rotation(name: &str) -> Option<Rotation> {
+    let name = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".zst"))
+        .unwrap_or(name);
+    let rest = name.strip_prefix("audit.log")?;
+    if rest.is_empty() {
+        Some(Rotation::Current)
+    } else if let Some(n) = rest.strip_prefix('.') {
+        n.parse()
+            .ok()
+            .map(|n| Rotation::Numbered(std::cmp::Reverse(n)))
+    } else if let Some(d) = rest.strip_prefix('-') {
+        (!d.is_empty() && d.bytes().all(|c| c.is_ascii_digit()))
+            .then(|| Rotation::Dated(d.to_string()))
+    } else {
+        None
+    }
+}
+
+/// Iterator over the messages in all log files of a directory such
+/// as `/var/log/audit`, oldest file first
+///
+/// `audit.log`, rotated files `audit.log.1`, `audit.log.2`, … and
+/// date-suffixed files `audit.log-20240101` are read, optionally
+/// compressed (see [`open_log`]). Other files are ignored. Empty lines
+/// are skipped.
+///
+/// Errors are reported with the file name and line number; iteration
+/// continues with the next line or file.
+pub struct LogDirectoryReader {
+    parser: Parser,
+    files: Vec<PathBuf>,
+    next_file: usize,
+    current: Option<(Box<dyn BufRead + Send>, usize)>,
+    buf: Vec<u8>,
+}
+
+impl LogDirectoryReader {
+    /// Lists the log files in `dir`, using the default [`Parser`]
+    /// configuration.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let mut files = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(r) = entry.file_name().to_str().and_then(rotation) {
+                files.push((r, entry.path()));
+            }
+        }
+        files.sort();
+        Ok(LogDirectoryReader {
+            parser: Parser::default(),
+            files: files.into_iter().map(|(_, p)| p).collect(),
+            next_file: 0,
+            current: None,
+            buf: vec![],
+        })
+    }
+
+    /// Sets the [`Parser`] configuration used for all files.
+    pub fn with_parser(mut self, parser: Parser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Returns the log files in the order in which they are read.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+impl std::fmt::Debug for LogDirectoryReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("LogDirectoryReader")
+            .field("parser", &self.parser)
+            .field("files", &self.files)
+            .field("next_file", &self.next_file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for LogDirectoryReader {
+    type Item = Result<Message<'static>, LogReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((reader, line)) = &mut self.current else {
+                let path = self.files.get(self.next_file)?;
+                self.next_file += 1;
+                match open_log(path) {
+                    Ok(r) => self.current = Some((r, 0)),
+                    Err(error) => {
+                        let path = path.clone();
+                        return Some(Err(LogReadError::Io { path, error }));
+                    }
+                }
+                continue;
+            };
+            let path = &self.files[self.next_file - 1];
+            self.buf.clear();
+            match reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    self.current = None;
+                    continue;
+                }
+                Ok(_) => *line += 1,
+                Err(error) => {
+                    self.current = None;
+                    let path = path.clone();
+                    return Some(Err(LogReadError::Io { path, error }));
+                }
+            }
+            if self.buf.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            return Some(
+                self.parser
+                    .parse(&self.buf)
+                    .map_err(|error| LogReadError::Parse {
+                        path: path.clone(),
+                        line: *line,
+                        error,
+                    }),
+            );
+        }
+    }
+}
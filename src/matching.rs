@@ -0,0 +1,280 @@
+use std::borrow::Cow;
+use std::ops::{Bound, RangeBounds};
+
+use crate::*;
+
+/// Matches `s` against a shell-style glob `pattern`, byte by byte
+///
+/// `*` matches any sequence of bytes, including `/`, `?` matches a
+/// single byte, `[abc]`, `[a-z]`, and `[!a-z]` match a single byte
+/// from (or not from) a set. A backslash matches the following byte
+/// literally. A `[` without matching `]` is treated as a literal.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// assert!(glob_match(b"/tmp/*", b"/tmp/x/payload"));
+/// assert!(glob_match(b"/usr/bin/python3.[0-9]*", b"/usr/bin/python3.11"));
+/// assert!(!glob_match(b"/tmp/?", b"/tmp/ab"));
+/// ```
+pub fn glob_match(pattern: &[u8], s: &[u8]) -> bool {
+    let (mut p, mut i) = (0, 0);
+    // Position after the last `*` and the input position it has
+    // been tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while i < s.len() {
+        let step = match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p + 1, i));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => Some(1),
+            Some(b'[') => match class_match(&pattern[p..], s[i]) {
+                Some((true, len)) => Some(len),
+                Some((false, _)) => None,
+                None if s[i] == b'[' => Some(1),
+                None => None,
+            },
+            Some(b'\\') if p + 1 < pattern.len() => (pattern[p + 1] == s[i]).then_some(2),
+            Some(c) => (*c == s[i]).then_some(1),
+            None => None,
+        };
+        match (step, backtrack) {
+            (Some(len), _) => {
+                p += len;
+                i += 1;
+            }
+            (None, Some((bp, bi))) => {
+                backtrack = Some((bp, bi + 1));
+                p = bp;
+                i = bi + 1;
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+/// Matches `c` against the character class at the start of
+/// `pattern`. Returns whether it matched and the length of the class
+/// in the pattern, or `None` if the class is not terminated.
+fn class_match(pattern: &[u8], c: u8) -> Option<(bool, usize)> {
+    let mut j = 1;
+    let negate = matches!(pattern.get(j), Some(b'!' | b'^'));
+    if negate {
+        j += 1;
+    }
+    let mut found = false;
+    let mut first = true;
+    loop {
+        let lo = match pattern.get(j)? {
+            b']' if !first => break,
+            b'\\' => {
+                j += 1;
+                *pattern.get(j)?
+            }
+            lo => *lo,
+        };
+        first = false;
+        j += 1;
+        if pattern.get(j) == Some(&b'-') && pattern.get(j + 1).is_some_and(|hi| *hi != b']') {
+            let hi = pattern[j + 1];
+            found |= (lo..=hi).contains(&c);
+            j += 2;
+        } else {
+            found |= lo == c;
+        }
+    }
+    Some((found != negate, j + 1))
+}
+
+fn bound(b: Bound<&i64>) -> Bound<i128> {
+    match b {
+        Bound::Included(n) => Bound::Included((*n).into()),
+        Bound::Excluded(n) => Bound::Excluded((*n).into()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl Value<'_> {
+    /// Returns the bytes of string values, without conversion. `None`
+    /// for numbers, lists, maps, and empty values.
+    pub fn as_bytes(&self) -> Option<Cow<'_, [u8]>> {
+        match self {
+            Value::Segments(segs) => Some(Cow::Owned(segs.concat())),
+            v => value_bytes(v).map(Cow::Borrowed),
+        }
+    }
+
+    /// Returns `true` if the value is a string matching the glob
+    /// `pattern`, see [`glob_match`].
+    pub fn matches_glob(&self, pattern: &[u8]) -> bool {
+        self.as_bytes().is_some_and(|s| glob_match(pattern, &s))
+    }
+
+    /// Returns `true` if the value is a string starting with `prefix`.
+    pub fn starts_with(&self, prefix: &[u8]) -> bool {
+        self.as_bytes().is_some_and(|s| s.starts_with(prefix))
+    }
+
+    /// Returns `true` if the value is a string ending with `suffix`.
+    pub fn ends_with(&self, suffix: &[u8]) -> bool {
+        self.as_bytes().is_some_and(|s| s.ends_with(suffix))
+    }
+
+    /// Returns `true` if the value is a string containing `needle`.
+    pub fn contains_bytes(&self, needle: &[u8]) -> bool {
+        self.as_bytes()
+            .is_some_and(|s| needle.is_empty() || s.windows(needle.len()).any(|w| w == needle))
+    }
+
+    /// Returns `true` if the value is a number within `range`,
+    /// regardless of radix. Hexadecimal and octal numbers larger than
+    /// [`i64::MAX`] are above any bounded range.
+    pub fn in_range<R: RangeBounds<i64>>(&self, range: R) -> bool {
+        let n: i128 = match self {
+            Value::Number(Number::Dec(n)) => (*n).into(),
+            Value::Number(Number::Hex(n) | Number::Oct(n)) => (*n).into(),
+            _ => return false,
+        };
+        (bound(range.start_bound()), bound(range.end_bound())).contains(&n)
+    }
+
+    /// Returns `true` if the value is a list with an element for
+    /// which `pred` returns `true`.
+    pub fn list_contains<F: Fn(&Value) -> bool>(&self, pred: F) -> bool {
+        match self {
+            Value::List(vs) | Value::StringifiedList(vs) => vs.iter().any(pred),
+            _ => false,
+        }
+    }
+}
+
+/// A predicate on [`Value`]s, for use in detection rules and filters
+///
+/// String matchers compare raw bytes and never match numbers; range
+/// matchers only match numbers. Matchers can be combined with
+/// [`and`](Matcher::and), [`or`](Matcher::or), and
+/// [`not`](Matcher::not). With the `regex` feature, values can also
+/// be matched against regular expressions, see [`RegexRules`] for
+/// matching many expressions at once.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let msg = parse(&b"type=SYSCALL msg=audit(1.000:1): syscall=59 exe=\"/tmp/x/sh\" auid=1000\n"[..], false).unwrap();
+/// let rule = Matcher::Glob(b"/tmp/*".to_vec()).and(Matcher::Suffix(b"/bash".to_vec()).not());
+/// assert!(msg.body.field_matches("exe", &rule));
+/// assert!(msg.body.field_matches("auid", &Matcher::range(1000..60000)));
+/// ```
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// String equal to the given bytes
+    Exact(Vec<u8>),
+    /// String matching a glob pattern, see [`glob_match`]
+    Glob(Vec<u8>),
+    Prefix(Vec<u8>),
+    Suffix(Vec<u8>),
+    Contains(Vec<u8>),
+    /// String matching a regular expression, see
+    /// [`Value::matches_regex`]
+    #[cfg(feature = "regex")]
+    Regex(regex::bytes::Regex),
+    /// Number within bounds, see [`Value::in_range`]
+    Range(Bound<i64>, Bound<i64>),
+    /// List with an element that matches
+    ListContains(Box<Matcher>),
+    /// Empty value, e.g. `?` or `(none)` in encoded fields
+    Empty,
+    Not(Box<Matcher>),
+    /// All matchers match; `true` if there are none
+    All(Vec<Matcher>),
+    /// Any matcher matches; `false` if there are none
+    Any(Vec<Matcher>),
+}
+
+/// Regular expressions are compared by their source.
+impl PartialEq for Matcher {
+    fn eq(&self, other: &Self) -> bool {
+        use Matcher::*;
+        match (self, other) {
+            (Exact(a), Exact(b))
+            | (Glob(a), Glob(b))
+            | (Prefix(a), Prefix(b))
+            | (Suffix(a), Suffix(b))
+            | (Contains(a), Contains(b)) => a == b,
+            #[cfg(feature = "regex")]
+            (Regex(a), Regex(b)) => a.as_str() == b.as_str(),
+            (Range(a0, a1), Range(b0, b1)) => a0 == b0 && a1 == b1,
+            (ListContains(a), ListContains(b)) | (Not(a), Not(b)) => a == b,
+            (Empty, Empty) => true,
+            (All(a), All(b)) | (Any(a), Any(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Matcher {
+    /// Constructs a [`Matcher::Range`] from a range expression, e.g.
+    /// `0..1000` or `1000..`.
+    pub fn range<R: RangeBounds<i64>>(range: R) -> Self {
+        Matcher::Range(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+
+    /// Matches if both `self` and `other` match.
+    pub fn and(self, other: Matcher) -> Self {
+        match self {
+            Matcher::All(mut ms) => {
+                ms.push(other);
+                Matcher::All(ms)
+            }
+            m => Matcher::All(vec![m, other]),
+        }
+    }
+
+    /// Matches if `self` or `other` matches.
+    pub fn or(self, other: Matcher) -> Self {
+        match self {
+            Matcher::Any(mut ms) => {
+                ms.push(other);
+                Matcher::Any(ms)
+            }
+            m => Matcher::Any(vec![m, other]),
+        }
+    }
+
+    /// Matches if `self` does not match.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        match self {
+            Matcher::Not(m) => *m,
+            m => Matcher::Not(Box::new(m)),
+        }
+    }
+
+    /// Returns `true` if `v` matches.
+    pub fn matches(&self, v: &Value) -> bool {
+        match self {
+            Matcher::Exact(b) => v.as_bytes().is_some_and(|s| *s == **b),
+            Matcher::Glob(p) => v.matches_glob(p),
+            Matcher::Prefix(p) => v.starts_with(p),
+            Matcher::Suffix(p) => v.ends_with(p),
+            Matcher::Contains(p) => v.contains_bytes(p),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(re) => v.matches_regex(re),
+            Matcher::Range(lo, hi) => v.in_range((lo.as_ref(), hi.as_ref())),
+            Matcher::ListContains(m) => v.list_contains(|e| m.matches(e)),
+            Matcher::Empty => matches!(v, Value::Empty),
+            Matcher::Not(m) => !m.matches(v),
+            Matcher::All(ms) => ms.iter().all(|m| m.matches(v)),
+            Matcher::Any(ms) => ms.iter().any(|m| m.matches(v)),
+        }
+    }
+}
+
+impl Body<'_> {
+    /// Returns `true` if the first value for `key` matches `m`.
+    /// Missing fields never match, not even [`Matcher::Not`].
+    pub fn field_matches<K: AsRef<[u8]>>(&self, key: K, m: &Matcher) -> bool {
+        self.get(key).is_some_and(|v| m.matches(v))
+    }
+}
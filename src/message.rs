@@ -9,6 +9,69 @@ pub struct Message<'a> {
     pub node: Option<Vec<u8>>,
     /// Message type, corresponding to `type=…` in audit log lines
     pub ty: MessageType,
-    /// The set of key/value parirs
+    /// The set of key/value pairs
     pub body: Body<'a>,
+    /// Set if the line was incomplete and has only been parsed
+    /// partially, see [`Parser::allow_truncated`]
+    pub truncated: bool,
+    /// Set if at least one value has been decoded from hexadecimal
+    /// encoding
+    pub hex_decoded: bool,
+    /// The symbolic message type if it is not part of the
+    /// dictionary, see [`Parser::keep_unknown_types`]
+    pub type_name: Option<Vec<u8>>,
+    /// Set if the line contained the `\x1d`-separated section with
+    /// enriched values written by _auditd(8)_ (`log_format=ENRICHED`),
+    /// regardless of whether the parser kept it, see
+    /// [`Parser::enriched`]. Mixed results across a fleet point to
+    /// differing `log_format` settings.
+    pub enriched: bool,
+}
+
+/// A [`Message`] that does not borrow from anywhere, see
+/// [`Message::into_owned`]
+pub type OwnedMessage = Message<'static>;
+
+/// Size metrics for a [`Message`], see [`Message::stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MessageStats {
+    /// Number of top-level key/value pairs
+    pub fields: usize,
+    /// Number of bytes in string values, see [`Value::byte_len`]
+    pub value_bytes: usize,
+    /// Number of bytes stored in the [`Body`]'s string storage
+    pub arena_bytes: usize,
+    /// See [`Message::hex_decoded`]
+    pub hex_decoded: bool,
+    /// See [`Message::truncated`]
+    pub truncated: bool,
+}
+
+impl Message<'_> {
+    /// Returns the message with `'static` lifetime, so that it can be
+    /// sent to other threads or stored. See [`Body::into_owned`].
+    pub fn into_owned(self) -> OwnedMessage {
+        Message {
+            id: self.id,
+            node: self.node,
+            ty: self.ty,
+            body: self.body.into_owned(),
+            truncated: self.truncated,
+            hex_decoded: self.hex_decoded,
+            type_name: self.type_name,
+            enriched: self.enriched,
+        }
+    }
+
+    /// Returns size metrics, e.g. for telemetry or sampling
+    /// decisions.
+    pub fn stats(&self) -> MessageStats {
+        MessageStats {
+            fields: self.body.len(),
+            value_bytes: (&self.body).into_iter().map(|(_, v)| v.byte_len()).sum(),
+            arena_bytes: self.body.arena_bytes(),
+            hex_decoded: self.hex_decoded,
+            truncated: self.truncated,
+        }
+    }
 }
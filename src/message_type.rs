@@ -1,4 +1,5 @@
 use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Serializer};
@@ -13,6 +14,10 @@ use crate::constants::*;
 /// symbolic values is generated using CSV retrieved from the [`Linux
 /// Audit Project`]'s documentation.
 ///
+/// The [`Display`] and [`FromStr`] implementations use the symbolic
+/// names found in log lines, e.g. `SYSCALL` or `UNKNOWN[1999]`, and
+/// are guaranteed to round-trip.
+///
 /// [`Linux Audit Project`]: https://github.com/linux-audit/audit-documentation
 #[derive(PartialEq, Eq, Hash, Default, Clone, Copy)]
 pub struct MessageType(pub u32);
@@ -26,6 +31,21 @@ impl Display for MessageType {
     }
 }
 
+impl FromStr for MessageType {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(n) = EVENT_IDS.get(s.as_bytes()) {
+            return Ok(MessageType(*n));
+        }
+        s.strip_prefix("UNKNOWN[")
+            .and_then(|s| s.strip_suffix(']'))
+            .filter(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit()))
+            .and_then(|n| n.parse().ok())
+            .map(MessageType)
+            .ok_or("unknown message type")
+    }
+}
+
 impl Debug for MessageType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match EVENT_NAMES.get(&(self.0)) {
@@ -56,4 +76,175 @@ impl MessageType {
     pub fn is_multipart(&self) -> bool {
         (1300..2100).contains(&self.0) || self == &MessageType::LOGIN
     }
+
+    /// True for records that are written by _auditd(8)_ about its
+    /// own state (`DAEMON_START` … `DAEMON_ERR`).
+    pub fn is_daemon(&self) -> bool {
+        (1200..1300).contains(&self.0)
+    }
+}
+
+/// Message types below this value are stored in the bitmap of a
+/// [`MessageTypeSet`]; this covers all types defined so far.
+const SET_BITMAP_RANGE: u32 = 4096;
+
+/// Set of [`MessageType`]s, e.g. for filtering
+///
+/// Membership tests for types below 4096, which includes all
+/// types defined by the kernel and by userspace tools, are a single
+/// bit lookup. Other types are kept in a sorted list.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let set: MessageTypeSet = "SYSCALL,EXECVE,UNKNOWN[9999]".parse().unwrap();
+/// assert!(set.contains(MessageType::EXECVE));
+/// assert!(set.contains(MessageType(9999)));
+/// assert!(!set.contains(MessageType::PATH));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct MessageTypeSet {
+    bits: Box<[u64; (SET_BITMAP_RANGE / 64) as usize]>,
+    overflow: Vec<u32>,
+}
+
+impl Default for MessageTypeSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageTypeSet {
+    /// Constructs an empty set.
+    pub fn new() -> Self {
+        MessageTypeSet {
+            bits: Box::new([0; (SET_BITMAP_RANGE / 64) as usize]),
+            overflow: vec![],
+        }
+    }
+
+    /// Constructs a set from symbolic names, see
+    /// [`MessageType::from_str`].
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<Self, &'static str> {
+        names.iter().map(|n| n.as_ref().parse()).collect()
+    }
+
+    /// Adds `ty`. Returns `true` if it was not present before.
+    pub fn insert(&mut self, ty: MessageType) -> bool {
+        if ty.0 < SET_BITMAP_RANGE {
+            let (word, bit) = ((ty.0 / 64) as usize, ty.0 % 64);
+            let new = self.bits[word] & (1 << bit) == 0;
+            self.bits[word] |= 1 << bit;
+            return new;
+        }
+        match self.overflow.binary_search(&ty.0) {
+            Ok(_) => false,
+            Err(pos) => {
+                self.overflow.insert(pos, ty.0);
+                true
+            }
+        }
+    }
+
+    /// Removes `ty`. Returns `true` if it was present.
+    pub fn remove(&mut self, ty: MessageType) -> bool {
+        if ty.0 < SET_BITMAP_RANGE {
+            let (word, bit) = ((ty.0 / 64) as usize, ty.0 % 64);
+            let present = self.bits[word] & (1 << bit) != 0;
+            self.bits[word] &= !(1 << bit);
+            return present;
+        }
+        match self.overflow.binary_search(&ty.0) {
+            Ok(pos) => {
+                self.overflow.remove(pos);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns `true` if `ty` is part of the set.
+    #[inline(always)]
+    pub fn contains(&self, ty: MessageType) -> bool {
+        if ty.0 < SET_BITMAP_RANGE {
+            self.bits[(ty.0 / 64) as usize] & (1 << (ty.0 % 64)) != 0
+        } else {
+            self.overflow.binary_search(&ty.0).is_ok()
+        }
+    }
+
+    /// Returns the number of types in the set.
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>()
+            + self.overflow.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|w| *w == 0) && self.overflow.is_empty()
+    }
+
+    /// Returns the types in the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = MessageType> + '_ {
+        (0..SET_BITMAP_RANGE)
+            .filter(|n| self.bits[(n / 64) as usize] & (1 << (n % 64)) != 0)
+            .chain(self.overflow.iter().copied())
+            .map(MessageType)
+    }
+}
+
+impl Debug for MessageTypeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Types are separated by `,`, as in `ausearch -m`.
+impl Display for MessageTypeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, ty) in self.iter().enumerate() {
+            if n > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{ty}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma-separated list of symbolic names, as in `ausearch
+/// -m`.
+impl FromStr for MessageTypeSet {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromIterator<MessageType> for MessageTypeSet {
+    fn from_iter<I: IntoIterator<Item = MessageType>>(iter: I) -> Self {
+        let mut set = MessageTypeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<MessageType> for MessageTypeSet {
+    fn extend<I: IntoIterator<Item = MessageType>>(&mut self, iter: I) {
+        for ty in iter {
+            self.insert(ty);
+        }
+    }
+}
+
+impl From<&[MessageType]> for MessageTypeSet {
+    fn from(types: &[MessageType]) -> Self {
+        types.iter().copied().collect()
+    }
 }
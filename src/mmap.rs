@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::*;
+
+/// Iterator over the messages in a memory-mapped log file, see
+/// [`Parser::parse_mmap`]
+#[derive(Debug)]
+pub struct MmapMessages {
+    map: Mmap,
+    pos: usize,
+    parser: Parser,
+}
+
+impl Iterator for MmapMessages {
+    type Item = Result<Message<'static>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = &self.map[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+            let len = match rest.iter().position(|c| *c == b'\n') {
+                Some(n) => n + 1,
+                None => rest.len(),
+            };
+            let line = &rest[..len];
+            self.pos += len;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            return Some(self.parser.parse(line));
+        }
+    }
+}
+
+impl Parser {
+    /// Maps the log file at `path` into memory and returns an
+    /// iterator over its messages. Lines are parsed directly from
+    /// the mapping, without reading the file into a buffer first.
+    /// Empty lines are skipped.
+    ///
+    /// The file must not be truncated while the mapping exists;
+    /// this is the case for logs that have been rotated away by
+    /// _auditd(8)_.
+    pub fn parse_mmap<P: AsRef<Path>>(&self, path: P) -> io::Result<MmapMessages> {
+        let file = File::open(path)?;
+        // safety: see above
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MmapMessages {
+            map,
+            pos: 0,
+            parser: self.clone(),
+        })
+    }
+}
+
+/// Maps the log file at `path` into memory and returns an iterator
+/// over its messages, using the default [`Parser`] configuration. See
+/// [`Parser::parse_mmap`].
+pub fn parse_mmap<P: AsRef<Path>>(path: P) -> io::Result<MmapMessages> {
+    Parser::default().parse_mmap(path)
+}
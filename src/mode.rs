@@ -0,0 +1,114 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+const S_IFMT: u32 = 0o170000;
+const S_ISUID: u32 = 0o4000;
+const S_ISGID: u32 = 0o2000;
+const S_ISVTX: u32 = 0o1000;
+
+/// File types, as defined in `linux/stat.h`, named like
+/// _ausearch(8)_ does
+const FILE_TYPES: &[(u32, &str)] = &[
+    (0o140000, "socket"),
+    (0o120000, "link"),
+    (0o100000, "file"),
+    (0o060000, "block"),
+    (0o040000, "dir"),
+    (0o020000, "character"),
+    (0o010000, "fifo"),
+];
+
+/// File type and permissions, e.g. from the `mode` field of `PATH`
+/// records or the mode argument of _chmod(2)_
+///
+/// The [`Display`] implementation follows _ausearch(8)_: file type,
+/// special bits, and permissions in octal, separated by commas, e.g.
+/// `file,755` or `dir,sticky,1777`. Unlike _ausearch(8)_, special
+/// bits are also part of the octal permissions. Modes without file
+/// type, such as _chmod(2)_ arguments, are shown without it.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let msg = parse(&b"type=PATH msg=audit(1.000:1): item=0 name=\"/tmp\" inode=2 dev=fd:00 mode=041777 ouid=0 ogid=0 rdev=00:00\n"[..], false).unwrap();
+/// let mode = msg.body.get("mode").unwrap().file_mode().unwrap();
+/// assert_eq!(mode.file_type(), Some("dir"));
+/// assert!(mode.is_sticky());
+/// assert_eq!(mode.to_string(), "dir,sticky,1777");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileMode(pub u32);
+
+impl FileMode {
+    /// Returns the name of the file type, e.g. `file` or `dir`, or
+    /// `None` if no or an unknown type is set.
+    pub fn file_type(&self) -> Option<&'static str> {
+        FILE_TYPES
+            .iter()
+            .find(|(t, _)| *t == self.0 & S_IFMT)
+            .map(|(_, name)| *name)
+    }
+
+    /// Returns the permission bits, including the set-user-ID,
+    /// set-group-ID, and sticky bits.
+    pub fn permissions(&self) -> u32 {
+        self.0 & 0o7777
+    }
+
+    /// Returns `true` if the set-user-ID bit is set.
+    pub fn is_setuid(&self) -> bool {
+        self.0 & S_ISUID != 0
+    }
+
+    /// Returns `true` if the set-group-ID bit is set.
+    pub fn is_setgid(&self) -> bool {
+        self.0 & S_ISGID != 0
+    }
+
+    /// Returns `true` if the sticky bit is set.
+    pub fn is_sticky(&self) -> bool {
+        self.0 & S_ISVTX != 0
+    }
+}
+
+impl Display for FileMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.file_type(), self.0 & S_IFMT) {
+            (Some(name), _) => write!(f, "{name},")?,
+            (None, 0) => {}
+            (None, t) => write!(f, "{t:03o},")?,
+        }
+        for (set, name) in [
+            (self.is_setuid(), "suid"),
+            (self.is_setgid(), "sgid"),
+            (self.is_sticky(), "sticky"),
+        ] {
+            if set {
+                write!(f, "{name},")?;
+            }
+        }
+        write!(f, "{:03o}", self.permissions())
+    }
+}
+
+impl Number {
+    /// Returns the number as [`FileMode`], or `None` if it is out of
+    /// range for a mode.
+    pub fn file_mode(&self) -> Option<FileMode> {
+        match self.as_u64()? {
+            n @ 0..=0o177777 => Some(FileMode(n as u32)),
+            _ => None,
+        }
+    }
+}
+
+impl Value<'_> {
+    /// Returns the value as [`FileMode`] if it is a [`Number`], see
+    /// [`Number::file_mode`].
+    pub fn file_mode(&self) -> Option<FileMode> {
+        match self {
+            Value::Number(n) => n.file_mode(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{self, Write};
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::*;
+
+/// Writes [`Message`]s and [`Event`]s as JSON Lines, one object per
+/// line
+///
+/// Objects are serialized directly into the underlying writer, byte
+/// strings are converted using the configured [`ByteEncoding`]. Values
+/// have the same shape as produced by [`Body::to_json_with`].
+///
+/// Messages are written as
+///
+/// ```json
+/// {"id":"1615114232.375:15558","node":"work","type":"SYSCALL","body":{…}}
+/// ```
+///
+/// events as
+///
+/// ```json
+/// {"id":"1615114232.375:15558","records":[{"type":"SYSCALL","body":{…}},…]}
+/// ```
+///
+/// `node` is omitted if not set. Writes block if the writer does, so
+/// a slow pipe or socket throttles the caller; I/O errors are
+/// returned as they occur. Wrap unbuffered writers in a
+/// [`std::io::BufWriter`].
+#[derive(Debug)]
+pub struct NdjsonWriter<W: Write> {
+    inner: W,
+    encoding: ByteEncoding,
+    auto_flush: bool,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Constructs a writer that uses [`ByteEncoding::Lossy`] and
+    /// does not flush after each line.
+    pub fn new(inner: W) -> Self {
+        NdjsonWriter {
+            inner,
+            encoding: ByteEncoding::default(),
+            auto_flush: false,
+        }
+    }
+
+    /// Sets the policy for byte strings.
+    pub fn with_encoding(mut self, encoding: ByteEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// If set, the underlying writer is flushed after each line.
+    pub fn auto_flush(mut self, auto_flush: bool) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// Writes a message, followed by a newline.
+    pub fn write_message(&mut self, msg: &Message) -> io::Result<()> {
+        let enc = self.encoding;
+        self.write_line(&MessageJson { msg, enc })
+    }
+
+    /// Writes an event, followed by a newline.
+    pub fn write_event(&mut self, ev: &Event) -> io::Result<()> {
+        let enc = self.encoding;
+        self.write_line(&EventJson { ev, enc })
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut self.inner, value)?;
+        self.inner.write_all(b"\n")?;
+        if self.auto_flush {
+            self.inner.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the underlying writer. It is not flushed.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+struct MessageJson<'a, 'b> {
+    msg: &'a Message<'b>,
+    enc: ByteEncoding,
+}
+
+impl Serialize for MessageJson<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let msg = self.msg;
+        let mut map = s.serialize_map(None)?;
+        map.serialize_entry("id", &msg.id)?;
+        if let Some(node) = &msg.node {
+            map.serialize_entry("node", &self.enc.encode(node))?;
+        }
+        map.serialize_entry("type", &msg.ty)?;
+        map.serialize_entry("body", &BodyJson(&msg.body, self.enc))?;
+        map.end()
+    }
+}
+
+struct EventJson<'a, 'b> {
+    ev: &'a Event<'b>,
+    enc: ByteEncoding,
+}
+
+impl Serialize for EventJson<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let ev = self.ev;
+        let mut map = s.serialize_map(None)?;
+        map.serialize_entry("id", &ev.id)?;
+        if let Some(node) = &ev.node {
+            map.serialize_entry("node", &self.enc.encode(node))?;
+        }
+        map.serialize_entry(
+            "records",
+            &Records {
+                records: &ev.records,
+                enc: self.enc,
+            },
+        )?;
+        map.end()
+    }
+}
+
+struct Records<'a, 'b> {
+    records: &'a [(MessageType, Body<'b>)],
+    enc: ByteEncoding,
+}
+
+impl Serialize for Records<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.records.iter().map(|(ty, body)| Record {
+            ty: *ty,
+            body: BodyJson(body, self.enc),
+        }))
+    }
+}
+
+struct Record<'a, 'b> {
+    ty: MessageType,
+    body: BodyJson<'a, 'b>,
+}
+
+impl Serialize for Record<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(2))?;
+        map.serialize_entry("type", &self.ty)?;
+        map.serialize_entry("body", &self.body)?;
+        map.end()
+    }
+}
+
+struct BodyJson<'a, 'b>(&'a Body<'b>, ByteEncoding);
+
+impl Serialize for BodyJson<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_map(self.0.into_iter().map(|(k, v)| (k, ValueJson(v, self.1))))
+    }
+}
+
+struct ValueJson<'a, 'b>(&'a Value<'b>, ByteEncoding);
+
+impl Serialize for ValueJson<'_, '_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let enc = self.1;
+        match self.0 {
+            Value::Empty => s.serialize_none(),
+            Value::Str(r, Quote::Braces) => s.collect_str(&enc.encode(&[b"{", *r, b"}"].concat())),
+            Value::Str(r, _) => s.collect_str(&enc.encode(r)),
+            Value::Owned(v) => s.collect_str(&enc.encode(v)),
+            Value::Segments(segs) => s.collect_str(&enc.encode(&segs.concat())),
+            Value::Number(Number::Dec(n)) => s.serialize_i64(*n),
+            Value::Number(n) => s.collect_str(n),
+            Value::List(vs) => s.collect_seq(vs.iter().map(|v| ValueJson(v, enc))),
+            Value::Map(vs) => s.collect_map(vs.iter().map(|(k, v)| (k, ValueJson(v, enc)))),
+            Value::Skipped((args, bytes)) => {
+                let mut map = s.serialize_map(Some(2))?;
+                map.serialize_entry("skipped_args", args)?;
+                map.serialize_entry("skipped_bytes", bytes)?;
+                map.end()
+            }
+            Value::Literal(l) => s.collect_str(l),
+            Value::StringifiedList(vs) => match join_stringified(vs) {
+                Ok(buf) => s.collect_str(&enc.encode(&buf)),
+                Err(_) => s.collect_seq(vs.iter().map(|v| ValueJson(v, enc))),
+            },
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::*;
+
+/// A generic nested data model consisting of maps, arrays, byte
+/// strings, and integers, such as the one used by Vector's VRL.
+///
+/// Implementing this trait for a foreign value type allows
+/// converting parsed records using [`Body::to_object`] and
+/// [`Value::to_object`].
+pub trait ObjectModel: Sized {
+    /// Constructs an empty value
+    fn null() -> Self;
+    /// Constructs a byte string
+    fn bytes(b: &[u8]) -> Self;
+    /// Constructs an integer
+    fn integer(n: i64) -> Self;
+    /// Constructs an array
+    fn array(elems: Vec<Self>) -> Self;
+    /// Constructs a map
+    fn object(entries: Vec<(String, Self)>) -> Self;
+}
+
+impl Value<'_> {
+    /// Converts the value to an [`ObjectModel`].
+    ///
+    /// Decimal numbers are converted to integers, hexadecimal and
+    /// octal numbers to byte strings with `0x`/`0o` prefix, as with
+    /// serialization. Only variants that are produced by the parser
+    /// are converted, `None` is returned for the others.
+    pub fn to_object<T: ObjectModel>(&self) -> Option<T> {
+        match self {
+            Value::Empty => Some(T::null()),
+            Value::Str(r, Quote::Braces) => {
+                let mut buf = Vec::with_capacity(r.len() + 2);
+                buf.push(b'{');
+                buf.extend(*r);
+                buf.push(b'}');
+                Some(T::bytes(&buf))
+            }
+            Value::Str(r, _) => Some(T::bytes(r)),
+            Value::Owned(v) => Some(T::bytes(v)),
+            Value::Literal(s) => Some(T::bytes(s.as_bytes())),
+            Value::Number(Number::Dec(n)) => Some(T::integer(*n)),
+            Value::Number(n) => Some(T::bytes(n.to_string().as_bytes())),
+            Value::List(vs) => Some(T::array(vs.iter().filter_map(Value::to_object).collect())),
+            Value::Map(vs) => Some(T::object(
+                vs.iter()
+                    .filter_map(|(k, v)| Some((k.to_string(), v.to_object()?)))
+                    .collect(),
+            )),
+            Value::Segments(_) | Value::StringifiedList(_) | Value::Skipped(_) => None,
+        }
+    }
+}
+
+impl Body<'_> {
+    /// Converts the body to a map in an [`ObjectModel`]. Entries
+    /// whose values are not produced by the parser are omitted, see
+    /// [`Value::to_object`].
+    pub fn to_object<T: ObjectModel>(&self) -> T {
+        T::object(
+            self.into_iter()
+                .filter_map(|(k, v)| Some((k.to_string(), v.to_object()?)))
+                .collect(),
+        )
+    }
+}
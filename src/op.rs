@@ -0,0 +1,164 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use crate::*;
+
+/// Classification of `op=` values, see [`op_class`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpClass {
+    /// Authentication and credential handling by PAM
+    Auth,
+    /// Session setup and teardown
+    Session,
+    /// Creation, modification, and removal of accounts and groups,
+    /// password changes
+    AccountMgmt,
+    /// Changes to audit rules, audit configuration, or firewall
+    /// rules
+    PolicyChange,
+    /// Key and session handling by cryptographic services
+    Crypto,
+    /// System administration: time changes, software installation,
+    /// _auditd(8)_ state changes
+    System,
+}
+
+impl Display for OpClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OpClass::Auth => "auth",
+            OpClass::Session => "session",
+            OpClass::AccountMgmt => "account-mgmt",
+            OpClass::PolicyChange => "policy-change",
+            OpClass::Crypto => "crypto",
+            OpClass::System => "system",
+        })
+    }
+}
+
+impl FromStr for OpClass {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auth" => Ok(OpClass::Auth),
+            "session" => Ok(OpClass::Session),
+            "account-mgmt" => Ok(OpClass::AccountMgmt),
+            "policy-change" => Ok(OpClass::PolicyChange),
+            "crypto" => Ok(OpClass::Crypto),
+            "system" => Ok(OpClass::System),
+            _ => Err("unknown op class"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for OpClass {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+/// Known `op=` values, sorted for binary search
+///
+/// Sources: the kernel (`CONFIG_CHANGE`, `NETFILTER_CFG`), Linux-PAM,
+/// shadow-utils, OpenSSH, and the audit userspace tools.
+const OPS: &[(&str, OpClass)] = &[
+    ("PAM:accounting", OpClass::Auth),
+    ("PAM:authentication", OpClass::Auth),
+    ("PAM:bad_ident", OpClass::Auth),
+    ("PAM:chauthtok", OpClass::AccountMgmt),
+    ("PAM:session_close", OpClass::Session),
+    ("PAM:session_open", OpClass::Session),
+    ("PAM:setcred", OpClass::Auth),
+    ("add-group", OpClass::AccountMgmt),
+    ("add-home-dir", OpClass::AccountMgmt),
+    ("add-shadow-group", OpClass::AccountMgmt),
+    ("add-to-group", OpClass::AccountMgmt),
+    ("add-to-shadow-group", OpClass::AccountMgmt),
+    ("add-user", OpClass::AccountMgmt),
+    ("add_rule", OpClass::PolicyChange),
+    ("autoremove_rule", OpClass::PolicyChange),
+    ("change-age", OpClass::AccountMgmt),
+    ("change-system-time", OpClass::System),
+    ("changed-password", OpClass::AccountMgmt),
+    ("changing", OpClass::AccountMgmt),
+    ("changing-comment", OpClass::AccountMgmt),
+    ("changing-home-dir", OpClass::AccountMgmt),
+    ("changing-name", OpClass::AccountMgmt),
+    ("changing-primary-group", OpClass::AccountMgmt),
+    ("changing-shell", OpClass::AccountMgmt),
+    ("changing-uid", OpClass::AccountMgmt),
+    ("delete-group", OpClass::AccountMgmt),
+    ("delete-home-dir", OpClass::AccountMgmt),
+    ("delete-mail-file", OpClass::AccountMgmt),
+    ("delete-shadow-group", OpClass::AccountMgmt),
+    ("delete-user", OpClass::AccountMgmt),
+    ("deleted-password", OpClass::AccountMgmt),
+    ("destroy", OpClass::Crypto),
+    ("display", OpClass::AccountMgmt),
+    ("expired-password", OpClass::AccountMgmt),
+    ("install", OpClass::System),
+    ("locked-password", OpClass::AccountMgmt),
+    ("login", OpClass::Session),
+    ("make_equiv", OpClass::PolicyChange),
+    ("modify-group", OpClass::AccountMgmt),
+    ("nft_register_chain", OpClass::PolicyChange),
+    ("nft_register_gen", OpClass::PolicyChange),
+    ("nft_register_obj", OpClass::PolicyChange),
+    ("nft_register_rule", OpClass::PolicyChange),
+    ("nft_register_set", OpClass::PolicyChange),
+    ("nft_register_setelem", OpClass::PolicyChange),
+    ("nft_register_table", OpClass::PolicyChange),
+    ("nft_unregister_chain", OpClass::PolicyChange),
+    ("nft_unregister_obj", OpClass::PolicyChange),
+    ("nft_unregister_rule", OpClass::PolicyChange),
+    ("nft_unregister_set", OpClass::PolicyChange),
+    ("nft_unregister_setelem", OpClass::PolicyChange),
+    ("nft_unregister_table", OpClass::PolicyChange),
+    ("reconfigure", OpClass::System),
+    ("remove_rule", OpClass::PolicyChange),
+    ("set", OpClass::PolicyChange),
+    ("terminate", OpClass::System),
+    ("trim", OpClass::PolicyChange),
+    ("tty_set", OpClass::PolicyChange),
+    ("unlocked-password", OpClass::AccountMgmt),
+    ("updated-password", OpClass::AccountMgmt),
+];
+
+/// Returns the classification of a known `op=` value.
+///
+/// `op` is compared exactly; values not found in the built-in table
+/// yield `None`. Ambiguous values, e.g. `start`, which is used both
+/// by OpenSSH for `CRYPTO_SESSION` and by other services, are not
+/// classified.
+pub fn op_class(op: &[u8]) -> Option<OpClass> {
+    OPS.binary_search_by_key(&op, |(s, _)| s.as_bytes())
+        .ok()
+        .map(|i| OPS[i].1)
+}
+
+/// Returns all known `op=` values with their classification.
+pub fn known_ops() -> impl Iterator<Item = (&'static str, OpClass)> {
+    OPS.iter().copied()
+}
+
+impl Message<'_> {
+    /// Returns the `op` value of the message, looking into the
+    /// `msg='…'` map of userspace messages as well.
+    pub fn op(&self) -> Option<&[u8]> {
+        let v = self.body.get("op").or_else(|| match self.body.get("msg") {
+            Some(Value::Map(m)) => m.iter().find(|(k, _)| k == "op").map(|(_, v)| v),
+            _ => None,
+        })?;
+        value_bytes(v)
+    }
+
+    /// Returns the classification of the message's `op` value, see
+    /// [`op_class`].
+    pub fn op_class(&self) -> Option<OpClass> {
+        self.op().and_then(op_class)
+    }
+}
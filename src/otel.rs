@@ -0,0 +1,220 @@
+//! Conversion of [`Message`](crate::Message) and
+//! [`Event`](crate::Event) into records shaped like the
+//! [OpenTelemetry log data model]
+//!
+//! The types mirror `LogRecord` and `AnyValue` without depending on an
+//! OpenTelemetry SDK, so that exporters can map them to whatever
+//! client library they use. Fields are turned into attributes named
+//! by [`attribute_name`]:
+//!
+//! - `linux_audit.<type>.<key>`, e.g. `linux_audit.syscall.exe`,
+//!   with the message type in lower case
+//! - `linux_audit.<type>.<n>.<key>` for the second and further
+//!   records of the same type in an event, e.g.
+//!   `linux_audit.path.1.name`
+//! - enriched fields get a `_name` suffix, e.g.
+//!   `linux_audit.syscall.uid_name` for `UID`
+//!
+//! In addition, every record carries `linux_audit.sequence`,
+//! `linux_audit.record_types`, and `host.name` if a node name is
+//! present. Decimal numbers become integers, hexadecimal and octal
+//! numbers become strings with radix prefix, strings that are not
+//! valid UTF-8 become bytes. Empty and skipped values are left out.
+//!
+//! [OpenTelemetry log data model]: https://opentelemetry.io/docs/specs/otel/logs/data-model/
+//!
+//! ```
+//! # use linux_audit_parser::*;
+//! let msg = parse(&b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n"[..], false).unwrap();
+//! let rec = otel::LogRecord::from(&msg);
+//! assert_eq!(rec.time_unix_nano, 1_000_000_000);
+//! assert_eq!(
+//!     rec.attributes.get("linux_audit.cwd.cwd"),
+//!     Some(&otel::AnyValue::String("/root".into()))
+//! );
+//! ```
+
+use indexmap::IndexMap;
+
+use crate::validate::is_enriched_name;
+use crate::{Body, Key, Number, RecordRef, Severity, SeverityMap, Value};
+
+/// Prefix of all attributes derived from audit records
+pub const NAMESPACE: &str = "linux_audit";
+
+/// Attribute for the event's sequence number
+pub const ATTR_SEQUENCE: &str = "linux_audit.sequence";
+
+/// Attribute for the list of message types in the event
+pub const ATTR_RECORD_TYPES: &str = "linux_audit.record_types";
+
+/// Attribute for the node name (OpenTelemetry semantic convention)
+pub const ATTR_HOST_NAME: &str = "host.name";
+
+/// Attribute value
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyValue {
+    String(String),
+    Bytes(Vec<u8>),
+    Int(i64),
+    Array(Vec<AnyValue>),
+    KvList(IndexMap<String, AnyValue>),
+}
+
+/// `SeverityNumber`, see [`severity_number`]
+pub type SeverityNumber = u8;
+
+/// Log record, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Time of the event, in nanoseconds since the epoch
+    pub time_unix_nano: u64,
+    pub severity_number: SeverityNumber,
+    /// [`Severity`] as text, e.g. `warning`
+    pub severity_text: String,
+    pub body: Option<AnyValue>,
+    pub attributes: IndexMap<String, AnyValue>,
+    /// Always `None`; audit records carry no trace context
+    pub trace_id: Option<[u8; 16]>,
+    /// Always `None`; audit records carry no trace context
+    pub span_id: Option<[u8; 8]>,
+    pub flags: u32,
+}
+
+/// Returns the OpenTelemetry `SeverityNumber` for `severity`:
+/// `INFO` (9), `WARN` (13), or `ERROR` (17) for
+/// [`Severity::Critical`].
+pub fn severity_number(severity: Severity) -> SeverityNumber {
+    match severity {
+        Severity::Info => 9,
+        Severity::Warning => 13,
+        Severity::Critical => 17,
+    }
+}
+
+/// Returns the attribute name for field `key` of record `rec`, see
+/// the [module documentation](self).
+pub fn attribute_name(rec: RecordRef, key: &Key) -> String {
+    let ty = rec.ty.to_string().to_ascii_lowercase();
+    let key = match key {
+        Key::Name(r) if is_enriched_name(r) => format!("{}_name", key.to_string().to_lowercase()),
+        Key::NameTranslated(_) => format!("{}_name", key.to_string().to_lowercase()),
+        _ => key.to_string(),
+    };
+    match rec.index {
+        0 => format!("{NAMESPACE}.{ty}.{key}"),
+        n => format!("{NAMESPACE}.{ty}.{n}.{key}"),
+    }
+}
+
+fn bytes_value(b: &[u8]) -> AnyValue {
+    match std::str::from_utf8(b) {
+        Ok(s) => AnyValue::String(s.into()),
+        Err(_) => AnyValue::Bytes(b.to_vec()),
+    }
+}
+
+/// Converts a value; `None` for empty and skipped values.
+pub fn any_value(v: &Value) -> Option<AnyValue> {
+    match v {
+        Value::Empty | Value::Skipped(_) => None,
+        Value::Number(Number::Dec(n)) => Some(AnyValue::Int(*n)),
+        Value::Number(n) => Some(AnyValue::String(n.to_string())),
+        Value::List(vs) | Value::StringifiedList(vs) => {
+            Some(AnyValue::Array(vs.iter().filter_map(any_value).collect()))
+        }
+        Value::Map(kv) => Some(AnyValue::KvList(
+            kv.iter()
+                .filter_map(|(k, v)| Some((k.to_string(), any_value(v)?)))
+                .collect(),
+        )),
+        Value::Str(s, _) => Some(bytes_value(s)),
+        Value::Owned(s) => Some(bytes_value(s)),
+        Value::Literal(s) => Some(AnyValue::String(s.to_string())),
+        Value::Segments(segs) => Some(bytes_value(&segs.concat())),
+    }
+}
+
+struct Builder {
+    attributes: IndexMap<String, AnyValue>,
+    types: Vec<AnyValue>,
+    severity: Severity,
+}
+
+impl Builder {
+    fn new(sequence: u32, node: &Option<Vec<u8>>) -> Self {
+        let mut attributes = IndexMap::new();
+        if let Some(node) = node {
+            attributes.insert(ATTR_HOST_NAME.into(), bytes_value(node));
+        }
+        attributes.insert(ATTR_SEQUENCE.into(), AnyValue::Int(sequence.into()));
+        Builder {
+            attributes,
+            types: vec![],
+            severity: Severity::Info,
+        }
+    }
+
+    fn add(&mut self, rec: RecordRef, body: &Body, map: &SeverityMap) {
+        self.severity = self.severity.max(map.get(rec.ty));
+        self.types.push(AnyValue::String(rec.ty.to_string()));
+        for (k, v) in body {
+            if let Some(v) = any_value(v) {
+                self.attributes.insert(attribute_name(rec, k), v);
+            }
+        }
+    }
+
+    fn build(mut self, timestamp: u64) -> LogRecord {
+        self.attributes
+            .insert(ATTR_RECORD_TYPES.into(), AnyValue::Array(self.types));
+        LogRecord {
+            time_unix_nano: timestamp * 1_000_000,
+            severity_number: severity_number(self.severity),
+            severity_text: self.severity.to_string(),
+            body: None,
+            attributes: self.attributes,
+            trace_id: None,
+            span_id: None,
+            flags: 0,
+        }
+    }
+}
+
+impl LogRecord {
+    /// Converts a message, with severity according to `map`.
+    pub fn from_message(msg: &crate::Message, map: &SeverityMap) -> Self {
+        let mut b = Builder::new(msg.id.sequence, &msg.node);
+        let rec = RecordRef {
+            ty: msg.ty,
+            index: 0,
+        };
+        b.add(rec, &msg.body, map);
+        b.build(msg.id.timestamp)
+    }
+
+    /// Converts an event. The severity is the highest severity of
+    /// its records according to `map`.
+    pub fn from_event(ev: &crate::Event, map: &SeverityMap) -> Self {
+        let mut b = Builder::new(ev.id.sequence, &ev.node);
+        for (n, (ty, body)) in ev.records.iter().enumerate() {
+            let index = ev.records[..n].iter().filter(|(t, _)| t == ty).count();
+            b.add(RecordRef { ty: *ty, index }, body, map);
+        }
+        b.build(ev.id.timestamp)
+    }
+}
+
+/// Uses the default [`SeverityMap`].
+impl From<&crate::Message<'_>> for LogRecord {
+    fn from(msg: &crate::Message) -> Self {
+        Self::from_message(msg, &SeverityMap::default())
+    }
+}
+
+/// Uses the default [`SeverityMap`].
+impl From<&crate::Event<'_>> for LogRecord {
+    fn from(ev: &crate::Event) -> Self {
+        Self::from_event(ev, &SeverityMap::default())
+    }
+}
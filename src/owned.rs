@@ -0,0 +1,197 @@
+use crate::*;
+
+/// A [`Value`] that does not borrow from a [`Body`], with the same
+/// shape as its serialized form, see [`Body::to_owned_pairs`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedValue {
+    /// Empty value, serialized as `None`
+    Empty,
+    /// A byte string. `{…}` values retain their braces,
+    /// non-contiguous strings and stringified lists are joined.
+    Bytes(Vec<u8>),
+    /// Parsed number
+    Number(Number),
+    List(Vec<OwnedValue>),
+    /// An internal key/value map, see [`Parser::split_msg`]
+    Map(Vec<(String, OwnedValue)>),
+    /// Elements removed from ARGV lists, see [`Value::Skipped`]
+    Skipped {
+        args: usize,
+        bytes: usize,
+    },
+}
+
+impl From<&Value<'_>> for OwnedValue {
+    fn from(value: &Value<'_>) -> Self {
+        match value {
+            Value::Empty => OwnedValue::Empty,
+            Value::Str(r, Quote::Braces) => OwnedValue::Bytes([b"{", *r, b"}"].concat()),
+            Value::Str(r, _) => OwnedValue::Bytes(r.to_vec()),
+            Value::Owned(v) => OwnedValue::Bytes(v.clone()),
+            Value::Segments(segs) => OwnedValue::Bytes(segs.concat()),
+            Value::Literal(s) => OwnedValue::Bytes(s.as_bytes().to_vec()),
+            Value::Number(n) => OwnedValue::Number(n.clone()),
+            Value::List(vs) => OwnedValue::List(vs.iter().map(OwnedValue::from).collect()),
+            Value::StringifiedList(vs) => match join_stringified(vs) {
+                Ok(buf) => OwnedValue::Bytes(buf),
+                Err(_) => OwnedValue::List(vs.iter().map(OwnedValue::from).collect()),
+            },
+            Value::Map(vs) => OwnedValue::Map(
+                vs.iter()
+                    .map(|(k, v)| (k.to_string(), OwnedValue::from(v)))
+                    .collect(),
+            ),
+            Value::Skipped((args, bytes)) => OwnedValue::Skipped {
+                args: *args,
+                bytes: *bytes,
+            },
+        }
+    }
+}
+
+impl OwnedValue {
+    /// Converts back into a [`Value`] of a record of type `ty`.
+    /// Strings become [`Value::Owned`], keys of maps are
+    /// reconstructed as the parser would have produced them.
+    pub fn into_value(self, ty: MessageType) -> Value<'static> {
+        match self {
+            OwnedValue::Empty => Value::Empty,
+            OwnedValue::Bytes(b) => Value::Owned(b),
+            OwnedValue::Number(n) => Value::Number(n),
+            OwnedValue::List(vs) => Value::List(vs.into_iter().map(|v| v.into_value(ty)).collect()),
+            OwnedValue::Map(kv) => Value::Map(
+                kv.into_iter()
+                    .map(|(k, v)| (decode_key(ty, &k), v.into_value(ty)))
+                    .collect(),
+            ),
+            OwnedValue::Skipped { args, bytes } => Value::Skipped((args, bytes)),
+        }
+    }
+}
+
+impl Body<'_> {
+    /// Returns copies of all key/value pairs, in order.
+    ///
+    /// Keys are formatted exactly as in serialized output, e.g.
+    /// `a2[3]`, `a0_len`, or `UID` for enriched values.
+    pub fn to_owned_pairs(&self) -> Vec<(String, OwnedValue)> {
+        self.into_iter()
+            .map(|(k, v)| (k.to_string(), OwnedValue::from(v)))
+            .collect()
+    }
+
+    /// Reconstructs the body of a record of type `ty` from pairs as
+    /// returned by [`to_owned_pairs`](Self::to_owned_pairs), see
+    /// [`OwnedValue::into_value`].
+    pub fn from_owned_pairs<I>(ty: MessageType, pairs: I) -> Body<'static>
+    where
+        I: IntoIterator<Item = (String, OwnedValue)>,
+    {
+        let pairs = pairs.into_iter();
+        let mut body = Body::with_capacity(pairs.size_hint().0);
+        for (k, v) in pairs {
+            body.push((decode_key(ty, &k), v.into_value(ty)));
+        }
+        body
+    }
+}
+
+/// A [`Message`] in the shape of its serialized form, shared by the
+/// conversions of the `proto` and `archive` modules
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+pub(crate) struct MessageParts {
+    pub id: EventID,
+    pub node: Option<Vec<u8>>,
+    pub ty: MessageType,
+    pub body: Vec<(String, OwnedValue)>,
+    pub truncated: bool,
+    pub hex_decoded: bool,
+    pub type_name: Option<Vec<u8>>,
+    pub enriched: bool,
+}
+
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+impl From<&Message<'_>> for MessageParts {
+    fn from(msg: &Message) -> Self {
+        MessageParts {
+            id: msg.id,
+            node: msg.node.clone(),
+            ty: msg.ty,
+            body: msg.body.to_owned_pairs(),
+            truncated: msg.truncated,
+            hex_decoded: msg.hex_decoded,
+            type_name: msg.type_name.clone(),
+            enriched: msg.enriched,
+        }
+    }
+}
+
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+impl From<MessageParts> for OwnedMessage {
+    fn from(p: MessageParts) -> Self {
+        Message {
+            id: p.id,
+            node: p.node,
+            ty: p.ty,
+            body: Body::from_owned_pairs(p.ty, p.body),
+            truncated: p.truncated,
+            hex_decoded: p.hex_decoded,
+            type_name: p.type_name,
+            enriched: p.enriched,
+        }
+    }
+}
+
+/// An [`Event`] in the shape of its serialized form, see
+/// [`MessageParts`]
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+pub(crate) struct EventParts {
+    pub id: EventID,
+    pub node: Option<Vec<u8>>,
+    pub records: Vec<(MessageType, Vec<(String, OwnedValue)>)>,
+}
+
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+impl From<&Event<'_>> for EventParts {
+    fn from(ev: &Event) -> Self {
+        EventParts {
+            id: ev.id,
+            node: ev.node.clone(),
+            records: ev
+                .records
+                .iter()
+                .map(|(ty, body)| (*ty, body.to_owned_pairs()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(any(feature = "proto", feature = "rkyv"))]
+impl From<EventParts> for Event<'static> {
+    fn from(p: EventParts) -> Self {
+        let mut ev = Event::new(p.id, p.node);
+        for (ty, body) in p.records {
+            ev.records.push((ty, Body::from_owned_pairs(ty, body)));
+        }
+        ev
+    }
+}
+
+/// Reconstructs a [`Key`] from its string representation, the way
+/// the parser would have produced it for a message of type `ty`
+pub(crate) fn decode_key(ty: MessageType, s: &str) -> Key {
+    use crate::parser::{parse_key, parse_key_a_x, parse_key_a_x_len, parse_key_a_xy};
+    use nom::{combinator::all_consuming, IResult};
+
+    let s = s.as_bytes();
+    let complete = |f: fn(&[u8]) -> IResult<&[u8], Key>| all_consuming(f)(s).ok().map(|(_, k)| k);
+    if matches!(ty, MessageType::SYSCALL | MessageType::EXECVE) {
+        if let Some(k) = complete(parse_key_a_x_len)
+            .or_else(|| complete(parse_key_a_xy))
+            .or_else(|| complete(parse_key_a_x))
+        {
+            return k;
+        }
+    }
+    complete(parse_key).unwrap_or_else(|| Key::Name(NVec::from(s)))
+}
@@ -0,0 +1,108 @@
+use std::fmt::{self, Debug};
+
+use crate::*;
+
+/// The subset of [`Value`] that the parser produces
+///
+/// [`Value`] also has variants that are only used by programs that
+/// modify or construct bodies, such as [`Value::Segments`] or
+/// [`Value::StringifiedList`]. Matching on `ParsedValue` instead
+/// does not need wildcard branches for those.
+///
+/// Values produced with the default [`Parser`] configuration can
+/// always be converted. [`Value::Skipped`], produced with
+/// [`LimitAction::Skip`], cannot be represented.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let msg = parse(&b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n"[..], false).unwrap();
+/// for (k, v) in msg.body.to_parsed().unwrap() {
+///     match v {
+///         ParsedValue::Str(s, _) => println!("{k}: {}", String::from_utf8_lossy(s)),
+///         ParsedValue::Owned(s) => println!("{k}: {}", String::from_utf8_lossy(&s)),
+///         ParsedValue::Number(n) => println!("{k}: {n}"),
+///         ParsedValue::Empty => println!("{k}: (empty)"),
+///         ParsedValue::List(_) | ParsedValue::Map(_) => println!("{k}: …"),
+///     }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Default)]
+pub enum ParsedValue<'a> {
+    /// Empty value.
+    #[default]
+    Empty,
+    /// A byte string.
+    Str(&'a [u8], Quote),
+    /// Parsed number.
+    Number(Number),
+    /// A list of values.
+    List(Vec<ParsedValue<'a>>),
+    /// A byte string that is not stored within the [`Body`]. Used for
+    /// decoded hex-strings.
+    Owned(Vec<u8>),
+    /// An internal key/value map. Used when [`Parser::split_msg`] is set.
+    Map(Vec<(Key, ParsedValue<'a>)>),
+}
+
+impl<'a> ParsedValue<'a> {
+    /// Converts a [`Value`]. Non-contiguous strings become
+    /// [`ParsedValue::Owned`], literals become [`ParsedValue::Str`],
+    /// and stringified lists become [`ParsedValue::List`]. Returns
+    /// `None` if `v` is or contains [`Value::Skipped`].
+    pub fn from_value(v: &Value<'a>) -> Option<Self> {
+        Some(match v {
+            Value::Empty => ParsedValue::Empty,
+            Value::Str(s, q) => ParsedValue::Str(s, *q),
+            Value::Number(n) => ParsedValue::Number(n.clone()),
+            Value::List(vs) | Value::StringifiedList(vs) => {
+                ParsedValue::List(vs.iter().map(Self::from_value).collect::<Option<_>>()?)
+            }
+            Value::Owned(s) => ParsedValue::Owned(s.clone()),
+            Value::Map(kv) => ParsedValue::Map(
+                kv.iter()
+                    .map(|(k, v)| Some((k.clone(), Self::from_value(v)?)))
+                    .collect::<Option<_>>()?,
+            ),
+            Value::Segments(segs) => ParsedValue::Owned(segs.concat()),
+            Value::Literal(s) => ParsedValue::Str(s.as_bytes(), Quote::None),
+            Value::Skipped(_) => return None,
+        })
+    }
+}
+
+impl<'a> From<ParsedValue<'a>> for Value<'a> {
+    fn from(v: ParsedValue<'a>) -> Self {
+        match v {
+            ParsedValue::Empty => Value::Empty,
+            ParsedValue::Str(s, q) => Value::Str(s, q),
+            ParsedValue::Number(n) => Value::Number(n),
+            ParsedValue::List(vs) => Value::List(vs.into_iter().map(Value::from).collect()),
+            ParsedValue::Owned(s) => Value::Owned(s),
+            ParsedValue::Map(kv) => {
+                Value::Map(kv.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl Debug for ParsedValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&Value::from(self.clone()), f)
+    }
+}
+
+impl Body<'_> {
+    /// Retrieves the first value found for a given `key` as
+    /// [`ParsedValue`], see [`ParsedValue::from_value`].
+    pub fn get_parsed<K: AsRef<[u8]>>(&self, key: K) -> Option<ParsedValue<'_>> {
+        self.get(key).and_then(ParsedValue::from_value)
+    }
+
+    /// Returns all key/value pairs as [`ParsedValue`]s, or `None` if
+    /// a value cannot be converted, see [`ParsedValue::from_value`].
+    pub fn to_parsed(&self) -> Option<Vec<(Key, ParsedValue<'_>)>> {
+        self.into_iter()
+            .map(|(k, v)| Some((k.clone(), ParsedValue::from_value(v)?)))
+            .collect()
+    }
+}
@@ -1,5 +1,7 @@
+use std::cell::Cell;
 use std::convert::{From, TryFrom};
 use std::str;
+use std::sync::Arc;
 
 use nom::{
     branch::*, bytes::complete::*, character::complete::*, character::*, combinator::*, multi::*,
@@ -10,16 +12,177 @@ use nom::character::complete::{i64 as dec_i64, u16 as dec_u16, u32 as dec_u32, u
 
 use thiserror::Error;
 
+use crate::compat::rewrite_legacy;
 use crate::constants::*;
 use crate::*;
 
 /// Parser for Linux Audit messages, with a few configurable options
-#[derive(Debug)]
+///
+/// A `Parser` is a plain configuration struct that can be cloned
+/// cheaply. It can be constructed using [`Parser::builder`]:
+///
+/// ```
+/// # use linux_audit_parser::Parser;
+/// let parser = Parser::builder().enriched(false).split_msg(true).build();
+/// ```
+#[derive(Debug, Clone)]
 pub struct Parser {
     /// Process enriched (i.e. ALL-CAPS keys). Default: true
     pub enriched: bool,
     /// Try to process common msg='…' strings into key/value maps. Default: true
     pub split_msg: bool,
+    /// Return the "unset" value (`4294967295` or `-1`) in ID fields
+    /// (`auid`, `uid`, …, `ses`) as [`Value::Empty`]. Default: false
+    pub unset_ids: bool,
+    /// Maximum number of bytes in string values of a message.
+    /// Default: None (unlimited)
+    pub max_bytes: Option<usize>,
+    /// Action taken if a message exceeds `max_bytes`. Default:
+    /// [`LimitAction::Error`]
+    pub on_limit: LimitAction,
+    /// Recover from lines that lack the trailing newline or have
+    /// been cut off in the middle of a value by dropping the
+    /// incomplete part. Such messages are marked as
+    /// [`Message::truncated`]. Default: false
+    pub allow_truncated: bool,
+    /// Instead of failing with [`ParseError::MalformedBody`] or
+    /// [`ParseError::TrailingGarbage`], keep the key/value pairs that
+    /// can be parsed and attach the rest of the line as
+    /// `_trailing` ([`Key::Literal`]). Default: false
+    pub keep_trailing: bool,
+    /// Collect keys and message types that are not part of the
+    /// dictionaries, to help keep them current. Clones of the parser
+    /// share the collector. Default: None
+    pub unknown: Option<Arc<UnknownCollector>>,
+    /// Only parse the body of messages of these types; other messages
+    /// are returned with an empty body. Default: None (all types)
+    pub only_types: Option<MessageTypeSet>,
+    /// Accept symbolic message types that are not part of the
+    /// dictionary, e.g. from newer kernels, instead of failing with
+    /// [`ParseError::MalformedHeader`]. Such messages have type
+    /// `MessageType(0)`, the name is kept in
+    /// [`Message::type_name`]. Default: false
+    pub keep_unknown_types: bool,
+    /// Prefixes of custom fields, e.g. `acme` for `acme_foo=…`, and
+    /// the types of their values. Such fields are returned as
+    /// [`Key::Vendor`]. Default: empty
+    pub vendor_prefixes: Vec<(String, FieldType)>,
+    /// Normalization applied to enriched values (`UID`, `AUID`, …).
+    /// Default: [`EnrichedNormalization::None`]
+    pub normalize_enriched: EnrichedNormalization,
+    /// Return the `?` placeholder for unknown values, e.g. in
+    /// `hostname=? addr=? terminal=?`, as [`Value::Empty`], including
+    /// values in `msg='…'` maps. Fields of the [`FieldType::Encoded`]
+    /// type are always handled this way. Default: false
+    pub placeholders_as_empty: bool,
+    /// String values that are returned as [`Value::Literal`] instead
+    /// of being copied into the [`Body`], including values in
+    /// `msg='…'` maps. This saves memory for frequent values such as
+    /// `success=yes`, see [`COMMON_VALUES`]. Note that literals are
+    /// serialized as strings, not as byte strings. Default: empty
+    pub intern: &'static [&'static str],
+    /// Accept the formats written by audit 1.x userspace and RHEL
+    /// 5/6-era kernels by rewriting them into the current format:
+    /// free text before the fields (`user pid=…`, `… by auid=…`) is
+    /// dropped, `old auid=… new auid=…` becomes `old-auid=… auid=…`,
+    /// and in `msg='…'` strings, leading text such as `PAM:
+    /// authentication` becomes `op=PAM:authentication` while ` : `
+    /// separators, parentheses, commas, and trailing colons are
+    /// dropped. Fields that old versions did not write, such as
+    /// `ses`, remain missing. Default: false
+    pub compat: bool,
+    /// Recognize numeric fields inside `msg='…'` maps according to
+    /// the field dictionary, so that e.g. `new_gid=4550` in a
+    /// `CHGRP_ID` record is returned as a number instead of being
+    /// taken for a hex-encoded string. Default: false
+    pub msg_numbers: bool,
+}
+
+/// Frequent values, for use with [`Parser::intern`]
+pub const COMMON_VALUES: &[&str] = &[
+    "yes", "no", "success", "failed", "root", "(none)", "(null)", "?", "unset",
+];
+
+/// What the parser does when a message exceeds
+/// [`Parser::max_bytes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitAction {
+    /// Fail with [`ParseError::LimitExceeded`]
+    #[default]
+    Error,
+    /// Replace values that would exceed the limit with
+    /// [`Value::Skipped`]`((n, len))`, where `n` is the number of
+    /// list elements, or 1 for other values.
+    Skip,
+}
+
+/// Normalization of enriched values, see
+/// [`Parser::normalize_enriched`]
+///
+/// Depending on the _auditd(8)_ version and name service, resolved
+/// names can carry extra quotes or whitespace, which gets in the way
+/// of joining them with other data. Only values of enriched (ALL-CAPS)
+/// keys are affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnrichedNormalization {
+    /// Keep values as they are
+    #[default]
+    None,
+    /// Remove leading and trailing whitespace and quotes (`"`, `'`)
+    Trim,
+    /// Like [`Trim`](Self::Trim), and convert ASCII letters to lower
+    /// case
+    TrimLowercase,
+}
+
+impl EnrichedNormalization {
+    fn apply<'a>(&self, v: Value<'a>) -> Value<'a> {
+        let trim = |s: &'a [u8]| {
+            let is_junk = |c: &u8| c.is_ascii_whitespace() || *c == b'"' || *c == b'\'';
+            let start = s.iter().position(|c| !is_junk(c)).unwrap_or(s.len());
+            let end = s.iter().rposition(|c| !is_junk(c)).map_or(start, |e| e + 1);
+            &s[start..end]
+        };
+        match (self, v) {
+            (EnrichedNormalization::None, v) => v,
+            (EnrichedNormalization::Trim, Value::Str(s, q)) => Value::Str(trim(s), q),
+            (EnrichedNormalization::TrimLowercase, Value::Str(s, q)) => {
+                let s = trim(s);
+                if s.iter().any(u8::is_ascii_uppercase) {
+                    Value::Owned(s.to_ascii_lowercase())
+                } else {
+                    Value::Str(s, q)
+                }
+            }
+            (_, v) => v,
+        }
+    }
+}
+
+/// Replaces strings found in `table` by [`Value::Literal`], see
+/// [`Parser::intern`]
+fn intern<'a>(table: &'static [&'static str], v: Value<'a>) -> Value<'a> {
+    match v {
+        Value::Str(s, _) => match table.iter().find(|t| t.as_bytes() == s) {
+            Some(t) => Value::Literal(t),
+            None => v,
+        },
+        Value::Map(kv) => Value::Map(kv.into_iter().map(|(k, v)| (k, intern(table, v))).collect()),
+        v => v,
+    }
+}
+
+/// Turns `?` into [`Value::Empty`], see [`Parser::placeholders_as_empty`]
+fn empty_placeholder(v: Value) -> Value {
+    match v {
+        Value::Str(b"?", _) => Value::Empty,
+        Value::Map(kv) => Value::Map(
+            kv.into_iter()
+                .map(|(k, v)| (k, empty_placeholder(v)))
+                .collect(),
+        ),
+        v => v,
+    }
 }
 
 impl Default for Parser {
@@ -27,12 +190,151 @@ impl Default for Parser {
         Self {
             enriched: true,
             split_msg: true,
+            unset_ids: false,
+            max_bytes: None,
+            on_limit: LimitAction::Error,
+            allow_truncated: false,
+            keep_trailing: false,
+            unknown: None,
+            only_types: None,
+            keep_unknown_types: false,
+            vendor_prefixes: vec![],
+            normalize_enriched: EnrichedNormalization::None,
+            placeholders_as_empty: false,
+            intern: &[],
+            compat: false,
+            msg_numbers: false,
         }
     }
 }
 
+impl Parser {
+    /// Returns the keys and message types not found in the
+    /// dictionaries that have been collected so far, if enabled.
+    pub fn unknown_stats(&self) -> Option<UnknownStats> {
+        self.unknown.as_ref().map(|c| c.snapshot())
+    }
+
+    /// Returns a [`ParserBuilder`] that starts out with the default
+    /// configuration.
+    pub fn builder() -> ParserBuilder {
+        ParserBuilder::default()
+    }
+}
+
+/// Builder for [`Parser`], see [`Parser::builder`]
+#[derive(Debug, Clone, Default)]
+pub struct ParserBuilder {
+    parser: Parser,
+}
+
+impl ParserBuilder {
+    /// Sets [`Parser::enriched`].
+    pub fn enriched(mut self, enriched: bool) -> Self {
+        self.parser.enriched = enriched;
+        self
+    }
+
+    /// Sets [`Parser::split_msg`].
+    pub fn split_msg(mut self, split_msg: bool) -> Self {
+        self.parser.split_msg = split_msg;
+        self
+    }
+
+    /// Sets [`Parser::unset_ids`].
+    pub fn unset_ids(mut self, unset_ids: bool) -> Self {
+        self.parser.unset_ids = unset_ids;
+        self
+    }
+
+    /// Sets [`Parser::max_bytes`].
+    pub fn max_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.parser.max_bytes = max_bytes;
+        self
+    }
+
+    /// Sets [`Parser::on_limit`].
+    pub fn on_limit(mut self, on_limit: LimitAction) -> Self {
+        self.parser.on_limit = on_limit;
+        self
+    }
+
+    /// Sets [`Parser::allow_truncated`].
+    pub fn allow_truncated(mut self, allow_truncated: bool) -> Self {
+        self.parser.allow_truncated = allow_truncated;
+        self
+    }
+
+    /// Sets [`Parser::keep_trailing`].
+    pub fn keep_trailing(mut self, keep_trailing: bool) -> Self {
+        self.parser.keep_trailing = keep_trailing;
+        self
+    }
+
+    /// Enables collection of unknown keys and message types, see
+    /// [`Parser::unknown`].
+    pub fn collect_unknown(mut self, collect: bool) -> Self {
+        self.parser.unknown = collect.then(Default::default);
+        self
+    }
+
+    /// Sets [`Parser::only_types`].
+    pub fn only_types(mut self, types: &[MessageType]) -> Self {
+        self.parser.only_types = Some(types.into());
+        self
+    }
+
+    /// Sets [`Parser::keep_unknown_types`].
+    pub fn keep_unknown_types(mut self, keep_unknown_types: bool) -> Self {
+        self.parser.keep_unknown_types = keep_unknown_types;
+        self
+    }
+
+    /// Adds an entry to [`Parser::vendor_prefixes`].
+    pub fn vendor_prefix(mut self, prefix: &str, ty: FieldType) -> Self {
+        self.parser.vendor_prefixes.push((prefix.into(), ty));
+        self
+    }
+
+    /// Sets [`Parser::normalize_enriched`].
+    pub fn normalize_enriched(mut self, normalize_enriched: EnrichedNormalization) -> Self {
+        self.parser.normalize_enriched = normalize_enriched;
+        self
+    }
+
+    /// Sets [`Parser::placeholders_as_empty`].
+    pub fn placeholders_as_empty(mut self, placeholders_as_empty: bool) -> Self {
+        self.parser.placeholders_as_empty = placeholders_as_empty;
+        self
+    }
+
+    /// Sets [`Parser::intern`].
+    pub fn intern(mut self, intern: &'static [&'static str]) -> Self {
+        self.parser.intern = intern;
+        self
+    }
+
+    /// Sets [`Parser::compat`].
+    pub fn compat(mut self, compat: bool) -> Self {
+        self.parser.compat = compat;
+        self
+    }
+
+    /// Sets [`Parser::msg_numbers`].
+    pub fn msg_numbers(mut self, msg_numbers: bool) -> Self {
+        self.parser.msg_numbers = msg_numbers;
+        self
+    }
+
+    /// Returns the configured [`Parser`].
+    pub fn build(self) -> Parser {
+        self.parser
+    }
+}
+
 /// Audit parser error type
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ParseError {
     /// The header (`type= … msg=audit(…):`) could not be parsed.
     #[error("cannot parse header: {}", String::from_utf8_lossy(.0))]
@@ -50,8 +352,25 @@ pub enum ParseError {
         id: EventID,
         hex_str: Vec<u8>,
     },
+    /// The message exceeded the configured size limit.
+    #[error("{id} ({ty}) exceeds limit of {limit} bytes")]
+    LimitExceeded {
+        ty: MessageType,
+        id: EventID,
+        limit: usize,
+    },
+    /// Input could not be read, see [`MessageReader`].
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    /// A line exceeded the maximum length, see
+    /// [`MessageReader::max_line_length`].
+    #[error("line {line} exceeds limit of {limit} bytes")]
+    LineTooLong { line: usize, limit: usize },
 }
 
+/// UTF-8 byte order mark
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
 /// Parse a single log line as produced by _auditd(8)_
 ///
 /// If `skip_enriched` is set and _auditd_ has been configured to
@@ -69,11 +388,180 @@ pub fn parse<'a>(raw: &[u8], skip_enriched: bool) -> Result<Message<'a>, ParseEr
     .parse(raw)
 }
 
+/// Parse only the header of a log line as produced by _auditd(8)_:
+/// node name, message type, event identifier, and the offset at which
+/// the body starts.
+///
+/// This is considerably cheaper than a full [`parse`] and can be used
+/// to decide whether a line is worth parsing.
+#[allow(clippy::type_complexity)]
+pub fn parse_header(
+    raw: &[u8],
+) -> Result<(Option<&[u8]>, MessageType, EventID, usize), ParseError> {
+    let (rest, (node, ty, id)) =
+        recognize_header(raw).map_err(|_| ParseError::MalformedHeader(raw.to_vec()))?;
+    Ok((node, ty, id, raw.len() - rest.len()))
+}
+
 impl Parser {
     /// Parse a single log line as produced by _auditd(8)_
+    ///
+    /// A UTF-8 byte order mark at the start of the line and a CR LF
+    /// line ending, as found in files exported by Windows-based
+    /// collectors, are ignored.
     pub fn parse<'a, 'b>(&'a self, raw: &'a [u8]) -> Result<Message<'b>, ParseError> {
-        let (rest, (node, ty, id)) =
-            parse_header(raw).map_err(|_| ParseError::MalformedHeader(raw.to_vec()))?;
+        let raw = raw.strip_prefix(BOM).unwrap_or(raw);
+        let buf;
+        let raw = match raw.strip_suffix(b"\r\n") {
+            Some(line) => {
+                buf = [line, b"\n"].concat();
+                &buf[..]
+            }
+            None => raw,
+        };
+        let legacy;
+        let raw = match self.compat.then(|| rewrite_legacy(raw)).flatten() {
+            Some(line) => {
+                legacy = line;
+                &legacy[..]
+            }
+            None => raw,
+        };
+        match self.parse_line(raw) {
+            Err(e) if self.keep_trailing => match self.parse_trailing(raw, &e) {
+                Some(msg) => Ok(msg),
+                None if self.allow_truncated => self.parse_truncated(raw).ok_or(e),
+                None => Err(e),
+            },
+            Err(e) if self.allow_truncated => self.parse_truncated(raw).ok_or(e),
+            result => result,
+        }
+    }
+
+    /// Parse the first line of `raw`, which may contain further
+    /// lines, and return the message along with the number of bytes
+    /// consumed, including the newline. This allows iterating over
+    /// large buffers, e.g. memory-mapped log files, without splitting
+    /// them into lines first. If `raw` contains no newline, all of it
+    /// is parsed as a single line.
+    ///
+    /// Empty lines are not skipped. If an error is returned, the line
+    /// extends up to and including the next newline.
+    ///
+    /// ```
+    /// # use linux_audit_parser::*;
+    /// let buf = &b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\ntype=EOE msg=audit(1.000:1): \n"[..];
+    /// let (msg, len) = Parser::default().parse_prefix(buf).unwrap();
+    /// assert_eq!(msg.ty, MessageType::CWD);
+    /// let (msg, _) = Parser::default().parse_prefix(&buf[len..]).unwrap();
+    /// assert_eq!(msg.ty, MessageType::EOE);
+    /// ```
+    pub fn parse_prefix<'a, 'b>(
+        &'a self,
+        raw: &'a [u8],
+    ) -> Result<(Message<'b>, usize), ParseError> {
+        let len = match raw.iter().position(|c| *c == b'\n') {
+            Some(n) => n + 1,
+            None => raw.len(),
+        };
+        self.parse(&raw[..len]).map(|msg| (msg, len))
+    }
+
+    /// Parse the line up to the first key/value pair that cannot be
+    /// parsed and attach the rest as `_trailing`.
+    fn parse_trailing<'b>(&self, raw: &[u8], err: &ParseError) -> Option<Message<'b>> {
+        let attach = |mut msg: Message<'b>, rest: &[u8]| {
+            let rest = rest.strip_suffix(b"\n").unwrap_or(rest);
+            msg.body
+                .push((Key::Literal("_trailing"), Value::Owned(rest.to_vec())));
+            msg
+        };
+        if let ParseError::TrailingGarbage(rest) = err {
+            let line = &raw[..raw.len() - rest.len()];
+            return self.parse_line(line).ok().map(|msg| attach(msg, rest));
+        }
+        if !matches!(err, ParseError::MalformedBody(_)) {
+            return None;
+        }
+        let line = raw.strip_suffix(b"\n").unwrap_or(raw);
+        let end = self.failing_field(raw)?;
+        if end >= line.len() {
+            return None;
+        }
+        let mut buf = line[..end].to_vec();
+        buf.push(b'\n');
+        let msg = self.parse_line(&buf).ok()?;
+        Some(attach(msg, &line[end + 1..]))
+    }
+
+    /// Best-effort parsing for incomplete lines: Remove the key/value
+    /// pair that cannot be parsed and everything after it.
+    fn parse_truncated<'b>(&self, raw: &[u8]) -> Option<Message<'b>> {
+        let mut buf = raw.strip_suffix(b"\n").unwrap_or(raw).to_vec();
+        buf.push(b'\n');
+        let end = self.failing_field(&buf)?;
+        buf.truncate(end);
+        buf.push(b'\n');
+        let mut msg = self.parse_line(&buf).ok()?;
+        msg.truncated = true;
+        Some(msg)
+    }
+
+    /// Returns the position of the separator in front of the first
+    /// key/value pair of `raw` that cannot be parsed, or the end of
+    /// the line if the body can be parsed. This is derived from where
+    /// the body parser gives up, so that the line has to be cut only
+    /// once.
+    fn failing_field(&self, raw: &[u8]) -> Option<usize> {
+        let (rest, ty) = match recognize_header(raw) {
+            Ok((rest, (_, ty, _))) => (rest, ty),
+            Err(_) if self.keep_unknown_types => {
+                (recognize_header_named(raw).ok()?.0, MessageType(0))
+            }
+            Err(_) => return None,
+        };
+        let pos = match self.parse_body(rest, ty) {
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => raw.len() - e.input.len(),
+            _ => return Some(raw.strip_suffix(b"\n").unwrap_or(raw).len()),
+        };
+        let line = raw.strip_suffix(b"\n").unwrap_or(raw);
+        let is_sep = |c: &u8| *c == b' ' || *c == b'\x1d';
+        match line.get(pos) {
+            None => Some(line.len()),
+            Some(c) if is_sep(c) => Some(pos),
+            Some(_) => line[..pos].iter().rposition(is_sep),
+        }
+    }
+
+    fn parse_line<'a, 'b>(&'a self, raw: &'a [u8]) -> Result<Message<'b>, ParseError> {
+        let (rest, (node, ty, id), type_name) = match recognize_header(raw) {
+            Ok((rest, header)) => (rest, header, None),
+            Err(_) if self.keep_unknown_types => {
+                let (rest, (node, name, id)) = recognize_header_named(raw)
+                    .map_err(|_| ParseError::MalformedHeader(raw.to_vec()))?;
+                (rest, (node, MessageType(0), id), Some(name.to_vec()))
+            }
+            Err(_) => return Err(ParseError::MalformedHeader(raw.to_vec())),
+        };
+
+        // The separator does not occur in values, those are
+        // hex-encoded if they contain control characters.
+        let enriched = rest.contains(&0x1d);
+
+        if let Some(types) = &self.only_types {
+            if !types.contains(ty) {
+                return Ok(Message {
+                    id,
+                    node: node.map(|s| s.to_vec()),
+                    ty,
+                    body: Body::new(),
+                    truncated: false,
+                    hex_decoded: false,
+                    type_name,
+                    enriched,
+                });
+            }
+        }
 
         let (rest, kv) = self
             .parse_body(rest, ty)
@@ -83,14 +571,49 @@ impl Parser {
             return Err(ParseError::TrailingGarbage(rest.to_vec()));
         }
 
+        if let (Some(limit), LimitAction::Error) = (self.max_bytes, self.on_limit) {
+            if kv.iter().any(|(_, v)| matches!(v, Value::Skipped(_))) {
+                return Err(ParseError::LimitExceeded { ty, id, limit });
+            }
+        }
+
         let node = node.map(|s| s.to_vec());
 
-        let mut body = Body::new();
+        let hex_decoded = kv.iter().any(|(_, v)| is_owned(v));
+
+        let bytes = kv.iter().map(|(_, v)| v.byte_len()).sum();
+        let mut body = Body::with_capacity_bytes(kv.len(), bytes);
         for (k, v) in kv {
+            let v = match &k {
+                Key::Name(r) if is_enriched_name(r) => self.normalize_enriched.apply(v),
+                _ => v,
+            };
+            let v = if self.placeholders_as_empty {
+                empty_placeholder(v)
+            } else {
+                v
+            };
+            let v = match self.intern {
+                [] => v,
+                table => intern(table, v),
+            };
             body.push((k, v));
         }
 
-        Ok(Message { id, node, ty, body })
+        if let Some(c) = &self.unknown {
+            c.record(ty, &body);
+        }
+
+        Ok(Message {
+            id,
+            node,
+            ty,
+            body,
+            truncated: false,
+            hex_decoded,
+            type_name,
+            enriched,
+        })
     }
 
     /// Recognize the body: Multiple key/value pairs, with special cases
@@ -135,15 +658,26 @@ impl Parser {
                 let (input, _) = opt(tag("policy loaded "))(input)?;
                 (input, None)
             }
+            _ if ty.is_daemon() => opt(map(parse_free_text, |s| {
+                (
+                    Key::Name(NVec::from(&b"reason"[..])),
+                    Value::Str(s, Quote::None),
+                )
+            }))(input)?,
             _ => opt(map(
                 terminated(tag("netlabel"), pair(tag(":"), space0)),
                 |s| (Key::Name(NVec::from(s)), Value::Empty),
             ))(input)?,
         };
 
+        // Bytes that values may still use, see Parser::max_bytes
+        let budget = Cell::new(self.max_bytes.unwrap_or(usize::MAX));
+
         let (input, mut kv) = if !self.enriched {
             terminated(
-                separated_list0(take_while1(|c| c == b' '), |input| self.parse_kv(input, ty)),
+                separated_list0(take_while1(|c| c == b' '), |input| {
+                    self.parse_kv(input, ty, &budget)
+                }),
                 alt((
                     value((), tuple((tag("\x1d"), is_not("\n"), tag("\n")))),
                     value((), tag("\n")),
@@ -152,25 +686,27 @@ impl Parser {
         } else {
             terminated(
                 separated_list0(take_while1(|c| c == b' ' || c == b'\x1d'), |input| {
-                    self.parse_kv(input, ty)
+                    self.parse_kv(input, ty, &budget)
                 }),
                 newline,
             )(input)?
         };
 
-        if let Some(s) = special {
-            kv.push(s)
+        if let Some((k, v)) = special {
+            kv.push((k, charge(&budget, v)))
         }
 
         Ok((input, kv))
     }
 
-    /// Recognize one key/value pair
+    /// Recognize one key/value pair. Values that do not fit into
+    /// `budget` are replaced by [`Value::Skipped`].
     #[inline(always)]
     fn parse_kv<'a>(
         &'a self,
         input: &'a [u8],
         ty: MessageType,
+        budget: &Cell<usize>,
     ) -> IResult<&'a [u8], (Key, Value<'a>)> {
         let (input, key) = match ty {
             // Special case for execve arguments: aX, aX[Y], aX_len
@@ -187,7 +723,30 @@ impl Parser {
             _ => terminated(parse_key, tag("="))(input),
         }?;
 
+        let (key, vendor) = match &key {
+            Key::Name(r) | Key::NameUID(r) | Key::NameGID(r) => match self.vendor_key(r) {
+                Some((key, vendor)) => (key, Some(vendor)),
+                None => (key, None),
+            },
+            _ => (key, None),
+        };
+
+        // Long hex strings are decoded to at least half their length
+        // (or kept as strings). Skip them before anything is
+        // allocated if that does not fit.
+        if self.max_bytes.is_some() {
+            let len = input.iter().position(|c| is_sep(*c)).unwrap_or(input.len());
+            if len > 32 && len / 2 > budget.get() && input[..len].iter().all(u8::is_ascii_hexdigit)
+            {
+                return Ok((&input[len..], (key, Value::Skipped((1, len / 2)))));
+            }
+        }
+
         let (input, value) = match (ty, &key) {
+            (_, Key::Vendor(p, r)) => {
+                let name = [p.as_slice(), b"_", r.as_slice()].concat();
+                parse_field(input, ty, &name, vendor)?
+            }
             (MessageType::SYSCALL, Key::Arg(_, None)) => map(
                 recognize(terminated(
                     many1_count(take_while1(is_hex_digit)),
@@ -204,6 +763,31 @@ impl Parser {
             (MessageType::SYSCALL, Key::Common(c)) => self.parse_common(input, ty, *c)?,
             (MessageType::EXECVE, Key::Arg(_, _)) => parse_encoded(input)?,
             (MessageType::EXECVE, Key::ArgLen(_)) => parse_dec(input)?,
+            // Capability sets, as emitted by older kernels, and the
+            // root ID of namespaced file capabilities
+            (MessageType::BPRM_FCAPS, Key::Name(name))
+                if matches!(name.as_slice(), b"new_pp" | b"new_pi" | b"new_pe") =>
+            {
+                parse_field(input, ty, name, Some(FieldType::NumericHex))?
+            }
+            (MessageType::BPRM_FCAPS, Key::Name(name)) if name.as_slice() == b"frootid" => {
+                parse_field(input, ty, name, Some(FieldType::NumericDec))?
+            }
+            // time= added to DAEMON_* records by non-auditd producers
+            (_, Key::Name(name)) if ty.is_daemon() && name.as_slice() == b"time" => {
+                alt((parse_daemon_time, |input| parse_named(input, ty, name)))(input)?
+            }
+            // hexadecimal values with 0x prefix
+            (_, Key::Name(name))
+                if matches!(
+                    (ty, name.as_slice()),
+                    (MessageType::MMAP, b"flags") | (MessageType::SECCOMP, b"code" | b"ip")
+                ) =>
+            {
+                alt((preceded(tag("0x"), parse_hex), |input| {
+                    parse_unspec_value(input, ty, name)
+                }))(input)?
+            }
             (_, Key::Name(name)) => parse_named(input, ty, name)?,
             (_, Key::Common(c)) => self.parse_common(input, ty, *c)?,
             (_, Key::NameUID(name)) | (_, Key::NameGID(name)) => {
@@ -212,7 +796,31 @@ impl Parser {
             _ => parse_encoded(input)?,
         };
 
-        Ok((input, (key, value)))
+        let value = match (&key, value) {
+            (
+                Key::NameUID(_) | Key::NameGID(_) | Key::Common(Common::Ses),
+                Value::Number(Number::Dec(-1 | 4294967295)),
+            ) if self.unset_ids => Value::Empty,
+            (_, value) => value,
+        };
+
+        Ok((input, (key, charge(budget, value))))
+    }
+
+    /// Turns a name into [`Key::Vendor`] if it starts with one of
+    /// the configured prefixes
+    fn vendor_key(&self, name: &[u8]) -> Option<(Key, FieldType)> {
+        for (prefix, ty) in &self.vendor_prefixes {
+            if let Some(rest) = name
+                .strip_prefix(prefix.as_bytes())
+                .and_then(|r| r.strip_prefix(b"_"))
+                .filter(|r| !r.is_empty())
+            {
+                let key = Key::Vendor(NVec::from(prefix.as_bytes()), NVec::from(rest));
+                return Some((key, *ty));
+            }
+        }
+        None
     }
 
     #[inline(always)]
@@ -254,9 +862,10 @@ impl Parser {
             Common::Mode => alt((parse_oct, |input| parse_unspec_value(input, ty, name)))(input),
             Common::Msg => {
                 if self.split_msg {
-                    alt((parse_kv_sq_as_map, |input| {
-                        parse_unspec_value(input, ty, name)
-                    }))(input)
+                    alt((
+                        |input| parse_kv_sq_as_map(input, self.msg_numbers),
+                        |input| parse_unspec_value(input, ty, name),
+                    ))(input)
                 } else {
                     alt((parse_encoded, |input| parse_unspec_value(input, ty, name)))(input)
                 }
@@ -265,10 +874,20 @@ impl Parser {
     }
 }
 
+/// True if the value contains decoded strings
+fn is_owned(v: &Value) -> bool {
+    match v {
+        Value::Owned(_) => true,
+        Value::List(vs) | Value::StringifiedList(vs) => vs.iter().any(is_owned),
+        Value::Map(vs) => vs.iter().any(|(_, v)| is_owned(v)),
+        _ => false,
+    }
+}
+
 /// Recognize the header: node, type, event identifier
 #[inline(always)]
 #[allow(clippy::type_complexity)]
-fn parse_header(input: &[u8]) -> IResult<&[u8], (Option<&[u8]>, MessageType, EventID)> {
+fn recognize_header(input: &[u8]) -> IResult<&[u8], (Option<&[u8]>, MessageType, EventID)> {
     tuple((
         opt(terminated(parse_node, is_a(" "))),
         terminated(parse_type, is_a(" ")),
@@ -276,15 +895,32 @@ fn parse_header(input: &[u8]) -> IResult<&[u8], (Option<&[u8]>, MessageType, Eve
     ))(input)
 }
 
+/// Recognize the header like [`recognize_header`], but accept any
+/// symbolic message type and return its name
+#[allow(clippy::type_complexity)]
+fn recognize_header_named(input: &[u8]) -> IResult<&[u8], (Option<&[u8]>, &[u8], EventID)> {
+    tuple((
+        opt(terminated(parse_node, is_a(" "))),
+        terminated(
+            preceded(
+                tag("type="),
+                recognize(many1_count(alt((alphanumeric1, tag("_"))))),
+            ),
+            is_a(" "),
+        ),
+        parse_msgid,
+    ))(input)
+}
+
 /// Recognize the node name
 #[inline(always)]
-fn parse_node(input: &[u8]) -> IResult<&[u8], &[u8]> {
+pub(crate) fn parse_node(input: &[u8]) -> IResult<&[u8], &[u8]> {
     preceded(tag("node="), is_not(" \t\r\n"))(input)
 }
 
 /// Recognize event type
 #[inline(always)]
-fn parse_type(input: &[u8]) -> IResult<&[u8], MessageType> {
+pub(crate) fn parse_type(input: &[u8]) -> IResult<&[u8], MessageType> {
     preceded(
         tag("type="),
         alt((
@@ -320,17 +956,27 @@ fn parse_msgid(input: &[u8]) -> IResult<&[u8], EventID> {
 
 #[inline(always)]
 fn parse_named<'a>(input: &'a [u8], ty: MessageType, name: &[u8]) -> IResult<&'a [u8], Value<'a>> {
-    match FIELD_TYPES.get(name) {
-        Some(&FieldType::Encoded) => {
+    parse_field(input, ty, name, FIELD_TYPES.get(name).copied())
+}
+
+#[inline(always)]
+fn parse_field<'a>(
+    input: &'a [u8],
+    ty: MessageType,
+    name: &[u8],
+    field_type: Option<FieldType>,
+) -> IResult<&'a [u8], Value<'a>> {
+    match field_type {
+        Some(FieldType::Encoded) => {
             alt((parse_encoded, |input| parse_unspec_value(input, ty, name)))(input)
         }
-        Some(&FieldType::NumericHex) => {
+        Some(FieldType::NumericHex) => {
             alt((parse_hex, |input| parse_unspec_value(input, ty, name)))(input)
         }
-        Some(&FieldType::NumericDec) => {
+        Some(FieldType::NumericDec) => {
             alt((parse_dec, |input| parse_unspec_value(input, ty, name)))(input)
         }
-        Some(&FieldType::NumericOct) => {
+        Some(FieldType::NumericOct) => {
             alt((parse_oct, |input| parse_unspec_value(input, ty, name)))(input)
         }
         // FIXME: Some(&FieldType::Numeric)
@@ -342,7 +988,7 @@ fn parse_named<'a>(input: &'a [u8], ty: MessageType, name: &[u8]) -> IResult<&'a
 ///
 /// May be double-quoted string, hex-encoded blob, (null), ?.
 #[inline(always)]
-fn parse_encoded(input: &[u8]) -> IResult<&[u8], Value> {
+fn parse_encoded(input: &[u8]) -> IResult<&[u8], Value<'_>> {
     alt((
         map(parse_str_dq_safe, |s| Value::Str(s, Quote::Double)),
         terminated(
@@ -368,7 +1014,7 @@ fn parse_encoded(input: &[u8]) -> IResult<&[u8], Value> {
 
 /// Recognize hexadecimal value
 #[inline(always)]
-fn parse_hex(input: &[u8]) -> IResult<&[u8], Value> {
+fn parse_hex(input: &[u8]) -> IResult<&[u8], Value<'_>> {
     map_res(
         terminated(take_while1(is_hex_digit), peek(take_while1(is_sep))),
         |digits| -> Result<_, std::num::ParseIntError> {
@@ -380,15 +1026,95 @@ fn parse_hex(input: &[u8]) -> IResult<&[u8], Value> {
 
 /// Recognize decimal value
 #[inline(always)]
-fn parse_dec(input: &[u8]) -> IResult<&[u8], Value> {
+fn parse_dec(input: &[u8]) -> IResult<&[u8], Value<'_>> {
     map(terminated(dec_i64, peek(take_while1(is_sep))), |n| {
         Value::Number(Number::Dec(n))
     })(input)
 }
 
+/// Subtracts the size of `value` from `budget`, or replaces it with
+/// [`Value::Skipped`] if it does not fit.
+fn charge<'a>(budget: &Cell<usize>, value: Value<'a>) -> Value<'a> {
+    let size = value.byte_len();
+    if size <= budget.get() {
+        budget.set(budget.get() - size);
+        return value;
+    }
+    let elems = match &value {
+        Value::List(vs) | Value::StringifiedList(vs) => vs.len(),
+        _ => 1,
+    };
+    Value::Skipped((elems, size))
+}
+
+/// Recognize the `time=` field of `DAEMON_*` records (not written by
+/// _auditd(8)_ itself, see [`DaemonRecord::time`]), either as seconds
+/// since the epoch (`1724970459.096`) or as RFC 3339 date with UTC
+/// offset (`2024-08-29T22:27:39.096+02:00`), optionally quoted.
+/// Returns milliseconds since the epoch, UTC.
+fn parse_daemon_time(input: &[u8]) -> IResult<&[u8], Value<'_>> {
+    map(
+        terminated(
+            alt((
+                delimited(tag("\""), parse_rfc3339, tag("\"")),
+                parse_rfc3339,
+                map(pair(dec_i64, parse_millis), |(secs, ms)| secs * 1000 + ms),
+            )),
+            peek(take_while1(is_sep)),
+        ),
+        |ms| Value::Number(Number::Dec(ms)),
+    )(input)
+}
+
+/// Recognize an optional fraction of a second, return milliseconds
+fn parse_millis(input: &[u8]) -> IResult<&[u8], i64> {
+    map(opt(preceded(tag("."), digit1)), |f: Option<&[u8]>| {
+        let f = f.unwrap_or_default();
+        (0..3).fold(0, |ms, i| {
+            ms * 10 + f.get(i).map_or(0, |c| i64::from(c - b'0'))
+        })
+    })(input)
+}
+
+/// Recognize an RFC 3339 date, return milliseconds since the epoch
+fn parse_rfc3339(input: &[u8]) -> IResult<&[u8], i64> {
+    let offset = alt((
+        value(0, tag("Z")),
+        map(
+            tuple((
+                alt((value(1, tag("+")), value(-1, tag("-")))),
+                map_parser(take_while_m_n(2, 2, is_digit), dec_u32),
+                opt(tag(":")),
+                map_parser(take_while_m_n(2, 2, is_digit), dec_u32),
+            )),
+            |(sign, h, _, m)| sign * (i64::from(h) * 60 + i64::from(m)) * 60_000,
+        ),
+    ));
+    map_opt(
+        tuple((
+            terminated(dec_u32, tag("-")),
+            terminated(dec_u32, tag("-")),
+            terminated(dec_u32, one_of("Tt ")),
+            terminated(dec_u32, tag(":")),
+            terminated(dec_u32, tag(":")),
+            dec_u32,
+            parse_millis,
+            preceded(space0, offset),
+        )),
+        |(y, mo, d, h, mi, s, ms, offset)| {
+            if !(1..=12).contains(&mo) || !(1..=31).contains(&d) || h > 23 || mi > 59 || s > 60 {
+                return None;
+            }
+            let days = days_from_civil(y.into(), mo.into(), d.into());
+            let secs = ((days * 24 + i64::from(h)) * 60 + i64::from(mi)) * 60 + i64::from(s);
+            Some(secs * 1000 + ms - offset)
+        },
+    )(input)
+}
+
 /// Recognize octal value
 #[inline(always)]
-fn parse_oct(input: &[u8]) -> IResult<&[u8], Value> {
+fn parse_oct(input: &[u8]) -> IResult<&[u8], Value<'_>> {
     map_res(
         terminated(take_while1(is_oct_digit), peek(take_while1(is_sep))),
         |digits| -> Result<_, std::num::ParseIntError> {
@@ -508,33 +1234,72 @@ fn parse_kv_sq(input: &[u8]) -> IResult<&[u8], &[u8]> {
     )(input)
 }
 
-/// Recognize a map enclosed in single quotes
+/// Recognize a map enclosed in single quotes. If `numbers` is set,
+/// numeric fields are typed, see [`Parser::msg_numbers`].
 #[inline(always)]
-fn parse_kv_sq_as_map(input: &[u8]) -> IResult<&[u8], Value> {
+fn parse_kv_sq_as_map(input: &[u8], numbers: bool) -> IResult<&[u8], Value<'_>> {
     map(
         delimited(
             tag("'"),
-            separated_list0(
-                space1,
-                alt((separated_pair(
-                    parse_key,
-                    alt((
-                        tag("="),
-                        recognize(tuple((tag(":"), space0))), // for 'avc:  mumble mumble mumble …'
-                    )),
-                    alt((
-                        parse_encoded,
-                        map(parse_str_words_inside_sq, |v| Value::Str(v, Quote::None)),
-                        map(parse_str_unq_inside_sq, |v| Value::Str(v, Quote::None)),
-                    )),
-                ),)),
-            ),
+            separated_list0(space1, |input| {
+                let (input, key) = parse_key(input)?;
+                let (input, _) = alt((
+                    tag("="),
+                    recognize(tuple((tag(":"), space0))), // for 'avc:  mumble mumble mumble …'
+                ))(input)?;
+                let (input, value) = alt((
+                    |input| match numbers {
+                        true => parse_map_number(input, &key),
+                        false => fail(input),
+                    },
+                    parse_encoded,
+                    map(parse_str_words_inside_sq, |v| Value::Str(v, Quote::None)),
+                    map(parse_str_unq_inside_sq, |v| Value::Str(v, Quote::None)),
+                ))(input)?;
+                Ok((input, (key, value)))
+            }),
             tag("'"),
         ),
         Value::Map,
     )(input)
 }
 
+/// Recognize numeric values inside `msg='…'` according to the field
+/// dictionary, so that e.g. `id=1000` is not taken for a hex-encoded
+/// string
+fn parse_map_number<'a>(input: &'a [u8], key: &Key) -> IResult<&'a [u8], Value<'a>> {
+    let field_type = match key {
+        Key::NameUID(_) | Key::NameGID(_) => Some(FieldType::NumericDec),
+        Key::Name(r) => FIELD_TYPES.get(r.as_slice()).copied(),
+        Key::Common(c) => FIELD_TYPES.get(<&str>::from(*c).as_bytes()).copied(),
+        _ => None,
+    };
+    let end = |input| peek(alt((space1, tag("'"))))(input);
+    match field_type {
+        Some(FieldType::NumericDec | FieldType::Numeric) => {
+            map(terminated(dec_i64, end), |n| Value::Number(Number::Dec(n)))(input)
+        }
+        Some(FieldType::NumericHex) => map_res(
+            terminated(take_while1(is_hex_digit), end),
+            |digits| -> Result<_, std::num::ParseIntError> {
+                let digits = unsafe { str::from_utf8_unchecked(digits) };
+                Ok(Value::Number(Number::Hex(u64::from_str_radix(digits, 16)?)))
+            },
+        )(input),
+        Some(FieldType::NumericOct) => map_res(
+            terminated(take_while1(is_oct_digit), end),
+            |digits| -> Result<_, std::num::ParseIntError> {
+                let digits = unsafe { str::from_utf8_unchecked(digits) };
+                Ok(Value::Number(Number::Oct(u64::from_str_radix(digits, 8)?)))
+            },
+        )(input),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Digit,
+        ))),
+    }
+}
+
 /// More "correct" variant of parse_str_braced
 #[inline(always)]
 fn parse_kv_braced(input: &[u8]) -> IResult<&[u8], &[u8]> {
@@ -554,7 +1319,7 @@ fn parse_kv_braced(input: &[u8]) -> IResult<&[u8], &[u8]> {
 
 /// Recognize regular keys of key/value pairs
 #[inline(always)]
-fn parse_key(input: &[u8]) -> IResult<&[u8], Key> {
+pub(crate) fn parse_key(input: &[u8]) -> IResult<&[u8], Key> {
     map(
         recognize(pair(alpha1, many0_count(alt((alphanumeric1, is_a("-_")))))),
         |s: &[u8]| {
@@ -573,13 +1338,13 @@ fn parse_key(input: &[u8]) -> IResult<&[u8], Key> {
 
 /// Recognize length specifier for EXECVE split arguments, e.g. a1_len
 #[inline(always)]
-fn parse_key_a_x_len(input: &[u8]) -> IResult<&[u8], Key> {
+pub(crate) fn parse_key_a_x_len(input: &[u8]) -> IResult<&[u8], Key> {
     map(delimited(tag("a"), dec_u32, tag("_len")), Key::ArgLen)(input)
 }
 
 /// Recognize EXECVE split arguments, e.g. a1[3]
 #[inline(always)]
-fn parse_key_a_xy(input: &[u8]) -> IResult<&[u8], Key> {
+pub(crate) fn parse_key_a_xy(input: &[u8]) -> IResult<&[u8], Key> {
     map(
         pair(
             preceded(tag("a"), dec_u32),
@@ -591,10 +1356,36 @@ fn parse_key_a_xy(input: &[u8]) -> IResult<&[u8], Key> {
 
 /// Recognize SYSCALL, EXECVE regular argument keys, e.g. a1, a2, a3…
 #[inline(always)]
-fn parse_key_a_x(input: &[u8]) -> IResult<&[u8], Key> {
+pub(crate) fn parse_key_a_x(input: &[u8]) -> IResult<&[u8], Key> {
     map(preceded(tag("a"), u32), |x| Key::Arg(x, None))(input)
 }
 
+/// Recognize free text that precedes the key/value pairs in some
+/// records written by _auditd(8)_, e.g. `auditd error halt, auid=0 …`.
+/// Returns the text without trailing comma.
+fn parse_free_text(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let is_kv = |input| terminated(parse_key, tag("="))(input).is_ok();
+    if !is_kv(input) {
+        let mut pos = 0;
+        while let Some(n) = input[pos..].iter().position(|c| *c == b' ' || *c == b'\n') {
+            let end = pos + n;
+            if input[end] == b'\n' {
+                break;
+            }
+            if is_kv(&input[end + 1..]) {
+                let text = input[..end].trim_ascii_end();
+                let text = text.strip_suffix(b",").unwrap_or(text);
+                return Ok((&input[end + 1..], text));
+            }
+            pos = end + 1;
+        }
+    }
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::Verify,
+    )))
+}
+
 /// Recognize identifiers (used in some irregular messages)
 /// Like [A-Za-z_][A-Za-z0-9_]*
 #[inline(always)]
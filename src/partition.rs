@@ -0,0 +1,71 @@
+use crate::*;
+
+/// How [`Event::partition_key`] derives keys for sharding event
+/// streams, e.g. across Kafka partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartitionStrategy {
+    /// Node name: all events from one host end up together.
+    Node,
+    /// Node name and login user ID (`auid`). User IDs are local to
+    /// a host, so the node name is included.
+    Auid,
+    /// Node name and session ID (`ses`).
+    Session,
+    /// Hash of the executable (`exe`), e.g. to analyze the behavior
+    /// of programs across the fleet.
+    Exe,
+}
+
+/// 64-bit FNV-1a. Unlike `std`'s `DefaultHasher`, results are stable
+/// across Rust versions and platforms.
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Event<'_> {
+    /// Returns the first value for `key` in any record, looking into
+    /// `msg='…'` maps of userspace records as well.
+    pub(crate) fn find_field(&self, key: &str) -> Option<&Value<'_>> {
+        self.records.iter().find_map(|(_, body)| {
+            body.get(key).or_else(|| match body.get("msg") {
+                Some(Value::Map(m)) => m.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+                _ => None,
+            })
+        })
+    }
+
+    /// Derives a key for sharding event streams so that related
+    /// events end up in the same partition. Keys are stable across
+    /// versions and platforms.
+    ///
+    /// Keys are formatted as `node:value`, or `value` for events
+    /// without a node name. `None` is returned if the event lacks the
+    /// relevant information, e.g. the login user ID has not been set,
+    /// so that callers can pick a fallback.
+    pub fn partition_key(&self, strategy: PartitionStrategy) -> Option<Vec<u8>> {
+        let value = match strategy {
+            PartitionStrategy::Node => return self.node.clone(),
+            PartitionStrategy::Auid => {
+                let auid = self
+                    .records
+                    .iter()
+                    .find_map(|(_, body)| identities(body).without_unset().auid)?;
+                auid.to_string().into_bytes()
+            }
+            PartitionStrategy::Session => SessionId::from_value(self.find_field("ses")?)?
+                .get()?
+                .to_string()
+                .into_bytes(),
+            PartitionStrategy::Exe => {
+                let exe = value_bytes(self.find_field("exe")?)?;
+                return Some(format!("{:016x}", fnv1a(exe)).into_bytes());
+            }
+        };
+        Some(match &self.node {
+            Some(node) => [node.as_slice(), b":", &value].concat(),
+            None => value,
+        })
+    }
+}
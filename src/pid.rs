@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use crate::*;
+
+/// Likelihood that a process ID refers to a different process than
+/// in earlier events, see [`PidTracker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ReuseConfidence {
+    /// No indication of reuse, or the process ID has not been seen
+    /// before
+    #[default]
+    None,
+    /// Weak indication: the command name changed without `execve`
+    /// (threads and `prctl(PR_SET_NAME)` can do that), the parent
+    /// became init, or the process has not been seen for longer than
+    /// the idle threshold
+    Low,
+    /// The executable changed without `execve`
+    Medium,
+    /// The parent process ID changed to something other than init
+    High,
+    /// A `fork`/`clone` returned the process ID as a new child
+    Certain,
+}
+
+/// What is known about a process, see [`PidTracker::get`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub ppid: Option<i64>,
+    /// Command name
+    pub comm: Option<Vec<u8>>,
+    /// Path of the executable
+    pub exe: Option<Vec<u8>>,
+    /// First event attributed to this process
+    pub first_seen: EventID,
+    /// Most recent event attributed to this process
+    pub last_seen: EventID,
+    /// Incremented whenever the process ID is considered reused with
+    /// at least [`ReuseConfidence::Medium`]. `(node, pid, generation)`
+    /// can be used as a join key.
+    pub generation: u32,
+}
+
+/// The subject of an event, taken from the `SYSCALL` record if there
+/// is one
+struct Observed<'e> {
+    pid: i64,
+    ppid: Option<i64>,
+    comm: Option<&'e [u8]>,
+    exe: Option<&'e [u8]>,
+    execve: bool,
+    child: Option<i64>,
+}
+
+impl<'e> Observed<'e> {
+    fn of(ev: &'e Event) -> Option<Self> {
+        let num = |k: &str| match ev.find_field(k) {
+            Some(Value::Number(n)) => n.as_i64(),
+            _ => None,
+        };
+        let bytes = |k: &str| ev.find_field(k).and_then(value_bytes);
+        let syscall = ev
+            .body(MessageType::SYSCALL)
+            .and_then(|b| b.get("SYSCALL"))
+            .and_then(value_bytes);
+        let success = bytes("success") == Some(b"yes");
+        let child = match syscall {
+            Some(b"clone" | b"clone3" | b"fork" | b"vfork") if success => {
+                num("exit").filter(|n| *n > 0)
+            }
+            _ => None,
+        };
+        Some(Observed {
+            pid: num("pid")?,
+            ppid: num("ppid"),
+            comm: bytes("comm"),
+            exe: bytes("exe"),
+            execve: ev.body(MessageType::EXECVE).is_some()
+                || matches!(syscall, Some(b"execve" | b"execveat")),
+            child,
+        })
+    }
+}
+
+/// Tracks processes by node name and process ID to detect PID reuse
+///
+/// Process IDs are recycled by the kernel, so joining events by
+/// `pid` across longer periods of time can attribute activity to the
+/// wrong process. The tracker remembers parent process ID, command
+/// name, and executable of each process and compares them against
+/// later events with the same process ID; see [`ReuseConfidence`]
+/// for the heuristics. Changes of command name and executable are
+/// expected after `execve`, recognized by an `EXECVE` record or the
+/// enriched `SYSCALL` field.
+///
+/// Events are expected in timestamp order.
+#[derive(Debug, Clone)]
+pub struct PidTracker {
+    processes: HashMap<(Option<Vec<u8>>, i64), ProcessInfo>,
+    idle_ms: Option<u64>,
+}
+
+impl Default for PidTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PidTracker {
+    /// Constructs a tracker without idle threshold.
+    pub fn new() -> Self {
+        PidTracker {
+            processes: HashMap::new(),
+            idle_ms: None,
+        }
+    }
+
+    /// Sets the time in milliseconds after which a process that has
+    /// not been seen is considered possibly gone.
+    pub fn idle_threshold(mut self, idle_ms: u64) -> Self {
+        self.idle_ms = Some(idle_ms);
+        self
+    }
+
+    fn assess(&self, prev: &ProcessInfo, ev: &Event, obs: &Observed) -> ReuseConfidence {
+        let changed = |old: &Option<Vec<u8>>, new: Option<&[u8]>| match (old, new) {
+            (Some(old), Some(new)) => old.as_slice() != new,
+            _ => false,
+        };
+        let mut conf = ReuseConfidence::None;
+        match (prev.ppid, obs.ppid) {
+            (Some(old), Some(1)) if old != 1 => conf = conf.max(ReuseConfidence::Low),
+            (Some(old), Some(new)) if old != new => conf = conf.max(ReuseConfidence::High),
+            _ => {}
+        }
+        if !obs.execve {
+            if changed(&prev.exe, obs.exe) {
+                conf = conf.max(ReuseConfidence::Medium);
+            }
+            if changed(&prev.comm, obs.comm) {
+                conf = conf.max(ReuseConfidence::Low);
+            }
+        }
+        if let Some(idle) = self.idle_ms {
+            if ev.id.timestamp.saturating_sub(prev.last_seen.timestamp) > idle {
+                conf = conf.max(ReuseConfidence::Low);
+            }
+        }
+        conf
+    }
+
+    /// Returns the likelihood that the process of `ev` is a different
+    /// one than the process with the same ID seen before, without
+    /// recording the event.
+    pub fn check(&self, ev: &Event) -> ReuseConfidence {
+        let Some(obs) = Observed::of(ev) else {
+            return ReuseConfidence::None;
+        };
+        match self.processes.get(&(ev.node.clone(), obs.pid)) {
+            Some(prev) => self.assess(prev, ev, &obs),
+            None => ReuseConfidence::None,
+        }
+    }
+
+    /// Records `ev` and returns the likelihood that its process is a
+    /// different one than the process with the same ID seen before.
+    ///
+    /// If `ev` is a successful `fork`/`clone`, the child's process ID
+    /// starts a new generation; a later event of the child then
+    /// returns [`ReuseConfidence::None`].
+    pub fn observe(&mut self, ev: &Event) -> ReuseConfidence {
+        let Some(obs) = Observed::of(ev) else {
+            return ReuseConfidence::None;
+        };
+        let key = (ev.node.clone(), obs.pid);
+        let conf = match self.processes.get(&key) {
+            Some(prev) => self.assess(prev, ev, &obs),
+            None => ReuseConfidence::None,
+        };
+        let fresh = |generation| ProcessInfo {
+            ppid: obs.ppid,
+            comm: obs.comm.map(<[u8]>::to_vec),
+            exe: obs.exe.map(<[u8]>::to_vec),
+            first_seen: ev.id,
+            last_seen: ev.id,
+            generation,
+        };
+        match self.processes.get_mut(&key) {
+            Some(info) if conf >= ReuseConfidence::Medium => *info = fresh(info.generation + 1),
+            Some(info) => {
+                info.ppid = obs.ppid.or(info.ppid);
+                if obs.comm.is_some() {
+                    info.comm = obs.comm.map(<[u8]>::to_vec);
+                }
+                if obs.exe.is_some() {
+                    info.exe = obs.exe.map(<[u8]>::to_vec);
+                }
+                info.last_seen = ev.id;
+            }
+            None => {
+                self.processes.insert(key, fresh(0));
+            }
+        }
+        if let Some(child) = obs.child {
+            let generation = self
+                .processes
+                .get(&(ev.node.clone(), child))
+                .map(|p| p.generation + 1)
+                .unwrap_or(0);
+            self.processes.insert(
+                (ev.node.clone(), child),
+                ProcessInfo {
+                    ppid: Some(obs.pid),
+                    comm: obs.comm.map(<[u8]>::to_vec),
+                    exe: obs.exe.map(<[u8]>::to_vec),
+                    first_seen: ev.id,
+                    last_seen: ev.id,
+                    generation,
+                },
+            );
+        }
+        conf
+    }
+
+    /// Returns what is known about process `pid` on `node`.
+    pub fn get(&self, node: Option<&[u8]>, pid: i64) -> Option<&ProcessInfo> {
+        self.processes.get(&(node.map(<[u8]>::to_vec), pid))
+    }
+
+    /// Forgets processes that have not been seen since `before`.
+    pub fn expire(&mut self, before: EventID) {
+        self.processes.retain(|_, p| p.last_seen >= before);
+    }
+
+    /// Returns the number of tracked processes.
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    /// Returns `true` if no processes are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+}
@@ -0,0 +1,24 @@
+//! The stable subset of the API, for glob imports
+//!
+//! ```
+//! use linux_audit_parser::prelude::*;
+//! let msg = Parser::default().parse(&b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n"[..]).unwrap();
+//! assert_eq!(msg.ty, MessageType::CWD);
+//! ```
+//!
+//! Items are only added to the prelude once their shape has settled.
+//! They are not removed or changed incompatibly without a major
+//! version bump. This includes [`ParsedValue`], which only has the
+//! variants that the parser produces; matching on [`Value`] instead
+//! should use a wildcard branch, as variants for constructed bodies
+//! may be added.
+//!
+//! Items that are public but not listed here, such as the support
+//! code for the [`key!`](crate::key!) macro, may change in minor
+//! releases.
+
+pub use crate::{
+    parse, parse_header, Body, DaemonRecord, Event, EventCoalescer, EventID, Key, Message,
+    MessageReader, MessageType, Number, OwnedMessage, ParseError, ParsedValue, Parser,
+    ParserBuilder, Quote, SeccompRecord, SockAddr, SyscallRecord, Value,
+};
@@ -0,0 +1,337 @@
+use std::fmt::{self, Display};
+
+use crate::validate::is_enriched_name;
+use crate::*;
+
+/// Fields that are shown first, per message type
+const IMPORTANT: &[(MessageType, &[&str])] = &[
+    (
+        MessageType::SYSCALL,
+        &[
+            "syscall", "success", "exit", "exe", "comm", "pid", "ppid", "auid", "uid", "euid",
+            "tty", "ses", "key",
+        ],
+    ),
+    (MessageType::EXECVE, &["argc"]),
+    (
+        MessageType::PATH,
+        &["item", "name", "nametype", "mode", "ouid", "ogid"],
+    ),
+    (MessageType::CWD, &["cwd"]),
+    (MessageType::PROCTITLE, &["proctitle"]),
+    (MessageType::SOCKADDR, &["saddr"]),
+];
+
+/// Human-friendly, multi-line rendering of a [`Message`], intended
+/// for alert tickets and command line tools.
+///
+/// Important fields for the message type are listed first. Enriched
+/// (ALL-CAPS) values are shown next to the raw value they resolve.
+/// Values that have not been enriched by _auditd(8)_ are resolved
+/// using the default [`Interpreters`], e.g. error numbers, file modes,
+/// capabilities, and, with the `syscalls` feature, syscall names:
+///
+/// ```text
+/// SYSCALL 1615114232.375:15558
+///   syscall : 59 (execve)
+///   success : yes
+///   …
+/// ```
+///
+/// Strings are escaped according to an [`Escaping`] policy, so that
+/// untrusted values (`comm`, `exe`, file names, …) cannot inject
+/// terminal control sequences or visually spoof other text.
+pub struct Pretty<'a, 'm> {
+    msg: &'a Message<'m>,
+    escaping: Escaping,
+}
+
+/// Policy for escaping untrusted strings in human-readable output,
+/// see [`Message::pretty_with`] and [`escape_for_display`]
+///
+/// Escaped characters are written as `\xNN` (ASCII) or `\u{NNNN}`.
+/// The default escapes everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Escaping {
+    /// Escape C0 and C1 control characters, DEL, invalid UTF-8, and
+    /// the backslash itself
+    pub control: bool,
+    /// Escape bidirectional formatting characters (U+202A … U+202E,
+    /// U+2066 … U+2069, LRM, RLM, ALM) that can reorder displayed
+    /// text
+    pub bidi: bool,
+    /// Escape invisible characters: zero-width spaces and joiners,
+    /// word joiner, invisible operators, soft hyphen, byte order
+    /// mark, and the like
+    pub invisible: bool,
+}
+
+impl Escaping {
+    /// Escape nothing; invalid UTF-8 is replaced by U+FFFD.
+    pub const NONE: Escaping = Escaping {
+        control: false,
+        bidi: false,
+        invisible: false,
+    };
+    /// Escape all characters covered by any of the knobs.
+    pub const ALL: Escaping = Escaping {
+        control: true,
+        bidi: true,
+        invisible: true,
+    };
+}
+
+impl Default for Escaping {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+fn is_bidi(c: char) -> bool {
+    matches!(c, '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{034f}'
+            | '\u{115f}'
+            | '\u{1160}'
+            | '\u{17b4}'
+            | '\u{17b5}'
+            | '\u{180e}'
+            | '\u{200b}'..='\u{200d}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{3164}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{feff}'
+            | '\u{ffa0}'
+    )
+}
+
+/// Renders `s` for display, escaping characters according to
+/// `escaping`.
+pub fn escape_for_display(s: &[u8], escaping: Escaping) -> String {
+    use std::fmt::Write;
+    if !escaping.control {
+        let s = String::from_utf8_lossy(s);
+        if !escaping.bidi && !escaping.invisible {
+            return s.into();
+        }
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            if (escaping.bidi && is_bidi(c)) || (escaping.invisible && is_invisible(c)) {
+                let _ = write!(out, "\\u{{{:04x}}}", c as u32);
+            } else {
+                out.push(c);
+            }
+        }
+        return out;
+    }
+    let mut out = String::with_capacity(s.len());
+    for chunk in s.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c == '\\' {
+                out.push_str("\\\\");
+            } else if c.is_ascii_control() {
+                let _ = write!(out, "\\x{:02x}", c as u32);
+            } else if c.is_control()
+                || (escaping.bidi && is_bidi(c))
+                || (escaping.invisible && is_invisible(c))
+            {
+                let _ = write!(out, "\\u{{{:04x}}}", c as u32);
+            } else {
+                out.push(c);
+            }
+        }
+        for b in chunk.invalid() {
+            let _ = write!(out, "\\x{b:02x}");
+        }
+    }
+    out
+}
+
+impl Message<'_> {
+    /// Returns a [`Display`]-able human-friendly rendering of the
+    /// message, escaping strings with the default [`Escaping`]
+    /// policy.
+    pub fn pretty(&self) -> Pretty<'_, '_> {
+        self.pretty_with(Escaping::default())
+    }
+
+    /// Returns a [`Display`]-able human-friendly rendering of the
+    /// message, escaping strings according to `escaping`.
+    pub fn pretty_with(&self, escaping: Escaping) -> Pretty<'_, '_> {
+        Pretty {
+            msg: self,
+            escaping,
+        }
+    }
+}
+
+impl Display for Pretty<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = self.msg;
+        // Cloning is cheap, string values are shared.
+        let mut body = msg.body.clone();
+        Interpreters::default().annotate(msg.ty, &mut body);
+        let body = &body;
+        write!(f, "{} {}", msg.ty, msg.id)?;
+        let esc = self.escaping;
+        if let Some(node) = &msg.node {
+            write!(f, " (node {})", escape_for_display(node, esc))?;
+        }
+        writeln!(f)?;
+
+        let important = IMPORTANT
+            .iter()
+            .find(|(ty, _)| *ty == msg.ty)
+            .map(|(_, fields)| *fields)
+            .unwrap_or(&[]);
+
+        // Collect (name, rendered value) lines: important fields
+        // first, then the rest in order; enriched values are folded
+        // into the line of the corresponding raw value.
+        let mut entries: Vec<&(Key, Value)> = body.into_iter().collect();
+        entries.sort_by_key(|(k, _)| {
+            important
+                .iter()
+                .position(|name| k == *name)
+                .unwrap_or(important.len())
+        });
+
+        let mut lines: Vec<(String, String)> = vec![];
+        for (k, v) in entries {
+            let name = k.to_string();
+            if is_enriched_name(name.as_bytes()) && body.get(name.to_ascii_lowercase()).is_some() {
+                continue;
+            }
+            match v {
+                Value::Map(vs) => {
+                    for (k2, v2) in vs {
+                        lines.push((format!("{name}.{k2}"), render(v2, esc)));
+                    }
+                }
+                _ => {
+                    let mut rendered = render(v, esc);
+                    if !is_enriched_name(name.as_bytes()) {
+                        match body.get(name.to_ascii_uppercase()).map(|v| render(v, esc)) {
+                            Some(resolved) if !resolved.is_empty() => {
+                                rendered = format!("{rendered} ({resolved})")
+                            }
+                            _ => {}
+                        }
+                    }
+                    lines.push((name, rendered));
+                }
+            }
+        }
+
+        let width = lines.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        for (k, v) in lines {
+            writeln!(f, "  {k:width$} : {v}")?;
+        }
+        Ok(())
+    }
+}
+
+fn render(v: &Value, esc: Escaping) -> String {
+    match v {
+        Value::Empty => "-".into(),
+        Value::Str(r, Quote::Braces) => format!("{{{}}}", escape_for_display(r, esc)),
+        Value::Str(r, _) => escape_for_display(r, esc),
+        Value::Owned(r) => escape_for_display(r, esc),
+        Value::Segments(segs) => escape_for_display(&segs.concat(), esc),
+        Value::Number(n) => n.to_string(),
+        Value::List(vs) | Value::StringifiedList(vs) => vs
+            .iter()
+            .map(|v| render(v, esc))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Map(vs) => vs
+            .iter()
+            .map(|(k, v)| format!("{k}={}", render(v, esc)))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Value::Skipped((args, bytes)) => format!("<skipped {args} args, {bytes} bytes>"),
+        Value::Literal(s) => s.to_string(),
+    }
+}
+
+/// Rendering of a [`Value`] with escaped strings, see
+/// [`Value::display_with`]
+pub struct DisplayValue<'a, 'v> {
+    value: &'a Value<'v>,
+    escaping: Escaping,
+}
+
+impl Value<'_> {
+    /// Returns a [`Display`]-able rendering of the value, escaping
+    /// strings according to `escaping`. The [`Display`]
+    /// implementation of `Value` uses the default [`Escaping`].
+    pub fn display_with(&self, escaping: Escaping) -> DisplayValue<'_, '_> {
+        DisplayValue {
+            value: self,
+            escaping,
+        }
+    }
+}
+
+impl Display for DisplayValue<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render(self.value, self.escaping))
+    }
+}
+
+/// Strings are escaped with the default [`Escaping`] policy, empty
+/// values are shown as `-`, list elements are separated by `, `.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let v = Value::Str(b"\x1b[2Jrm", Quote::Double);
+/// assert_eq!(v.to_string(), "\\x1b[2Jrm");
+/// ```
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(Escaping::default()).fmt(f)
+    }
+}
+
+/// Rendering of a [`Body`] with escaped strings, see
+/// [`Body::display_with`]
+pub struct DisplayBody<'a, 'b> {
+    body: &'a Body<'b>,
+    escaping: Escaping,
+}
+
+impl Body<'_> {
+    /// Returns a [`Display`]-able rendering of the body as
+    /// `key=value` pairs separated by spaces, escaping strings
+    /// according to `escaping`. The [`Display`] implementation of
+    /// `Body` uses the default [`Escaping`].
+    pub fn display_with(&self, escaping: Escaping) -> DisplayBody<'_, '_> {
+        DisplayBody {
+            body: self,
+            escaping,
+        }
+    }
+}
+
+impl Display for DisplayBody<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, (k, v)) in self.body.into_iter().enumerate() {
+            if n > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{k}={}", v.display_with(self.escaping))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Body<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(Escaping::default()).fmt(f)
+    }
+}
@@ -0,0 +1,257 @@
+//! Protobuf representation of [`Message`](crate::Message) and
+//! [`Event`](crate::Event), for use with [`prost`]
+//!
+//! The types correspond to `proto/audit.proto` in the source
+//! distribution. Byte strings are transmitted as `bytes`, numbers
+//! retain their radix.
+//!
+//! Converting back into [`crate::Message`] and [`crate::Event`] is
+//! lossless except for details that are not visible in serialized
+//! output: quoting of string values is not preserved, and
+//! [`Key::Vendor`](crate::Key::Vendor) keys are restored as
+//! [`Key::Name`](crate::Key::Name). The flags of
+//! [`crate::Message`] and the type name of unknown types are
+//! retained.
+
+use crate::owned::{EventParts, MessageParts};
+use crate::{EventID, MessageType, Number, OwnedValue};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    /// Milliseconds since the epoch
+    #[prost(uint64, tag = "1")]
+    pub timestamp: u64,
+    #[prost(uint32, tag = "2")]
+    pub sequence: u32,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub node: Option<Vec<u8>>,
+    #[prost(uint32, tag = "4")]
+    pub r#type: u32,
+    #[prost(message, repeated, tag = "5")]
+    pub body: Vec<Entry>,
+    /// See [`crate::Message::truncated`]
+    #[prost(bool, tag = "6")]
+    pub truncated: bool,
+    /// See [`crate::Message::hex_decoded`]
+    #[prost(bool, tag = "7")]
+    pub hex_decoded: bool,
+    /// See [`crate::Message::type_name`]
+    #[prost(bytes = "vec", optional, tag = "8")]
+    pub type_name: Option<Vec<u8>>,
+    /// See [`crate::Message::enriched`]
+    #[prost(bool, tag = "9")]
+    pub enriched: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Event {
+    #[prost(uint64, tag = "1")]
+    pub timestamp: u64,
+    #[prost(uint32, tag = "2")]
+    pub sequence: u32,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub node: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "4")]
+    pub records: Vec<Record>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Record {
+    #[prost(uint32, tag = "1")]
+    pub r#type: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub body: Vec<Entry>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Entry {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(message, optional, tag = "2")]
+    pub value: Option<Value>,
+}
+
+/// A value; `kind` is `None` for empty values
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Value {
+    #[prost(oneof = "value::Kind", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub kind: Option<value::Kind>,
+}
+
+pub mod value {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(bytes, tag = "1")]
+        Str(Vec<u8>),
+        #[prost(int64, tag = "2")]
+        Dec(i64),
+        #[prost(uint64, tag = "3")]
+        Hex(u64),
+        #[prost(uint64, tag = "4")]
+        Oct(u64),
+        #[prost(message, tag = "5")]
+        List(super::List),
+        #[prost(message, tag = "6")]
+        Map(super::Map),
+        #[prost(message, tag = "7")]
+        Skipped(super::Skipped),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct List {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Map {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Skipped {
+    #[prost(uint64, tag = "1")]
+    pub args: u64,
+    #[prost(uint64, tag = "2")]
+    pub bytes: u64,
+}
+
+impl From<OwnedValue> for Value {
+    fn from(v: OwnedValue) -> Self {
+        use value::Kind;
+        let kind = match v {
+            OwnedValue::Empty => None,
+            OwnedValue::Bytes(b) => Some(Kind::Str(b)),
+            OwnedValue::Number(Number::Dec(n)) => Some(Kind::Dec(n)),
+            OwnedValue::Number(Number::Hex(n)) => Some(Kind::Hex(n)),
+            OwnedValue::Number(Number::Oct(n)) => Some(Kind::Oct(n)),
+            OwnedValue::List(vs) => Some(Kind::List(List {
+                values: vs.into_iter().map(Value::from).collect(),
+            })),
+            OwnedValue::Map(vs) => Some(Kind::Map(Map {
+                entries: vs.into_iter().map(Entry::from).collect(),
+            })),
+            OwnedValue::Skipped { args, bytes } => Some(Kind::Skipped(Skipped {
+                args: args as u64,
+                bytes: bytes as u64,
+            })),
+        };
+        Value { kind }
+    }
+}
+
+impl From<(String, OwnedValue)> for Entry {
+    fn from((key, value): (String, OwnedValue)) -> Self {
+        Entry {
+            key,
+            value: Some(value.into()),
+        }
+    }
+}
+
+impl From<Value> for OwnedValue {
+    fn from(v: Value) -> Self {
+        use value::Kind;
+        match v.kind {
+            None => OwnedValue::Empty,
+            Some(Kind::Str(b)) => OwnedValue::Bytes(b),
+            Some(Kind::Dec(n)) => OwnedValue::Number(Number::Dec(n)),
+            Some(Kind::Hex(n)) => OwnedValue::Number(Number::Hex(n)),
+            Some(Kind::Oct(n)) => OwnedValue::Number(Number::Oct(n)),
+            Some(Kind::List(l)) => {
+                OwnedValue::List(l.values.into_iter().map(OwnedValue::from).collect())
+            }
+            Some(Kind::Map(m)) => OwnedValue::Map(m.entries.into_iter().map(Entry::into).collect()),
+            Some(Kind::Skipped(s)) => OwnedValue::Skipped {
+                args: s.args as usize,
+                bytes: s.bytes as usize,
+            },
+        }
+    }
+}
+
+impl From<Entry> for (String, OwnedValue) {
+    fn from(e: Entry) -> Self {
+        (e.key, e.value.map_or(OwnedValue::Empty, OwnedValue::from))
+    }
+}
+
+impl From<&crate::Message<'_>> for Message {
+    fn from(msg: &crate::Message) -> Self {
+        let p = MessageParts::from(msg);
+        Message {
+            timestamp: p.id.timestamp,
+            sequence: p.id.sequence,
+            node: p.node,
+            r#type: p.ty.0,
+            body: p.body.into_iter().map(Entry::from).collect(),
+            truncated: p.truncated,
+            hex_decoded: p.hex_decoded,
+            type_name: p.type_name,
+            enriched: p.enriched,
+        }
+    }
+}
+
+impl From<&crate::Event<'_>> for Event {
+    fn from(ev: &crate::Event) -> Self {
+        let p = EventParts::from(ev);
+        Event {
+            timestamp: p.id.timestamp,
+            sequence: p.id.sequence,
+            node: p.node,
+            records: p
+                .records
+                .into_iter()
+                .map(|(ty, body)| Record {
+                    r#type: ty.0,
+                    body: body.into_iter().map(Entry::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Message> for crate::Message<'_> {
+    fn from(msg: Message) -> Self {
+        MessageParts {
+            id: EventID {
+                timestamp: msg.timestamp,
+                sequence: msg.sequence,
+            },
+            node: msg.node,
+            ty: MessageType(msg.r#type),
+            body: msg.body.into_iter().map(Entry::into).collect(),
+            truncated: msg.truncated,
+            hex_decoded: msg.hex_decoded,
+            type_name: msg.type_name,
+            enriched: msg.enriched,
+        }
+        .into()
+    }
+}
+
+impl From<Event> for crate::Event<'_> {
+    fn from(ev: Event) -> Self {
+        EventParts {
+            id: EventID {
+                timestamp: ev.timestamp,
+                sequence: ev.sequence,
+            },
+            node: ev.node,
+            records: ev
+                .records
+                .into_iter()
+                .map(|r| {
+                    (
+                        MessageType(r.r#type),
+                        r.body.into_iter().map(Entry::into).collect(),
+                    )
+                })
+                .collect(),
+        }
+        .into()
+    }
+}
@@ -0,0 +1,217 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::*;
+
+/// Classes of fields that identify users or hosts, see
+/// [`identity_field`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdentityField {
+    /// Numeric user IDs: `uid`, `auid`, `euid`, `ouid`, …
+    UserId,
+    /// Numeric group IDs: `gid`, `egid`, `ogid`, …
+    GroupId,
+    /// User names: `acct` and enriched user ID fields (`AUID`,
+    /// `UID`, …)
+    UserName,
+    /// Group names: enriched group ID fields (`GID`, `EGID`, …)
+    GroupName,
+    /// Host names: `hostname` and the node name of messages and
+    /// events
+    Hostname,
+    /// Network addresses: `addr`
+    Address,
+}
+
+impl IdentityField {
+    /// All classes
+    pub const ALL: &'static [IdentityField] = &[
+        IdentityField::UserId,
+        IdentityField::GroupId,
+        IdentityField::UserName,
+        IdentityField::GroupName,
+        IdentityField::Hostname,
+        IdentityField::Address,
+    ];
+
+    /// Label that is mixed into the HMAC input, so that equal values
+    /// from different classes get different pseudonyms
+    fn domain(&self) -> &'static [u8] {
+        match self {
+            IdentityField::UserId => b"uid",
+            IdentityField::GroupId => b"gid",
+            IdentityField::UserName => b"user",
+            IdentityField::GroupName => b"group",
+            IdentityField::Hostname => b"host",
+            IdentityField::Address => b"addr",
+        }
+    }
+}
+
+/// Determines whether the field `key` identifies a user or host.
+pub fn identity_field(key: &Key) -> Option<IdentityField> {
+    match key {
+        Key::NameUID(_) | Key::NameGID(_) => key.identity_kind().map(|k| {
+            if k.is_group() {
+                IdentityField::GroupId
+            } else {
+                IdentityField::UserId
+            }
+        }),
+        Key::NameTranslated(r) => enriched_identity_field(r),
+        Key::Name(r) if is_enriched_name(r) => enriched_identity_field(r),
+        Key::Name(r) => match r.as_slice() {
+            b"acct" => Some(IdentityField::UserName),
+            b"hostname" => Some(IdentityField::Hostname),
+            b"addr" => Some(IdentityField::Address),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn enriched_identity_field(name: &[u8]) -> Option<IdentityField> {
+    identity_kind_by_name(&name.to_ascii_lowercase()).map(|k| {
+        if k.is_group() {
+            IdentityField::GroupName
+        } else {
+            IdentityField::UserName
+        }
+    })
+}
+
+/// Replaces identifying values with keyed pseudonyms
+///
+/// Pseudonyms are derived from the value using HMAC-SHA256 with a
+/// secret key, so that the same value of the same
+/// [`IdentityField`] class is always replaced by the same pseudonym,
+/// across messages, events, and runs. This keeps records of the same
+/// user or host linkable without revealing who they are, as long as
+/// the key is kept secret.
+///
+/// A pseudonym consists of the first 16 hexadecimal digits of the
+/// HMAC. Values that do not identify anyone – unset IDs (`-1`,
+/// `4294967295`), `?`, `(none)`, and empty values – are kept as they
+/// are.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let p = Pseudonymizer::new(b"secret").fields(&[IdentityField::UserId]);
+/// let mut msg = parse(&b"type=LOGIN msg=audit(1.000:1): pid=1 uid=0 old-auid=4294967295 auid=1000 tty=(none) old-ses=4294967295 ses=1 res=1\n"[..], false).unwrap();
+/// p.pseudonymize_message(&mut msg);
+/// assert_ne!(msg.body.get("auid").unwrap(), "1000");
+/// assert_eq!(msg.body.get("old-auid").unwrap(), &Value::Number(Number::Dec(4294967295)));
+/// ```
+#[derive(Clone)]
+pub struct Pseudonymizer {
+    mac: Hmac<Sha256>,
+    fields: Vec<IdentityField>,
+}
+
+impl std::fmt::Debug for Pseudonymizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Pseudonymizer")
+            .field("fields", &self.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Pseudonymizer {
+    /// Constructs a pseudonymizer using the secret `key` that
+    /// replaces all classes of identifying fields.
+    pub fn new(key: &[u8]) -> Self {
+        Pseudonymizer {
+            mac: Hmac::new_from_slice(key).expect("HMAC accepts keys of any length"),
+            fields: IdentityField::ALL.to_vec(),
+        }
+    }
+
+    /// Sets the classes of fields to be replaced.
+    pub fn fields(mut self, fields: &[IdentityField]) -> Self {
+        self.fields = fields.to_vec();
+        self
+    }
+
+    /// Returns the pseudonym for `value` in the class `field`.
+    pub fn pseudonym(&self, field: IdentityField, value: &[u8]) -> Vec<u8> {
+        let mut mac = self.mac.clone();
+        mac.update(field.domain());
+        mac.update(b"\0");
+        mac.update(value);
+        let digest = mac.finalize().into_bytes();
+        digest[..8]
+            .iter()
+            .flat_map(|b| {
+                let hex = b"0123456789abcdef";
+                [hex[(b >> 4) as usize], hex[(b & 0xf) as usize]]
+            })
+            .collect()
+    }
+
+    fn replace(&self, field: IdentityField, value: &Value) -> Option<Vec<u8>> {
+        if !self.fields.contains(&field) {
+            return None;
+        }
+        let input = match value {
+            Value::Number(Number::Dec(-1)) => return None,
+            Value::Number(Number::Dec(n)) if *n == UNSET_ID as i64 => return None,
+            Value::Number(n) => n.to_string().into_bytes(),
+            v => match value_bytes(v) {
+                Some(b"" | b"?" | b"(none)") | None => return None,
+                Some(b) => b.to_vec(),
+            },
+        };
+        Some(self.pseudonym(field, &input))
+    }
+
+    fn transform<'a>(&self, k: &Key, v: Value<'a>) -> Value<'a> {
+        match v {
+            Value::Map(vs) => Value::Map(
+                vs.into_iter()
+                    .map(|(k, v)| {
+                        let v = self.transform(&k, v);
+                        (k, v)
+                    })
+                    .collect(),
+            ),
+            v => match identity_field(k).and_then(|f| self.replace(f, &v)) {
+                Some(p) => Value::Owned(p),
+                None => v,
+            },
+        }
+    }
+
+    /// Replaces identifying values in `body`, including those in the
+    /// `msg` field of userspace messages.
+    pub fn pseudonymize_body(&self, body: &mut Body) {
+        body.map_entries(|k, v| {
+            let v = self.transform(&k, v);
+            Some((k, v))
+        });
+    }
+
+    fn pseudonymize_node(&self, node: &mut Option<Vec<u8>>) {
+        if !self.fields.contains(&IdentityField::Hostname) {
+            return;
+        }
+        if let Some(n) = node {
+            *n = self.pseudonym(IdentityField::Hostname, n);
+        }
+    }
+
+    /// Replaces identifying values in the body and the node name of
+    /// `msg`.
+    pub fn pseudonymize_message(&self, msg: &mut Message) {
+        self.pseudonymize_node(&mut msg.node);
+        self.pseudonymize_body(&mut msg.body);
+    }
+
+    /// Replaces identifying values in all records and the node name
+    /// of `ev`.
+    pub fn pseudonymize_event(&self, ev: &mut Event) {
+        self.pseudonymize_node(&mut ev.node);
+        for (_, body) in ev.records.iter_mut() {
+            self.pseudonymize_body(body);
+        }
+    }
+}
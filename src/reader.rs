@@ -0,0 +1,193 @@
+use std::io::{self, Read};
+use std::ops::Range;
+
+use crate::*;
+
+/// Number of bytes requested from the underlying reader at once
+const READ_SIZE: usize = 64 * 1024;
+
+/// Default for [`MessageReader::max_line_length`]
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+/// Iterator over the messages read from an [`io::Read`], e.g. a log
+/// file, a pipe, or a socket
+///
+/// Input is buffered and split into lines, so reads that end in the
+/// middle of a line are handled. Empty lines are skipped. A final
+/// line without newline is parsed as if it were terminated. After a
+/// read error, which is returned as [`ParseError::Io`], the data
+/// buffered so far is processed and iteration ends. Lines that exceed
+/// the [maximum length](Self::max_line_length) are skipped up to the
+/// next newline and returned as [`ParseError::LineTooLong`].
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let input = &b"type=CWD msg=audit(1.000:1): cwd=\"/root\"\n\ntype=EOE msg=audit(1.000:1): \n"[..];
+/// let types: Vec<_> = MessageReader::new(input).map(|m| m.unwrap().ty).collect();
+/// assert_eq!(types, [MessageType::CWD, MessageType::EOE]);
+/// ```
+#[derive(Debug)]
+pub struct MessageReader<R> {
+    inner: R,
+    parser: Parser,
+    buf: Vec<u8>,
+    /// Start of the data in `buf` that has not been returned
+    pos: usize,
+    /// Offset in `buf` from which to search for the next newline
+    scan: usize,
+    eof: bool,
+    line: usize,
+    max_line_length: usize,
+    /// Set while the rest of a line that is too long is skipped
+    discard: bool,
+    /// If set, interrupted reads are returned as [`ParseError::Io`]
+    /// instead of being retried. Used by the _audisp_ runtime.
+    pub(crate) interruptible: bool,
+}
+
+impl<R: Read> MessageReader<R> {
+    /// Constructs a reader that uses the default [`Parser`]
+    /// configuration.
+    pub fn new(inner: R) -> Self {
+        MessageReader {
+            inner,
+            parser: Parser::default(),
+            buf: Vec::with_capacity(READ_SIZE),
+            pos: 0,
+            scan: 0,
+            eof: false,
+            line: 0,
+            max_line_length: MAX_LINE_LENGTH,
+            discard: false,
+            interruptible: false,
+        }
+    }
+
+    /// Sets the maximum length of a line in bytes, not counting the
+    /// newline. Default: 1 MiB
+    pub fn max_line_length(mut self, len: usize) -> Self {
+        self.max_line_length = len;
+        self
+    }
+
+    /// Sets the [`Parser`] configuration.
+    pub fn with_parser(mut self, parser: Parser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Returns the number of the line that has been read last,
+    /// starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the underlying reader. Data that has been read but
+    /// not returned yet is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Stops reading from the underlying reader. The lines that have
+    /// been buffered are still returned.
+    #[cfg(feature = "audisp")]
+    pub(crate) fn stop(&mut self) {
+        self.eof = true;
+    }
+
+    /// Returns the next line that is not empty.
+    #[cfg(feature = "audisp")]
+    pub(crate) fn next_raw(&mut self) -> Option<Result<&[u8], ParseError>> {
+        self.next_nonempty()
+            .map(|line| line.map(|line| &self.buf[line]))
+    }
+
+    fn next_nonempty(&mut self) -> Option<Result<Range<usize>, ParseError>> {
+        loop {
+            let line = match self.next_line()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if !self.buf[line.clone()].iter().all(u8::is_ascii_whitespace) {
+                return Some(Ok(line));
+            }
+        }
+    }
+
+    fn too_long(&self) -> ParseError {
+        ParseError::LineTooLong {
+            line: self.line,
+            limit: self.max_line_length,
+        }
+    }
+
+    /// Returns the position of the next line in `buf`, reading more
+    /// data as necessary. Lines that are too long are dropped from the
+    /// buffer as soon as the limit has been exceeded.
+    fn next_line(&mut self) -> Option<Result<Range<usize>, ParseError>> {
+        loop {
+            if let Some(n) = self.buf[self.scan..].iter().position(|c| *c == b'\n') {
+                let end = self.scan + n + 1;
+                let line = self.pos..end;
+                self.pos = end;
+                self.scan = end;
+                if std::mem::take(&mut self.discard) {
+                    continue;
+                }
+                self.line += 1;
+                if line.len() - 1 > self.max_line_length {
+                    return Some(Err(self.too_long()));
+                }
+                return Some(Ok(line));
+            }
+            if self.discard || self.buf.len() - self.pos > self.max_line_length {
+                self.pos = self.buf.len();
+                self.scan = self.buf.len();
+                if !self.discard {
+                    self.discard = true;
+                    self.line += 1;
+                    return Some(Err(self.too_long()));
+                }
+            }
+            if self.eof {
+                if self.pos == self.buf.len() {
+                    return None;
+                }
+                self.buf.push(b'\n');
+                let line = self.pos..self.buf.len();
+                self.pos = self.buf.len();
+                self.scan = self.buf.len();
+                self.line += 1;
+                return Some(Ok(line));
+            }
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+            self.scan = self.buf.len();
+            let len = self.buf.len();
+            self.buf.resize(len + READ_SIZE, 0);
+            let result = self.inner.read(&mut self.buf[len..]);
+            self.buf.truncate(len + *result.as_ref().unwrap_or(&0));
+            match result {
+                Ok(0) => self.eof = true,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted && !self.interruptible => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => return Some(Err(e.into())),
+                Err(e) => {
+                    self.eof = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for MessageReader<R> {
+    type Item = Result<Message<'static>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_nonempty()? {
+            Ok(line) => Some(self.parser.parse(&self.buf[line])),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
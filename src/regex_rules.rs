@@ -0,0 +1,142 @@
+use regex::bytes::{Regex, RegexSet};
+
+use crate::*;
+
+impl Value<'_> {
+    /// Returns `true` if the value is a string that `re` matches. The
+    /// raw bytes are matched, so non-UTF-8 values such as decoded
+    /// hex strings need no lossy conversion.
+    pub fn matches_regex(&self, re: &Regex) -> bool {
+        self.as_bytes().is_some_and(|s| re.is_match(&s))
+    }
+}
+
+impl Body<'_> {
+    /// Returns `true` if the first value for `key` is a string that
+    /// `re` matches.
+    pub fn matches<K: AsRef<[u8]>>(&self, key: K, re: &Regex) -> bool {
+        self.get(key).is_some_and(|v| v.matches_regex(re))
+    }
+}
+
+/// Regular expressions for one field
+#[derive(Debug, Clone)]
+struct FieldRules {
+    key: Vec<u8>,
+    set: RegexSet,
+    /// Rule index for each expression in `set`
+    rules: Vec<usize>,
+}
+
+/// A set of rules, each consisting of a field name and a regular
+/// expression, that are compiled once and matched together
+///
+/// Expressions for the same field are combined into a single
+/// [`RegexSet`], so that every field value is scanned once,
+/// regardless of the number of rules.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let rules = RegexRules::new([
+///     ("exe", r"^/(tmp|dev/shm)/"),
+///     ("comm", r"^(nc|ncat|socat)$"),
+///     ("exe", r"/\.[^/]+$"),
+/// ]).unwrap();
+/// let msg = parse(&b"type=SYSCALL msg=audit(1.000:1): comm=\"nc\" exe=\"/tmp/.x\"\n"[..], false).unwrap();
+/// assert_eq!(rules.matches(&msg.body), [0, 1, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexRules {
+    fields: Vec<FieldRules>,
+    len: usize,
+}
+
+impl RegexRules {
+    /// Compiles `rules`. Rules are identified by their position.
+    pub fn new<I, K, P>(rules: I) -> Result<Self, regex::Error>
+    where
+        I: IntoIterator<Item = (K, P)>,
+        K: AsRef<[u8]>,
+        P: AsRef<str>,
+    {
+        let mut grouped: Vec<(Vec<u8>, Vec<String>, Vec<usize>)> = vec![];
+        let mut len = 0;
+        for (n, (key, pattern)) in rules.into_iter().enumerate() {
+            let key = key.as_ref();
+            let pos = match grouped.iter().position(|(k, _, _)| k == key) {
+                Some(pos) => pos,
+                None => {
+                    grouped.push((key.to_vec(), vec![], vec![]));
+                    grouped.len() - 1
+                }
+            };
+            grouped[pos].1.push(pattern.as_ref().into());
+            grouped[pos].2.push(n);
+            len = n + 1;
+        }
+        let fields = grouped
+            .into_iter()
+            .map(|(key, patterns, rules)| {
+                Ok(FieldRules {
+                    key,
+                    set: RegexSet::new(patterns)?,
+                    rules,
+                })
+            })
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(RegexRules { fields, len })
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set contains no rules.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn match_into(&self, body: &Body, matched: &mut [bool]) {
+        for (k, v) in body {
+            let Some(f) = self.fields.iter().find(|f| k == &f.key[..]) else {
+                continue;
+            };
+            if let Some(s) = v.as_bytes() {
+                for i in f.set.matches(&s).iter() {
+                    matched[f.rules[i]] = true;
+                }
+            }
+        }
+    }
+
+    /// Returns the indices of the rules that match a value in
+    /// `body`, in ascending order. Like [`Matcher`], only string
+    /// values are matched.
+    pub fn matches(&self, body: &Body) -> Vec<usize> {
+        let mut matched = vec![false; self.len];
+        self.match_into(body, &mut matched);
+        (0..self.len).filter(|n| matched[*n]).collect()
+    }
+
+    /// Returns the indices of the rules that match a value in any
+    /// record of `ev`, in ascending order.
+    pub fn matches_event(&self, ev: &Event) -> Vec<usize> {
+        let mut matched = vec![false; self.len];
+        for (_, body) in &ev.records {
+            self.match_into(body, &mut matched);
+        }
+        (0..self.len).filter(|n| matched[*n]).collect()
+    }
+
+    /// Returns `true` if any rule matches a value in `body`.
+    pub fn is_match(&self, body: &Body) -> bool {
+        body.into_iter().any(|(k, v)| {
+            self.fields
+                .iter()
+                .find(|f| k == &f.key[..])
+                .zip(v.as_bytes())
+                .is_some_and(|(f, s)| f.set.is_match(&s))
+        })
+    }
+}
@@ -0,0 +1,456 @@
+//! Parser for audit rules in _auditctl(8)_ syntax, as found in
+//! `audit.rules` files
+//!
+//! Rules carry keys (`-k`) that the kernel attaches to the records it
+//! generates, as `key=` field. [`KeyIndex`] maps such keys back to the
+//! rules that may have generated an event.
+//!
+//! ```
+//! # use linux_audit_parser::*;
+//! use linux_audit_parser::rules::*;
+//! let rule: Rule = "-a always,exit -F arch=b64 -S execve -k exec".parse().unwrap();
+//! let Rule::Syscall(r) = &rule else { panic!() };
+//! assert_eq!((r.action, r.list), (Action::Always, List::Exit));
+//! assert_eq!(r.syscalls, ["execve"]);
+//! assert_eq!(r.keys, ["exec"]);
+//!
+//! let index = KeyIndex::new([&rule]);
+//! let msg = parse(&b"type=SYSCALL msg=audit(1.000:1): syscall=59 key=\"exec\"\n"[..], false).unwrap();
+//! assert_eq!(index.rules_for(&msg.body), [0]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::constants::FIELD_TYPES;
+use crate::{value_bytes, Body, Common, Event, FieldType, Key};
+
+/// Error from parsing a rule
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RuleError {
+    #[error("empty rule")]
+    Empty,
+    #[error("unknown option {0}")]
+    UnknownOption(String),
+    #[error("missing argument for {0}")]
+    MissingArgument(String),
+    #[error("invalid list/action {0}")]
+    InvalidListAction(String),
+    #[error("invalid condition {0}")]
+    InvalidCondition(String),
+    #[error("invalid permissions {0}")]
+    InvalidPermissions(String),
+    #[error("option {0} cannot be used in this rule")]
+    UnexpectedOption(String),
+}
+
+/// Whether events matching a rule are recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Always,
+    Never,
+}
+
+/// Filter list a rule is added to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum List {
+    Task,
+    Exit,
+    User,
+    Exclude,
+    Filesystem,
+    IoUring,
+}
+
+/// How a rule is applied to the list (`-a`, `-A`, `-d`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Append,
+    Prepend,
+    Delete,
+}
+
+/// Comparison operator in `-F` and `-C` conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    /// `&`
+    BitMask,
+    /// `&=`
+    BitTest,
+}
+
+/// Operators, longest first so that e.g. `!=` is not taken for `=`
+const OPERATORS: &[(&str, Operator)] = &[
+    ("!=", Operator::Ne),
+    ("<=", Operator::Le),
+    (">=", Operator::Ge),
+    ("&=", Operator::BitTest),
+    ("=", Operator::Eq),
+    ("<", Operator::Lt),
+    (">", Operator::Gt),
+    ("&", Operator::BitMask),
+];
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (s, _) = OPERATORS.iter().find(|(_, op)| op == self).unwrap();
+        f.write_str(s)
+    }
+}
+
+/// A `-F` field condition, or a `-C` comparison between two fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub field: String,
+    pub op: Operator,
+    /// Value, or the second field for comparisons
+    pub value: String,
+}
+
+impl FromStr for Condition {
+    type Err = RuleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RuleError::InvalidCondition(s.into());
+        let pos = s.find(['!', '<', '>', '=', '&']).ok_or_else(invalid)?;
+        let (field, rest) = s.split_at(pos);
+        let (op_str, op) = OPERATORS
+            .iter()
+            .find(|(o, _)| rest.starts_with(o))
+            .ok_or_else(invalid)?;
+        let value = &rest[op_str.len()..];
+        if field.is_empty() || value.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Condition {
+            field: field.into(),
+            op: *op,
+            value: value.into(),
+        })
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.field, self.op, self.value)
+    }
+}
+
+impl Condition {
+    /// Returns the type of the field according to the field
+    /// dictionary, if the field also occurs in records under the
+    /// same name.
+    pub fn field_type(&self) -> Option<FieldType> {
+        FIELD_TYPES.get(self.field.as_bytes()).copied()
+    }
+}
+
+/// A syscall or filter rule (`-a`, `-A`, `-d`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallRule {
+    pub command: Command,
+    pub list: List,
+    pub action: Action,
+    /// Syscall names or numbers, `all` for all syscalls
+    pub syscalls: Vec<String>,
+    /// `-F` conditions, including `arch`
+    pub fields: Vec<Condition>,
+    /// `-C` comparisons
+    pub comparisons: Vec<Condition>,
+    /// Keys given as `-k` or `-F key=…`
+    pub keys: Vec<String>,
+}
+
+/// A file system watch (`-w`, `-W`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchRule {
+    /// Set for `-W`
+    pub delete: bool,
+    pub path: String,
+    /// Permission filter (`-p`), a combination of `r`, `w`, `x`, and
+    /// `a`
+    pub perms: Option<String>,
+    pub keys: Vec<String>,
+}
+
+/// A rule in _auditctl(8)_ syntax
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Syscall(SyscallRule),
+    Watch(WatchRule),
+    /// Any other option, e.g. `-D`, `-b 8192`, or `-e 2`
+    Control {
+        option: String,
+        value: Option<String>,
+    },
+}
+
+impl Rule {
+    /// Returns the keys of syscall rules and watches.
+    pub fn keys(&self) -> &[String] {
+        match self {
+            Rule::Syscall(r) => &r.keys,
+            Rule::Watch(r) => &r.keys,
+            Rule::Control { .. } => &[],
+        }
+    }
+}
+
+fn parse_list_action(s: &str) -> Result<(List, Action), RuleError> {
+    let invalid = || RuleError::InvalidListAction(s.into());
+    let (a, b) = s.split_once(',').ok_or_else(invalid)?;
+    let list = |s| {
+        Some(match s {
+            "task" => List::Task,
+            "exit" => List::Exit,
+            "user" => List::User,
+            "exclude" => List::Exclude,
+            "filesystem" => List::Filesystem,
+            "io_uring" => List::IoUring,
+            _ => return None,
+        })
+    };
+    let action = |s| match s {
+        "always" => Some(Action::Always),
+        "never" => Some(Action::Never),
+        _ => None,
+    };
+    match (list(a), action(b), action(a), list(b)) {
+        (Some(l), Some(a), _, _) | (_, _, Some(a), Some(l)) => Ok((l, a)),
+        _ => Err(invalid()),
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut args = s.split_whitespace();
+        let first = args.next().ok_or(RuleError::Empty)?;
+        let mut arg = |opt: &str| {
+            args.next()
+                .ok_or_else(|| RuleError::MissingArgument(opt.into()))
+        };
+        let mut rule = match first {
+            "-a" | "-A" | "-d" => {
+                let (list, action) = parse_list_action(arg(first)?)?;
+                let command = match first {
+                    "-a" => Command::Append,
+                    "-A" => Command::Prepend,
+                    _ => Command::Delete,
+                };
+                Rule::Syscall(SyscallRule {
+                    command,
+                    list,
+                    action,
+                    syscalls: vec![],
+                    fields: vec![],
+                    comparisons: vec![],
+                    keys: vec![],
+                })
+            }
+            "-w" | "-W" => Rule::Watch(WatchRule {
+                delete: first == "-W",
+                path: arg(first)?.into(),
+                perms: None,
+                keys: vec![],
+            }),
+            "-b" | "-f" | "-e" | "-r" | "--backlog_wait_time" => {
+                let value = arg(first)?.into();
+                if let Some(extra) = args.next() {
+                    return Err(RuleError::UnexpectedOption(extra.into()));
+                }
+                return Ok(Rule::Control {
+                    option: first.into(),
+                    value: Some(value),
+                });
+            }
+            "-D" | "-i" | "-c" | "--loginuid-immutable" | "--reset-lost" => {
+                if let Some(extra) = args.next() {
+                    return Err(RuleError::UnexpectedOption(extra.into()));
+                }
+                return Ok(Rule::Control {
+                    option: first.into(),
+                    value: None,
+                });
+            }
+            _ => return Err(RuleError::UnknownOption(first.into())),
+        };
+        while let Some(opt) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| RuleError::MissingArgument(opt.into()))?;
+            match (&mut rule, opt) {
+                (Rule::Syscall(r), "-S") => r.syscalls.extend(value.split(',').map(String::from)),
+                (Rule::Syscall(r), "-F") => {
+                    let c: Condition = value.parse()?;
+                    if c.field == "key" && c.op == Operator::Eq {
+                        r.keys.push(c.value);
+                    } else {
+                        r.fields.push(c);
+                    }
+                }
+                (Rule::Syscall(r), "-C") => r.comparisons.push(value.parse()?),
+                (Rule::Syscall(r), "-k") => r.keys.push(value.into()),
+                (Rule::Watch(r), "-p") => {
+                    if value.is_empty() || !value.chars().all(|c| "rwxa".contains(c)) {
+                        return Err(RuleError::InvalidPermissions(value.into()));
+                    }
+                    r.perms = Some(value.into());
+                }
+                (Rule::Watch(r), "-k") => r.keys.push(value.into()),
+                (_, "-S" | "-F" | "-C" | "-k" | "-p") => {
+                    return Err(RuleError::UnexpectedOption(opt.into()))
+                }
+                _ => return Err(RuleError::UnknownOption(opt.into())),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// Formats the rule in _auditctl(8)_ syntax. Conditions come first,
+/// followed by syscalls, comparisons, and keys.
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Syscall(r) => {
+                let cmd = match r.command {
+                    Command::Append => "-a",
+                    Command::Prepend => "-A",
+                    Command::Delete => "-d",
+                };
+                let action = match r.action {
+                    Action::Always => "always",
+                    Action::Never => "never",
+                };
+                let list = match r.list {
+                    List::Task => "task",
+                    List::Exit => "exit",
+                    List::User => "user",
+                    List::Exclude => "exclude",
+                    List::Filesystem => "filesystem",
+                    List::IoUring => "io_uring",
+                };
+                write!(f, "{cmd} {action},{list}")?;
+                for c in &r.fields {
+                    write!(f, " -F {c}")?;
+                }
+                if !r.syscalls.is_empty() {
+                    write!(f, " -S {}", r.syscalls.join(","))?;
+                }
+                for c in &r.comparisons {
+                    write!(f, " -C {c}")?;
+                }
+                for k in &r.keys {
+                    write!(f, " -k {k}")?;
+                }
+                Ok(())
+            }
+            Rule::Watch(r) => {
+                write!(f, "{} {}", if r.delete { "-W" } else { "-w" }, r.path)?;
+                if let Some(p) = &r.perms {
+                    write!(f, " -p {p}")?;
+                }
+                for k in &r.keys {
+                    write!(f, " -k {k}")?;
+                }
+                Ok(())
+            }
+            Rule::Control {
+                option,
+                value: None,
+            } => f.write_str(option),
+            Rule::Control {
+                option,
+                value: Some(v),
+            } => write!(f, "{option} {v}"),
+        }
+    }
+}
+
+/// Parses a rules file. Empty lines and comments are skipped.
+/// Returns the line number, starting at 1, along with each rule.
+pub fn parse_rules(text: &str) -> impl Iterator<Item = (usize, Result<Rule, RuleError>)> + '_ {
+    text.lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| (n, line.parse()))
+}
+
+/// Separator between multiple keys in `key=` fields
+const KEY_SEPARATOR: u8 = 0x01;
+
+/// Returns the keys in the `key` field of `body`. The kernel joins
+/// multiple keys with `\x01`, which causes the field to be
+/// hex-encoded.
+pub fn record_keys<'b>(body: &'b Body) -> Vec<&'b [u8]> {
+    match body
+        .get_key(&Key::Common(Common::Key))
+        .and_then(value_bytes)
+    {
+        Some(v) => v.split(|c| *c == KEY_SEPARATOR).collect(),
+        None => vec![],
+    }
+}
+
+/// Maps rule keys to rules, see the [module documentation](self)
+#[derive(Debug, Clone, Default)]
+pub struct KeyIndex {
+    keys: HashMap<Vec<u8>, Vec<usize>>,
+}
+
+impl KeyIndex {
+    /// Builds the index. Rules are identified by their position.
+    pub fn new<'r, I: IntoIterator<Item = &'r Rule>>(rules: I) -> Self {
+        let mut keys: HashMap<_, Vec<_>> = HashMap::new();
+        for (n, rule) in rules.into_iter().enumerate() {
+            for k in rule.keys() {
+                let ids = keys.entry(k.as_bytes().to_vec()).or_default();
+                if ids.last() != Some(&n) {
+                    ids.push(n);
+                }
+            }
+        }
+        KeyIndex { keys }
+    }
+
+    /// Returns the rules that carry `key`.
+    pub fn get(&self, key: &[u8]) -> &[usize] {
+        self.keys.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the rules that carry any of the keys of `body`, in
+    /// ascending order.
+    pub fn rules_for(&self, body: &Body) -> Vec<usize> {
+        let mut ids: Vec<_> = record_keys(body)
+            .into_iter()
+            .flat_map(|k| self.get(k))
+            .copied()
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the rules that carry any of the keys in the records
+    /// of `ev`, in ascending order.
+    pub fn rules_for_event(&self, ev: &Event) -> Vec<usize> {
+        let mut ids: Vec<_> = ev
+            .records
+            .iter()
+            .flat_map(|(_, body)| self.rules_for(body))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
@@ -0,0 +1,142 @@
+use crate::*;
+
+/// `SECCOMP_RET_ACTION_FULL`
+const ACTION_MASK: u32 = 0xffff_0000;
+/// `SECCOMP_RET_DATA`
+const DATA_MASK: u32 = 0x0000_ffff;
+
+/// Action taken by a seccomp filter, decoded from the `code` field of
+/// `SECCOMP` records, see `linux/seccomp.h`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeccompAction {
+    /// `SECCOMP_RET_KILL_PROCESS`
+    KillProcess,
+    /// `SECCOMP_RET_KILL_THREAD`
+    KillThread,
+    /// `SECCOMP_RET_TRAP`: `SIGSYS` is sent
+    Trap,
+    /// `SECCOMP_RET_ERRNO`: the syscall fails with the given error
+    /// number
+    Errno(u16),
+    /// `SECCOMP_RET_USER_NOTIF`
+    UserNotif,
+    /// `SECCOMP_RET_TRACE`, with the value passed to the tracer
+    Trace(u16),
+    /// `SECCOMP_RET_LOG`
+    Log,
+    /// `SECCOMP_RET_ALLOW`
+    Allow,
+    /// Any other value
+    Unknown(u32),
+}
+
+impl From<u32> for SeccompAction {
+    fn from(code: u32) -> Self {
+        let data = (code & DATA_MASK) as u16;
+        match code & ACTION_MASK {
+            0x8000_0000 => SeccompAction::KillProcess,
+            0x0000_0000 => SeccompAction::KillThread,
+            0x0003_0000 => SeccompAction::Trap,
+            0x0005_0000 => SeccompAction::Errno(data),
+            0x7fc0_0000 => SeccompAction::UserNotif,
+            0x7ff0_0000 => SeccompAction::Trace(data),
+            0x7ffc_0000 => SeccompAction::Log,
+            0x7fff_0000 => SeccompAction::Allow,
+            _ => SeccompAction::Unknown(code),
+        }
+    }
+}
+
+/// View on `SECCOMP` records, written when a seccomp filter kills a
+/// process or logs a syscall
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeccompRecord<'a> {
+    /// Identifier of the record
+    pub id: EventID,
+    pub pid: Option<i64>,
+    /// Command name
+    pub comm: Option<&'a [u8]>,
+    /// Path of the executable
+    pub exe: Option<&'a [u8]>,
+    /// Signal sent to the process, 0 if none
+    pub sig: Option<i64>,
+    /// Architecture (`AUDIT_ARCH_*` value)
+    pub arch: Option<u64>,
+    /// Syscall number
+    pub syscall: Option<i64>,
+    /// Syscall name, from the enriched `SYSCALL` field
+    pub syscall_name: Option<&'a [u8]>,
+    /// Set if the syscall was made through a compat (32-bit) entry
+    /// point
+    pub compat: Option<bool>,
+    /// Instruction pointer
+    pub ip: Option<u64>,
+    /// Return value of the filter, see [`SeccompRecord::action`]
+    pub code: Option<u32>,
+}
+
+impl<'a> SeccompRecord<'a> {
+    /// Builds a view on `msg`, returns `None` if `msg` is not a
+    /// `SECCOMP` record.
+    pub fn from_message(msg: &'a Message) -> Option<Self> {
+        if msg.ty != MessageType::SECCOMP {
+            return None;
+        }
+        let body = &msg.body;
+        let num = |k: &str| match body.get(k) {
+            Some(Value::Number(n)) => n.as_i64(),
+            _ => None,
+        };
+        Some(SeccompRecord {
+            id: msg.id,
+            pid: num("pid"),
+            comm: body.get("comm").and_then(value_bytes),
+            exe: body.get("exe").and_then(value_bytes),
+            sig: num("sig"),
+            arch: body.get("arch").and_then(Value::to_u64),
+            syscall: num("syscall"),
+            syscall_name: body.get("SYSCALL").and_then(value_bytes),
+            compat: num("compat").map(|n| n != 0),
+            ip: body.get("ip").and_then(Value::to_u64),
+            code: body
+                .get("code")
+                .and_then(Value::to_u64)
+                .and_then(|n| u32::try_from(n).ok()),
+        })
+    }
+
+    /// Returns the decoded `arch` field.
+    pub fn arch_info(&self) -> Option<ArchInfo> {
+        self.arch
+            .and_then(|a| u32::try_from(a).ok())
+            .map(ArchInfo::from_audit_arch)
+    }
+
+    /// Returns the decoded `code` field.
+    pub fn action(&self) -> Option<SeccompAction> {
+        self.code.map(SeccompAction::from)
+    }
+
+    /// Returns `true` if the filter killed the thread or the process.
+    pub fn is_kill(&self) -> bool {
+        matches!(
+            self.action(),
+            Some(SeccompAction::KillProcess | SeccompAction::KillThread)
+        )
+    }
+
+    /// Returns `true` if the filter made the syscall fail with an
+    /// error number.
+    pub fn is_errno(&self) -> bool {
+        matches!(self.action(), Some(SeccompAction::Errno(_)))
+    }
+
+    /// Returns the symbolic name of the error number returned by the
+    /// filter, e.g. `EPERM`.
+    pub fn errno_name(&self) -> Option<&'static str> {
+        match self.action()? {
+            SeccompAction::Errno(n) => errno_name(n.into()),
+            _ => None,
+        }
+    }
+}
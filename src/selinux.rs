@@ -0,0 +1,83 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// An SELinux security context, e.g.
+/// `system_u:system_r:auditd_t:s0`, as found in `subj`, `scontext`,
+/// `tcontext`, `obj` fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SELinuxContext<'a> {
+    user: &'a [u8],
+    role: &'a [u8],
+    ty: &'a [u8],
+    level: Option<&'a [u8]>,
+}
+
+impl<'a> SELinuxContext<'a> {
+    /// Splits `s` into its components. Returns `None` if `s` does not
+    /// contain at least user, role, and type.
+    pub fn parse(s: &'a [u8]) -> Option<Self> {
+        let mut parts = s.splitn(4, |c| *c == b':');
+        let user = parts.next().filter(|p| !p.is_empty())?;
+        let role = parts.next().filter(|p| !p.is_empty())?;
+        let ty = parts.next().filter(|p| !p.is_empty())?;
+        let level = parts.next();
+        if [user, role, ty].iter().any(|p| p.contains(&b' ')) {
+            return None;
+        }
+        Some(SELinuxContext {
+            user,
+            role,
+            ty,
+            level,
+        })
+    }
+
+    /// The SELinux user, e.g. `system_u`
+    pub fn user(&self) -> &'a [u8] {
+        self.user
+    }
+
+    /// The role, e.g. `system_r`
+    pub fn role(&self) -> &'a [u8] {
+        self.role
+    }
+
+    /// The type or domain, e.g. `auditd_t`
+    pub fn ty(&self) -> &'a [u8] {
+        self.ty
+    }
+
+    /// The MLS/MCS level or range, e.g. `s0-s0:c0.c1023`
+    pub fn level(&self) -> Option<&'a [u8]> {
+        self.level
+    }
+}
+
+impl Display for SELinuxContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            String::from_utf8_lossy(self.user),
+            String::from_utf8_lossy(self.role),
+            String::from_utf8_lossy(self.ty)
+        )?;
+        if let Some(level) = self.level {
+            write!(f, ":{}", String::from_utf8_lossy(level))?;
+        }
+        Ok(())
+    }
+}
+
+impl Value<'_> {
+    /// Interprets a string value as SELinux context, see
+    /// [`SELinuxContext::parse`].
+    pub fn as_selinux_context(&self) -> Option<SELinuxContext<'_>> {
+        match self {
+            Value::Str(s, _) => SELinuxContext::parse(s),
+            Value::Owned(s) => SELinuxContext::parse(s),
+            _ => None,
+        }
+    }
+}
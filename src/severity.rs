@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::*;
+
+/// Severity level of an audit record, e.g. for SIEM routing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+impl FromStr for Severity {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "critical" => Ok(Severity::Critical),
+            _ => Err("unknown severity"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Severity {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+/// Returns the default severity for records of type `ty`.
+///
+/// - [`Critical`](Severity::Critical): anomalies detected by the
+///   kernel or by userspace (`ANOM_*`), responses to anomalies
+///   (`RESP_*`), changes to the MAC policy or its enforcement
+///   status (`MAC_POLICY_LOAD`, `MAC_STATUS`, …), processes killed
+///   by AppArmor, and _auditd(8)_ failures (`DAEMON_ABORT`,
+///   `DAEMON_ERR`).
+/// - [`Warning`](Severity::Warning): access denials (`AVC`,
+///   `USER_AVC`, `APPARMOR_DENIED`, …), changes to the audit or MAC
+///   configuration, kernel module loading, seccomp actions,
+///   integrity measurement, account and role management, and
+///   failed cryptographic operations.
+/// - [`Info`](Severity::Info): everything else.
+pub fn severity(ty: MessageType) -> Severity {
+    use MessageType as T;
+    match ty {
+        T::MAC_POLICY_LOAD
+        | T::MAC_STATUS
+        | T::USER_MAC_POLICY_LOAD
+        | T::USER_MAC_STATUS
+        | T::APPARMOR_KILL
+        | T::DAEMON_ABORT
+        | T::DAEMON_ERR => Severity::Critical,
+        T(1700..=1799) | T(2100..=2299) => Severity::Critical,
+        T::AVC
+        | T::USER_AVC
+        | T::SELINUX_ERR
+        | T::USER_SELINUX_ERR
+        | T::APPARMOR_DENIED
+        | T::APPARMOR_ERROR
+        | T::CONFIG_CHANGE
+        | T::DAEMON_CONFIG
+        | T::USYS_CONFIG
+        | T::FEATURE_CHANGE
+        | T::KERN_MODULE
+        | T::SECCOMP
+        | T::MAC_CONFIG_CHANGE
+        | T::USER_MAC_CONFIG_CHANGE
+        | T::LABEL_OVERRIDE
+        | T::ADD_USER
+        | T::DEL_USER
+        | T::ADD_GROUP
+        | T::DEL_GROUP
+        | T::ACCT_LOCK
+        | T::ROLE_ASSIGN
+        | T::ROLE_REMOVE
+        | T::ROLE_MODIFY
+        | T::CRYPTO_FAILURE_USER
+        | T::CRYPTO_REPLAY_USER
+        | T::VIRT_INTEGRITY_CHECK => Severity::Warning,
+        T(1800..=1899) => Severity::Warning,
+        _ => Severity::Info,
+    }
+}
+
+/// Configurable mapping from [`MessageType`] to [`Severity`]
+///
+/// Types without an explicit entry are mapped using [`severity`].
+#[derive(Debug, Clone, Default)]
+pub struct SeverityMap {
+    overrides: HashMap<MessageType, Severity>,
+}
+
+impl SeverityMap {
+    /// Constructs a mapping that uses the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the severity for records of type `ty`.
+    pub fn set(&mut self, ty: MessageType, severity: Severity) -> &mut Self {
+        self.overrides.insert(ty, severity);
+        self
+    }
+
+    /// Overrides the severity for all records in `types`, e.g. for
+    /// a class of message types.
+    pub fn set_range(
+        &mut self,
+        types: std::ops::RangeInclusive<u32>,
+        severity: Severity,
+    ) -> &mut Self {
+        for ty in types {
+            self.overrides.insert(MessageType(ty), severity);
+        }
+        self
+    }
+
+    /// Returns the severity for records of type `ty`.
+    pub fn get(&self, ty: MessageType) -> Severity {
+        self.overrides
+            .get(&ty)
+            .copied()
+            .unwrap_or_else(|| severity(ty))
+    }
+}
+
+impl Message<'_> {
+    /// Returns the severity of the message according to `map`.
+    pub fn severity(&self, map: &SeverityMap) -> Severity {
+        map.get(self.ty)
+    }
+
+    /// Returns a wrapper that serializes the message body with an
+    /// additional `severity` entry, as determined by `map`.
+    #[cfg(feature = "serde")]
+    pub fn with_severity(&self, map: &SeverityMap) -> WithSeverity<'_> {
+        WithSeverity {
+            body: &self.body,
+            severity: self.severity(map),
+        }
+    }
+}
+
+/// [`Body`] wrapper that adds a `severity` entry during
+/// serialization, see [`Message::with_severity`]
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy)]
+pub struct WithSeverity<'a> {
+    pub body: &'a Body<'a>,
+    pub severity: Severity,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithSeverity<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.body.len() + 1))?;
+        for (k, v) in self.body {
+            map.serialize_entry(k, v)?;
+        }
+        map.serialize_entry("severity", &self.severity)?;
+        map.end()
+    }
+}
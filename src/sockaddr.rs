@@ -0,0 +1,177 @@
+use std::fmt::{self, Display};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use crate::*;
+
+const AF_LOCAL: u16 = 1;
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+const AF_NETLINK: u16 = 16;
+
+/// Socket address decoded from the `saddr` field of `SOCKADDR`
+/// records
+///
+/// `saddr` contains the `struct sockaddr` passed to system calls such
+/// as _connect(2)_ or _bind(2)_. The address family and some fields
+/// are stored in the byte order of the audited system; it is detected
+/// from the family, so that logs from big-endian systems are decoded
+/// correctly on little-endian systems and vice versa.
+///
+/// The [`Display`] implementation mirrors the `SADDR` field of
+/// enriched logs and the output of `ausearch -i`.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// let msg = parse(&b"type=SOCKADDR msg=audit(1.000:1): saddr=02000016C0A800010000000000000000\n"[..], false).unwrap();
+/// let addr = SockAddr::from_message(&msg).unwrap();
+/// assert_eq!(addr.socket_addr(), Some("192.168.0.1:22".parse().unwrap()));
+/// assert_eq!(addr.to_string(), "fam=inet laddr=192.168.0.1 lport=22");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SockAddr {
+    /// `AF_INET`
+    Inet(SocketAddrV4),
+    /// `AF_INET6`
+    Inet6(SocketAddrV6),
+    /// `AF_LOCAL`, a.k.a. `AF_UNIX`. The path is empty for unnamed
+    /// sockets.
+    Local {
+        path: Vec<u8>,
+        /// The path is in the abstract namespace, i.e. it started
+        /// with a NUL byte, which has been removed.
+        is_abstract: bool,
+    },
+    /// `AF_NETLINK`
+    Netlink { pid: u32, groups: u32 },
+    /// Any other family, including `AF_UNSPEC`
+    Other { family: u16, data: Vec<u8> },
+}
+
+impl SockAddr {
+    /// Decodes a `struct sockaddr`. Returns `None` if `b` is shorter
+    /// than the structure for its family.
+    pub fn parse(b: &[u8]) -> Option<Self> {
+        let family = b.get(..2)?;
+        // Families are small numbers, so the byte that is zero
+        // reveals the byte order.
+        let big_endian = family[0] == 0 && family[1] != 0;
+        let u16_at = |i: usize| -> Option<u16> {
+            let n = b.get(i..i + 2)?.try_into().ok()?;
+            Some(if big_endian {
+                u16::from_be_bytes(n)
+            } else {
+                u16::from_le_bytes(n)
+            })
+        };
+        let u32_at = |i: usize| -> Option<u32> {
+            let n = b.get(i..i + 4)?.try_into().ok()?;
+            Some(if big_endian {
+                u32::from_be_bytes(n)
+            } else {
+                u32::from_le_bytes(n)
+            })
+        };
+        // Ports and addresses are in network byte order.
+        let port = || Some(u16::from_be_bytes(b.get(2..4)?.try_into().ok()?));
+        Some(match u16_at(0)? {
+            AF_INET => {
+                let ip: [u8; 4] = b.get(4..8)?.try_into().ok()?;
+                SockAddr::Inet(SocketAddrV4::new(Ipv4Addr::from(ip), port()?))
+            }
+            AF_INET6 => {
+                let flowinfo = u32::from_be_bytes(b.get(4..8)?.try_into().ok()?);
+                let ip: [u8; 16] = b.get(8..24)?.try_into().ok()?;
+                let scope_id = u32_at(24).unwrap_or(0);
+                SockAddr::Inet6(SocketAddrV6::new(
+                    Ipv6Addr::from(ip),
+                    port()?,
+                    flowinfo,
+                    scope_id,
+                ))
+            }
+            AF_LOCAL => {
+                let path = &b[2..];
+                let (path, is_abstract) = match path.split_first() {
+                    Some((0, rest)) if !rest.is_empty() => (rest, true),
+                    _ => (path, false),
+                };
+                let path = match is_abstract {
+                    // Abstract names are not NUL-terminated.
+                    true => path
+                        .iter()
+                        .rposition(|c| *c != 0)
+                        .map_or(&[][..], |n| &path[..=n]),
+                    false => path.split(|c| *c == 0).next().unwrap_or_default(),
+                };
+                SockAddr::Local {
+                    path: path.to_vec(),
+                    is_abstract,
+                }
+            }
+            AF_NETLINK => SockAddr::Netlink {
+                pid: u32_at(4)?,
+                groups: u32_at(8)?,
+            },
+            family => SockAddr::Other {
+                family,
+                data: b[2..].to_vec(),
+            },
+        })
+    }
+
+    /// Builds a view on the `saddr` field of `msg`, returns `None` if
+    /// `msg` is not a `SOCKADDR` record or the field cannot be
+    /// decoded.
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if msg.ty != MessageType::SOCKADDR {
+            return None;
+        }
+        msg.body.get("saddr")?.try_into().ok()
+    }
+
+    /// Returns the address family, e.g. 2 for `AF_INET`.
+    pub fn family(&self) -> u16 {
+        match self {
+            SockAddr::Inet(_) => AF_INET,
+            SockAddr::Inet6(_) => AF_INET6,
+            SockAddr::Local { .. } => AF_LOCAL,
+            SockAddr::Netlink { .. } => AF_NETLINK,
+            SockAddr::Other { family, .. } => *family,
+        }
+    }
+
+    /// Returns IP address and port for the `AF_INET` and `AF_INET6`
+    /// families.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            SockAddr::Inet(a) => Some(SocketAddr::V4(*a)),
+            SockAddr::Inet6(a) => Some(SocketAddr::V6(*a)),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&Value<'_>> for SockAddr {
+    type Error = &'static str;
+    fn try_from(v: &Value) -> Result<Self, Self::Error> {
+        let b = v.as_bytes().ok_or("not a string")?;
+        SockAddr::parse(&b).ok_or("truncated socket address")
+    }
+}
+
+impl Display for SockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SockAddr::Inet(a) => write!(f, "fam=inet laddr={} lport={}", a.ip(), a.port()),
+            SockAddr::Inet6(a) => write!(f, "fam=inet6 laddr={} lport={}", a.ip(), a.port()),
+            SockAddr::Local { path, is_abstract } => {
+                let at = if *is_abstract { "@" } else { "" };
+                write!(f, "fam=local path={at}{}", String::from_utf8_lossy(path))
+            }
+            SockAddr::Netlink { pid, .. } => {
+                write!(f, "fam=netlink nlnk-fam={AF_NETLINK} nlnk-pid={pid}")
+            }
+            SockAddr::Other { family, .. } => write!(f, "unknown-family({family})"),
+        }
+    }
+}
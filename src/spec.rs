@@ -0,0 +1,36 @@
+use crate::constants::*;
+
+/// Provenance of the message type and field tables that have been
+/// compiled into the crate, see [`spec_info`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecInfo {
+    /// See [`spec_version`]
+    pub version: &'static str,
+    /// FNV-1a hash of the message and field dictionaries, as 16 hex
+    /// digits. Builds with identical tables have identical digests.
+    pub digest: &'static str,
+    /// Number of known message types
+    pub message_types: usize,
+    /// Number of known field names
+    pub fields: usize,
+}
+
+/// Returns the version of the _audit-specs_ tables the crate was
+/// built with: the commit ID if `src/audit-specs` was a git checkout
+/// at build time, the contents of `src/audit-specs/VERSION` if
+/// present, `"unknown"` otherwise.
+pub fn spec_version() -> &'static str {
+    SPEC_VERSION
+}
+
+/// Returns version, digest, and size of the _audit-specs_ tables
+/// the crate was built with, e.g. to verify which dictionary a
+/// deployed agent uses when investigating unknown message types.
+pub fn spec_info() -> SpecInfo {
+    SpecInfo {
+        version: SPEC_VERSION,
+        digest: SPEC_DIGEST,
+        message_types: SPEC_MESSAGE_TYPES,
+        fields: SPEC_FIELDS,
+    }
+}
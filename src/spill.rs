@@ -0,0 +1,352 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use prost::Message as _;
+
+use crate::coalescer::EventKey;
+use crate::*;
+
+const SEGMENT_SUFFIX: &str = ".seg";
+
+/// On-disk buffer for the records of incomplete events, see
+/// [`EventCoalescer::spill`]
+///
+/// Each event is stored in a segment file in the store's directory,
+/// named after its [`EventID`] and node name. Segment files consist
+/// of frames, one per record: a 32-bit little-endian length followed
+/// by the record, encoded as [`proto::Record`]. Reading a segment
+/// fails with [`io::ErrorKind::InvalidData`] if a frame has been cut
+/// off, e.g. by a crash while writing, or cannot be decoded.
+///
+/// Segments are removed once their event has been read back, so that
+/// the segments that are present when a store is opened belong to
+/// events that were incomplete when the previous process ended. They
+/// are picked up by [`EventCoalescer::spill`]; see [`Checkpoint`] for
+/// where to resume reading the input.
+#[derive(Debug, Clone)]
+pub struct SpillStore {
+    dir: PathBuf,
+}
+
+fn hex(s: &[u8]) -> String {
+    s.iter().map(|c| format!("{c:02x}")).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    let chunks = s.as_bytes().chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    chunks
+        .map(|c| u8::from_str_radix(std::str::from_utf8(c).ok()?, 16).ok())
+        .collect()
+}
+
+impl SpillStore {
+    /// Opens the store in `dir`, which is created if necessary.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(SpillStore { dir })
+    }
+
+    fn file_name(node: &Option<Vec<u8>>, id: EventID) -> String {
+        match node {
+            Some(node) => format!("{}_{}{SEGMENT_SUFFIX}", id.to_compact_string(), hex(node)),
+            None => format!("{}{SEGMENT_SUFFIX}", id.to_compact_string()),
+        }
+    }
+
+    fn path(&self, node: &Option<Vec<u8>>, id: EventID) -> PathBuf {
+        self.dir.join(Self::file_name(node, id))
+    }
+
+    fn parse_name(name: &str) -> Option<EventKey> {
+        let name = name.strip_suffix(SEGMENT_SUFFIX)?;
+        let (id, node) = match name.split_once('_') {
+            Some((id, node)) => (id, Some(unhex(node)?)),
+            None => (name, None),
+        };
+        Some((node, EventID::from_compact_str(id).ok()?))
+    }
+
+    /// Returns node names and identifiers of all events in the
+    /// store, ordered by identifier.
+    pub fn events(&self) -> io::Result<Vec<EventKey>> {
+        let mut keys = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            if let Some(key) = entry?.file_name().to_str().and_then(Self::parse_name) {
+                keys.push(key);
+            }
+        }
+        keys.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+        Ok(keys)
+    }
+
+    /// Appends a record to the segment of event `id`.
+    pub fn append(
+        &self,
+        node: &Option<Vec<u8>>,
+        id: EventID,
+        ty: MessageType,
+        body: &Body,
+    ) -> io::Result<()> {
+        let mut buf = vec![];
+        encode_frame(&mut buf, ty, body)?;
+        self.write_frames(node, id, &buf)
+    }
+
+    /// Appends all records of `ev`.
+    pub fn append_event(&self, ev: &Event) -> io::Result<()> {
+        let mut buf = vec![];
+        for (ty, body) in &ev.records {
+            encode_frame(&mut buf, *ty, body)?;
+        }
+        self.write_frames(&ev.node, ev.id, &buf)
+    }
+
+    /// Appends `frames` to a segment. If writing fails, the segment
+    /// is truncated to its previous length, so that records stored
+    /// before are kept.
+    fn write_frames(&self, node: &Option<Vec<u8>>, id: EventID, frames: &[u8]) -> io::Result<()> {
+        let path = self.path(node, id);
+        let mut f = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = f.metadata()?.len();
+        if let Err(e) = f.write_all(frames) {
+            let _ = match len {
+                0 => fs::remove_file(&path),
+                len => f.set_len(len),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Reads the records of event `id` without removing them. Returns
+    /// `None` if the store does not contain the event.
+    pub fn read<'a>(&self, node: &Option<Vec<u8>>, id: EventID) -> io::Result<Option<Event<'a>>> {
+        let mut buf = vec![];
+        match File::open(self.path(node, id)) {
+            Ok(mut f) => f.read_to_end(&mut buf)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let corrupt = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("corrupt segment {}", Self::file_name(node, id)),
+            )
+        };
+        let mut ev = Event::new(id, node.clone());
+        let mut input = &buf[..];
+        while !input.is_empty() {
+            // Records are never empty, so a frame of length 0 is
+            // taken for zeroes left over by a crash.
+            let (len, rest) = take_u32(input).ok_or_else(corrupt)?;
+            if len == 0 || rest.len() < len as usize {
+                return Err(corrupt());
+            }
+            let (frame, rest) = rest.split_at(len as usize);
+            ev.records.push(decode_record(frame).ok_or_else(corrupt)?);
+            input = rest;
+        }
+        Ok(Some(ev))
+    }
+
+    /// Removes event `id` from the store.
+    pub fn remove(&self, node: &Option<Vec<u8>>, id: EventID) -> io::Result<()> {
+        match fs::remove_file(self.path(node, id)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            r => r,
+        }
+    }
+
+    /// Flushes the segments of `keys` to disk and returns their file
+    /// names and lengths. The directory is synced as well, so that
+    /// segments that have been created or removed before are
+    /// persisted.
+    pub(crate) fn sync_segments<'k>(
+        &self,
+        keys: impl IntoIterator<Item = &'k EventKey>,
+    ) -> io::Result<Vec<(String, u64)>> {
+        let mut segments = vec![];
+        for (node, id) in keys {
+            let f = File::open(self.path(node, *id))?;
+            f.sync_data()?;
+            segments.push((Self::file_name(node, *id), f.metadata()?.len()));
+        }
+        File::open(&self.dir)?.sync_all()?;
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Brings segments back to the state of `checkpoint`: segments
+    /// are truncated to their length at the checkpoint, segments
+    /// created later are removed. Fails if a segment of the
+    /// checkpoint is missing or shorter than recorded.
+    pub(crate) fn restore(&self, checkpoint: &Checkpoint) -> io::Result<()> {
+        for (name, len) in &checkpoint.segments {
+            let actual = match fs::metadata(self.dir.join(name)) {
+                Ok(m) => m.len(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+                Err(e) => return Err(e),
+            };
+            if actual < *len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("segment {name} is shorter than at checkpoint"),
+                ));
+            }
+        }
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                continue;
+            };
+            if Self::parse_name(&name).is_none() {
+                continue;
+            }
+            match checkpoint.segments.binary_search_by(|(n, _)| n.cmp(&name)) {
+                Ok(i) => OpenOptions::new()
+                    .write(true)
+                    .open(entry.path())?
+                    .set_len(checkpoint.segments[i].1)?,
+                Err(_) => fs::remove_file(entry.path())?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads and removes event `id`.
+    pub fn take<'a>(&self, node: &Option<Vec<u8>>, id: EventID) -> io::Result<Option<Event<'a>>> {
+        let ev = self.read(node, id)?;
+        self.remove(node, id)?;
+        Ok(ev)
+    }
+}
+
+/// Appends a frame holding the record, encoded as [`proto::Record`].
+fn encode_frame(buf: &mut Vec<u8>, ty: MessageType, body: &Body) -> io::Result<()> {
+    let rec = proto::Record {
+        r#type: ty.0,
+        body: body
+            .to_owned_pairs()
+            .into_iter()
+            .map(proto::Entry::from)
+            .collect(),
+    };
+    let len = u32::try_from(rec.encoded_len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+    buf.extend(len.to_le_bytes());
+    rec.encode(buf).map_err(io::Error::other)
+}
+
+fn take_u32(input: &[u8]) -> Option<(u32, &[u8])> {
+    if input.len() < 4 {
+        return None;
+    }
+    let (n, rest) = input.split_at(4);
+    Some((u32::from_le_bytes(n.try_into().unwrap()), rest))
+}
+
+fn decode_record(frame: &[u8]) -> Option<(MessageType, Body<'static>)> {
+    let rec = proto::Record::decode(frame).ok()?;
+    let ty = MessageType(rec.r#type);
+    Some((
+        ty,
+        Body::from_owned_pairs(ty, rec.body.into_iter().map(proto::Entry::into)),
+    ))
+}
+
+/// Position in the input up to which all messages have been handled
+/// by an [`EventCoalescer`], along with the state of its
+/// [`SpillStore`], see [`EventCoalescer::checkpoint`]
+///
+/// After a restart, the store is brought back to the state of the
+/// checkpoint by [`EventCoalescer::resume`] and reading the input
+/// resumes at `offset`. Events that were incomplete at the checkpoint
+/// are then completed from the store.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// # let dir = std::env::temp_dir().join(format!("checkpoint-doc-{}", std::process::id()));
+/// # let _ = std::fs::remove_dir_all(&dir);
+/// let parser = Parser::default();
+/// let store = SpillStore::open(dir.join("spill")).unwrap();
+/// let mut c = EventCoalescer::new().spill(store, 100).unwrap();
+/// let line = b"type=SYSCALL msg=audit(1.000:10): syscall=59\n";
+/// c.push_line(&parser, line).unwrap();
+/// c.checkpoint(line.len() as u64).unwrap().save(dir.join("checkpoint")).unwrap();
+///
+/// // after a restart
+/// let cp = Checkpoint::load(dir.join("checkpoint")).unwrap().unwrap();
+/// assert_eq!(cp.offset, line.len() as u64);
+/// let store = SpillStore::open(dir.join("spill")).unwrap();
+/// let mut c = EventCoalescer::new().resume(store, 100, &cp).unwrap();
+/// let ev = c.push_line(&parser, b"type=EOE msg=audit(1.000:10): \n").unwrap().unwrap();
+/// assert_eq!(ev.records[0].0, MessageType::SYSCALL);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Checkpoint {
+    /// Offset in the input at which reading resumes
+    pub offset: u64,
+    /// Identifier of the last message before `offset`, e.g. to
+    /// check that the input has not been replaced
+    pub last: Option<EventID>,
+    /// File names and lengths of the segments in the store, sorted
+    pub(crate) segments: Vec<(String, u64)>,
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to `path`. The file is replaced
+    /// atomically, so that a crash leaves either the previous or the
+    /// new checkpoint.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let last = match self.last {
+            Some(id) => id.to_compact_string(),
+            None => "-".into(),
+        };
+        let mut buf = format!("{} {last}\n", self.offset);
+        for (name, len) in &self.segments {
+            buf += &format!("{name} {len}\n");
+        }
+        let mut f = File::create(&tmp)?;
+        f.write_all(buf.as_bytes())?;
+        f.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Reads a checkpoint written by [`save`](Self::save). Returns
+    /// `None` if `path` does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid checkpoint");
+        let mut lines = s.lines().map(|l| l.split_once(' ').ok_or_else(invalid));
+        let (offset, last) = lines.next().ok_or_else(invalid)??;
+        let offset = offset.parse().map_err(|_| invalid())?;
+        let last = match last {
+            "-" => None,
+            id => Some(EventID::from_compact_str(id).map_err(|_| invalid())?),
+        };
+        let mut segments = vec![];
+        for line in lines {
+            let (name, len) = line?;
+            segments.push((name.to_string(), len.parse().map_err(|_| invalid())?));
+        }
+        segments.sort();
+        Ok(Some(Checkpoint {
+            offset,
+            last,
+            segments,
+        }))
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// Separator between multiple keys in the `key` field of a
+/// `SYSCALL` record (`AUDIT_KEY_SEPARATOR`)
+pub const KEY_SEPARATOR: u8 = 0x01;
+
+/// Error number, e.g. from a failed syscall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Errno(pub i64);
+
+impl Errno {
+    /// Returns the symbolic name, e.g. `EACCES`, see [`errno_name`].
+    pub fn name(&self) -> Option<&'static str> {
+        errno_name(self.0)
+    }
+}
+
+/// Uses the symbolic name if known, the number otherwise.
+impl Display for Errno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Result of a syscall, see [`SyscallRecord::outcome`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The syscall succeeded and returned the value
+    Success(i64),
+    /// The syscall failed with the error number
+    Failure(Errno),
+}
+
+impl Outcome {
+    /// Returns `true` for [`Outcome::Success`].
+    pub fn is_success(&self) -> bool {
+        matches!(self, Outcome::Success(_))
+    }
+}
+
+/// View on `SYSCALL` records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyscallRecord<'a> {
+    /// Identifier of the record
+    pub id: EventID,
+    /// Architecture (`AUDIT_ARCH_*` value)
+    pub arch: Option<u64>,
+    /// Syscall number
+    pub syscall: Option<i64>,
+    /// `success=yes` / `success=no`
+    pub success: Option<bool>,
+    /// Return value of the syscall
+    pub exit: Option<i64>,
+    /// Syscall arguments, see [`Body::args`]
+    pub args: [Option<u64>; 4],
+    /// Number of `PATH` records belonging to the event
+    pub items: Option<i64>,
+    pub pid: Option<i64>,
+    pub ppid: Option<i64>,
+    /// Login session ID
+    pub ses: Option<SessionId>,
+    /// Command name
+    pub comm: Option<&'a [u8]>,
+    /// Path of the executable
+    pub exe: Option<&'a [u8]>,
+    /// Audit rule key(s), hex-decoded; `None` for `key=(null)`. See
+    /// [`SyscallRecord::rule_keys`].
+    pub key: Option<&'a [u8]>,
+}
+
+impl<'a> SyscallRecord<'a> {
+    /// Builds a view on `msg`, returns `None` if `msg` is not a
+    /// `SYSCALL` record.
+    pub fn from_message(msg: &'a Message) -> Option<Self> {
+        if msg.ty != MessageType::SYSCALL {
+            return None;
+        }
+        let body = &msg.body;
+        let num = |k: &str| match body.get(k) {
+            Some(Value::Number(n)) => n.as_i64(),
+            _ => None,
+        };
+        Some(SyscallRecord {
+            id: msg.id,
+            arch: body.get("arch").and_then(Value::to_u64),
+            syscall: num("syscall"),
+            success: match body.get("success").and_then(value_bytes) {
+                Some(b"yes") => Some(true),
+                Some(b"no") => Some(false),
+                _ => None,
+            },
+            exit: num("exit"),
+            args: body.args(),
+            items: num("items"),
+            pid: num("pid"),
+            ppid: num("ppid"),
+            ses: body.get("ses").and_then(SessionId::from_value),
+            comm: body.get("comm").and_then(value_bytes),
+            exe: body.get("exe").and_then(value_bytes),
+            key: body
+                .get("key")
+                .and_then(value_bytes)
+                .filter(|k| !k.is_empty()),
+        })
+    }
+
+    /// Returns the result of the syscall, combining `success` and
+    /// `exit`. Without `success`, return values from -4095 to -1 are
+    /// taken as error numbers, as the kernel does. `None` is returned
+    /// if both fields are missing, or if a failure is reported
+    /// without a negative return value.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match (self.success, self.exit) {
+            (Some(true), exit) => Some(Outcome::Success(exit.unwrap_or(0))),
+            (Some(false), Some(exit)) if exit < 0 => Some(Outcome::Failure(Errno(-exit))),
+            (Some(false), _) => None,
+            (None, Some(exit @ -4095..=-1)) => Some(Outcome::Failure(Errno(-exit))),
+            (None, Some(exit)) => Some(Outcome::Success(exit)),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the keys of the audit rules that matched. If several
+    /// rules matched, their keys are separated by [`KEY_SEPARATOR`]
+    /// in the `key` field.
+    pub fn rule_keys(&self) -> Vec<&'a [u8]> {
+        match self.key {
+            Some(key) => key
+                .split(|c| *c == KEY_SEPARATOR)
+                .filter(|k| !k.is_empty())
+                .collect(),
+            None => vec![],
+        }
+    }
+}
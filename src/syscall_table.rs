@@ -0,0 +1,109 @@
+use crate::*;
+
+mod tables {
+    include!(concat!(env!("OUT_DIR"), "/syscall_tables.rs"));
+}
+
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+const EM_RISCV: u16 = 243;
+const EM_LOONGARCH: u16 = 258;
+
+/// Returns the name of syscall number `syscall` on `arch`, e.g.
+/// `execve` for 59 on x86_64, as _ausearch(8)_ and enriched logs show
+/// it. Tables are included for x86_64, i386, and the architectures
+/// that use the generic syscall table: aarch64, riscv64, and
+/// loongarch64.
+///
+/// ```
+/// # use linux_audit_parser::*;
+/// assert_eq!(syscall_name(ArchInfo::from(0xc000003e), 59), Some("execve"));
+/// assert_eq!(syscall_name(ArchInfo::from(0x40000003), 11), Some("execve"));
+/// assert_eq!(syscall_name(ArchInfo::from(0xc00000b7), 221), Some("execve"));
+/// ```
+pub fn syscall_name(arch: ArchInfo, syscall: i64) -> Option<&'static str> {
+    let table = match (arch.machine, arch.is_64bit) {
+        (EM_X86_64, true) => tables::X86_64,
+        (EM_386, false) => tables::I386,
+        (EM_AARCH64 | EM_RISCV | EM_LOONGARCH, true) => tables::GENERIC,
+        _ => return None,
+    };
+    let (_, syscall) = arch.syscall_abi(syscall);
+    table
+        .binary_search_by_key(&syscall, |(n, _)| *n)
+        .ok()
+        .map(|i| table[i].1)
+}
+
+impl ArchInfo {
+    /// Returns the name of syscall number `syscall`, see
+    /// [`syscall_name`].
+    pub fn syscall_name(&self, syscall: i64) -> Option<&'static str> {
+        syscall_name(*self, syscall)
+    }
+}
+
+impl SyscallRecord<'_> {
+    /// Returns the name of the syscall, see [`syscall_name`].
+    pub fn syscall_name(&self) -> Option<&'static str> {
+        self.arch_info()?.syscall_name(self.syscall?)
+    }
+}
+
+/// Architecture and syscall name of a record that has `arch` and
+/// `syscall` fields
+fn resolve(body: &Body) -> Option<(ArchInfo, &'static str)> {
+    let arch = ArchInfo::from(u32::try_from(body.get("arch")?.to_u64()?).ok()?);
+    let syscall = match body.get("syscall")? {
+        Value::Number(n) => n.as_i64()?,
+        _ => return None,
+    };
+    Some((arch, arch.syscall_name(syscall)?))
+}
+
+impl Message<'_> {
+    /// Adds the `ARCH` and `SYSCALL` fields that _auditd(8)_ writes
+    /// with `log_format=ENRICHED`, translated from the `arch` and
+    /// `syscall` fields, unless they are already present. Returns
+    /// `true` if the fields have been added.
+    ///
+    /// ```
+    /// # use linux_audit_parser::*;
+    /// let mut msg = parse(&b"type=SYSCALL msg=audit(1.000:1): arch=c000003e syscall=59 success=yes exit=0\n"[..], false).unwrap();
+    /// assert!(msg.resolve_syscall());
+    /// assert_eq!(&*msg.body.get("SYSCALL").unwrap().as_bytes().unwrap(), b"execve");
+    /// ```
+    pub fn resolve_syscall(&mut self) -> bool {
+        if self.body.get("ARCH").is_some() || self.body.get("SYSCALL").is_some() {
+            return false;
+        }
+        let Some((arch, name)) = resolve(&self.body) else {
+            return false;
+        };
+        self.body
+            .push_str(&b"ARCH"[..], arch.to_string().as_bytes());
+        self.body.push_str(&b"SYSCALL"[..], name.as_bytes());
+        true
+    }
+}
+
+/// `syscall` fields, e.g. in `SYSCALL` and `SECCOMP` records: syscall
+/// names for the record's `arch`, see [`syscall_name`]. Only
+/// available with [`Interpreters::apply_body`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyscallInterpreter;
+
+impl Interpreter for SyscallInterpreter {
+    fn name(&self) -> &str {
+        "syscall"
+    }
+
+    fn interpret(&self, ctx: &InterpretContext, key: &Key, _: &Value) -> Option<Interpreted> {
+        if key != "syscall" {
+            return None;
+        }
+        let (_, name) = resolve(ctx.body?)?;
+        Some(Interpreted::Name(name.into()))
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::*;
+
+/// Items that can be ordered by [`TimelineMerger`]
+pub trait Timestamped {
+    fn id(&self) -> EventID;
+    fn node(&self) -> Option<&[u8]>;
+}
+
+impl Timestamped for Message<'_> {
+    fn id(&self) -> EventID {
+        self.id
+    }
+    fn node(&self) -> Option<&[u8]> {
+        self.node.as_deref()
+    }
+}
+
+impl Timestamped for Event<'_> {
+    fn id(&self) -> EventID {
+        self.id
+    }
+    fn node(&self) -> Option<&[u8]> {
+        self.node.as_deref()
+    }
+}
+
+/// An item that arrived too late to be put in order, see
+/// [`TimelineMerger::push`]
+#[derive(Debug, Clone)]
+pub struct OutOfWindow<T> {
+    pub item: T,
+    /// Milliseconds between the item's timestamp and the timestamp
+    /// of the most recently released item
+    pub behind_ms: u64,
+}
+
+struct Entry<T> {
+    key: (u64, Option<Vec<u8>>, u32, u64),
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Merges [`Message`]s or [`Event`]s from several nodes into a single
+/// timeline ordered by timestamp, tolerating clock skew between the
+/// nodes
+///
+/// Items are held back until an item with a timestamp more than
+/// `skew_ms` milliseconds later has been seen, so that items from
+/// nodes whose clocks lag behind by up to `skew_ms` are still put in
+/// order. Items with equal timestamps are ordered by node name and
+/// sequence number.
+///
+/// Items that arrive after later items have already been released
+/// are out of window; they are returned to the caller by
+/// [`push`](Self::push) and counted.
+pub struct TimelineMerger<T> {
+    skew_ms: u64,
+    heap: BinaryHeap<Reverse<Entry<T>>>,
+    max_seen: Option<u64>,
+    released: Option<u64>,
+    counter: u64,
+    out_of_window: u64,
+}
+
+impl<T: Timestamped> TimelineMerger<T> {
+    /// Constructs a merger that tolerates `skew_ms` milliseconds of
+    /// clock skew.
+    pub fn new(skew_ms: u64) -> Self {
+        TimelineMerger {
+            skew_ms,
+            heap: BinaryHeap::new(),
+            max_seen: None,
+            released: None,
+            counter: 0,
+            out_of_window: 0,
+        }
+    }
+
+    /// Adds an item. Fails if items with later timestamps have
+    /// already been released.
+    pub fn push(&mut self, item: T) -> Result<(), OutOfWindow<T>> {
+        let id = item.id();
+        if let Some(released) = self.released {
+            if id.timestamp < released {
+                self.out_of_window += 1;
+                return Err(OutOfWindow {
+                    item,
+                    behind_ms: released - id.timestamp,
+                });
+            }
+        }
+        self.max_seen = Some(self.max_seen.map_or(id.timestamp, |m| m.max(id.timestamp)));
+        let key = (
+            id.timestamp,
+            item.node().map(<[u8]>::to_vec),
+            id.sequence,
+            self.counter,
+        );
+        self.counter += 1;
+        self.heap.push(Reverse(Entry { key, item }));
+        Ok(())
+    }
+
+    /// Returns the next item in timestamp order if it can no longer
+    /// be preceded by items within the skew window.
+    pub fn pop(&mut self) -> Option<T> {
+        let watermark = self.max_seen?.checked_sub(self.skew_ms)?;
+        if self.heap.peek()?.0.key.0 > watermark {
+            return None;
+        }
+        self.pop_unchecked()
+    }
+
+    fn pop_unchecked(&mut self) -> Option<T> {
+        let Reverse(entry) = self.heap.pop()?;
+        self.released = Some(entry.key.0);
+        Some(entry.item)
+    }
+
+    /// Returns all remaining items in timestamp order, e.g. at the
+    /// end of input.
+    pub fn flush(&mut self) -> Vec<T> {
+        std::iter::from_fn(|| self.pop_unchecked()).collect()
+    }
+
+    /// Returns the number of items that are held back.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if no items are held back.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the number of items that have been rejected as out of
+    /// window.
+    pub fn out_of_window(&self) -> u64 {
+        self.out_of_window
+    }
+}
+
+impl<T> std::fmt::Debug for TimelineMerger<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TimelineMerger")
+            .field("skew_ms", &self.skew_ms)
+            .field("len", &self.heap.len())
+            .field("max_seen", &self.max_seen)
+            .field("released", &self.released)
+            .field("out_of_window", &self.out_of_window)
+            .finish()
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+use crate::*;
+
+/// Decides whether a line of terminal input should be redacted, see
+/// [`TtySessions::redact_with`]
+///
+/// `previous` is the preceding line of the same session, after
+/// redaction; `line` is the line in question.
+pub trait TtyRedactor {
+    fn redact(&mut self, previous: Option<&TtyLine>, line: &[u8]) -> bool;
+}
+
+impl<F: FnMut(Option<&TtyLine>, &[u8]) -> bool> TtyRedactor for F {
+    fn redact(&mut self, previous: Option<&TtyLine>, line: &[u8]) -> bool {
+        self(previous, line)
+    }
+}
+
+/// Commands that are commonly followed by a password prompt, see
+/// [`PasswordPromptRedactor`]
+pub const PASSWORD_PROMPT_COMMANDS: &[&str] = &["kinit", "login", "passwd", "su", "sudo"];
+
+/// Redacts the line following a command from
+/// [`PASSWORD_PROMPT_COMMANDS`], e.g. the password typed after
+/// `sudo -i`
+///
+/// With `pam_tty_audit log_passwd`, or for programs that read
+/// passwords without disabling echo, the password shows up as the
+/// next line of input. If no password was asked for, e.g. because
+/// _sudo(8)_ cached the credentials, the next command is redacted
+/// instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PasswordPromptRedactor;
+
+impl TtyRedactor for PasswordPromptRedactor {
+    fn redact(&mut self, previous: Option<&TtyLine>, _: &[u8]) -> bool {
+        let Some(prev) = previous.filter(|p| !p.redacted) else {
+            return false;
+        };
+        let cmd = prev
+            .text
+            .split(|c| c.is_ascii_whitespace())
+            .find(|w| !w.is_empty() && !w.contains(&b'='));
+        let Some(cmd) = cmd else {
+            return false;
+        };
+        let cmd = cmd.rsplit(|c| *c == b'/').next().unwrap_or(cmd);
+        PASSWORD_PROMPT_COMMANDS.iter().any(|c| c.as_bytes() == cmd)
+    }
+}
+
+/// A line of terminal input, reconstructed from `TTY` or `USER_TTY`
+/// records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtyLine {
+    /// Identifier of the record that completed the line
+    pub id: EventID,
+    /// `TTY` (kernel, via `pam_tty_audit`) or `USER_TTY` (userspace,
+    /// e.g. _bash(1)_ via readline)
+    pub ty: MessageType,
+    pub pid: Option<i64>,
+    /// Command name (`TTY` only)
+    pub comm: Option<Vec<u8>>,
+    /// Input with editing keys applied and control sequences
+    /// removed; empty if redacted
+    pub text: Vec<u8>,
+    pub redacted: bool,
+}
+
+/// Terminal input of a login session
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TtySession {
+    pub node: Option<Vec<u8>>,
+    /// Session ID (`ses`)
+    pub ses: Option<u32>,
+    /// Login user ID (`auid`)
+    pub auid: Option<u32>,
+    pub lines: Vec<TtyLine>,
+    /// Input after the last line break
+    pending: Vec<u8>,
+}
+
+impl TtySession {
+    /// Returns the input as text, one line per reconstructed line.
+    /// Redacted lines are replaced by `<redacted>`.
+    pub fn transcript(&self) -> Vec<u8> {
+        let mut out = vec![];
+        for line in &self.lines {
+            if line.redacted {
+                out.extend(b"<redacted>");
+            } else {
+                out.extend(&line.text);
+            }
+            out.push(b'\n');
+        }
+        out
+    }
+
+    /// Returns input that has not been terminated by a line break
+    /// yet.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+}
+
+/// Applies editing keys to raw terminal input: backspace and DEL
+/// remove the previous character, `^U` the line; escape sequences
+/// (e.g. cursor keys) and other control characters except tab are
+/// dropped.
+fn apply_editing(line: &mut Vec<u8>, input: &[u8]) {
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            0x08 | 0x7f => {
+                line.pop();
+            }
+            0x15 => line.clear(),
+            0x1b => {
+                // CSI (ESC [ … final) or SS3 (ESC O x); other escape
+                // sequences consist of two bytes.
+                match input.get(i + 1) {
+                    Some(b'[') => {
+                        i += 2;
+                        while i < input.len() && !(0x40..=0x7e).contains(&input[i]) {
+                            i += 1;
+                        }
+                    }
+                    Some(b'O') => i += 2,
+                    Some(_) => i += 1,
+                    None => {}
+                }
+            }
+            b'\t' => line.push(b'\t'),
+            c if c < 0x20 => {}
+            c => line.push(c),
+        }
+        i += 1;
+    }
+}
+
+/// Like [`AuparsePolicy`], but `TTY` records, which are not followed
+/// by `EOE`, form single-record events.
+#[derive(Debug, Clone, Copy, Default)]
+struct TtyPolicy;
+
+impl MultipartPolicy for TtyPolicy {
+    fn is_multipart(&self, ty: MessageType) -> bool {
+        ty != MessageType::TTY && AuparsePolicy.is_multipart(ty)
+    }
+
+    fn ends_event(&self, ty: MessageType) -> bool {
+        AuparsePolicy.ends_event(ty)
+    }
+}
+
+/// Correlates `TTY` and `USER_TTY` records by login session and
+/// reconstructs the typed input
+///
+/// Messages are grouped into events by an [`EventCoalescer`]; events
+/// can also be passed directly with
+/// [`push_event`](Self::push_event). Sessions are identified by node
+/// name and `ses`.
+///
+/// `TTY` records contain raw keystrokes that may end in the middle
+/// of a line; the rest of the line is taken from subsequent records
+/// of the same session. `USER_TTY` records contain complete lines.
+pub struct TtySessions<'a> {
+    coalescer: EventCoalescer<'a, TtyPolicy>,
+    sessions: BTreeMap<(Option<Vec<u8>>, Option<u32>), TtySession>,
+    redactors: Vec<Box<dyn TtyRedactor + Send + 'a>>,
+}
+
+impl std::fmt::Debug for TtySessions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TtySessions")
+            .field("coalescer", &self.coalescer)
+            .field("sessions", &self.sessions)
+            .field("redactors", &self.redactors.len())
+            .finish()
+    }
+}
+
+impl Default for TtySessions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TtySessions<'a> {
+    /// Constructs a correlator without redaction.
+    pub fn new() -> Self {
+        TtySessions {
+            coalescer: EventCoalescer::with_policy(TtyPolicy),
+            sessions: BTreeMap::new(),
+            redactors: vec![],
+        }
+    }
+
+    /// Adds a redaction hook. A line is redacted if any hook says
+    /// so.
+    pub fn redact_with<R: TtyRedactor + Send + 'a>(mut self, redactor: R) -> Self {
+        self.redactors.push(Box::new(redactor));
+        self
+    }
+
+    /// Adds a message.
+    pub fn push(&mut self, msg: Message<'a>) {
+        if let Some(ev) = self.coalescer.push(msg) {
+            self.push_event(&ev);
+        }
+    }
+
+    /// Adds the `TTY` and `USER_TTY` records of an event.
+    pub fn push_event(&mut self, ev: &Event) {
+        for (ty, body) in &ev.records {
+            if !matches!(*ty, MessageType::TTY | MessageType::USER_TTY) {
+                continue;
+            }
+            let Some(data) = body.get("data").and_then(value_bytes) else {
+                continue;
+            };
+            let num = |k: &str| match body.get(k) {
+                Some(Value::Number(n)) => n.as_i64(),
+                _ => None,
+            };
+            let id32 = |k: &str| {
+                num(k)
+                    .and_then(|n| u32::try_from(n).ok())
+                    .filter(|n| *n != UNSET_ID)
+            };
+            let ses = id32("ses");
+            let session = self
+                .sessions
+                .entry((ev.node.clone(), ses))
+                .or_insert_with(|| TtySession {
+                    node: ev.node.clone(),
+                    ses,
+                    ..TtySession::default()
+                });
+            if session.auid.is_none() {
+                session.auid = id32("auid");
+            }
+            let comm = body.get("comm").and_then(value_bytes).map(<[u8]>::to_vec);
+            let mut complete = vec![];
+            if *ty == MessageType::USER_TTY {
+                let mut line = vec![];
+                apply_editing(&mut line, data);
+                complete.push(line);
+            } else {
+                for chunk in data.split_inclusive(|c| *c == b'\r' || *c == b'\n') {
+                    apply_editing(&mut session.pending, chunk);
+                    if chunk.ends_with(b"\r") || chunk.ends_with(b"\n") {
+                        complete.push(std::mem::take(&mut session.pending));
+                    }
+                }
+            }
+            for text in complete {
+                // all hooks see every line, so that they can keep state
+                let mut redacted = false;
+                for h in self.redactors.iter_mut() {
+                    redacted |= h.redact(session.lines.last(), &text);
+                }
+                session.lines.push(TtyLine {
+                    id: ev.id,
+                    ty: *ty,
+                    pid: num("pid"),
+                    comm: comm.clone(),
+                    text: if redacted { vec![] } else { text },
+                    redacted,
+                });
+            }
+        }
+    }
+
+    /// Returns the session identified by `node` and `ses`.
+    pub fn get(&self, node: Option<&[u8]>, ses: Option<u32>) -> Option<&TtySession> {
+        self.sessions.get(&(node.map(<[u8]>::to_vec), ses))
+    }
+
+    /// Returns all sessions, ordered by node name and session ID.
+    pub fn sessions(&self) -> impl Iterator<Item = &TtySession> {
+        self.sessions.values()
+    }
+
+    /// Processes incomplete events and returns all sessions, ordered
+    /// by node name and session ID.
+    pub fn finish(mut self) -> Vec<TtySession> {
+        for ev in self.coalescer.flush() {
+            self.push_event(&ev);
+        }
+        self.sessions.into_values().collect()
+    }
+}
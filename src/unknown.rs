@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::constants::*;
+use crate::validate::{is_enriched_name, PARSER_FIELDS};
+use crate::*;
+
+/// Keys and message types encountered during parsing that are not
+/// part of the field and message dictionaries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownStats {
+    /// Number of occurrences of unknown keys, by name
+    pub keys: HashMap<Vec<u8>, u64>,
+    /// Number of occurrences of unknown message types
+    pub types: HashMap<MessageType, u64>,
+}
+
+/// Collector for [`UnknownStats`], shared between clones of a
+/// [`Parser`], see [`Parser::unknown`]
+#[derive(Debug, Default)]
+pub struct UnknownCollector {
+    stats: Mutex<UnknownStats>,
+}
+
+impl UnknownCollector {
+    /// Constructs an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records unknown keys and the message type of a message, if
+    /// unknown.
+    pub fn record(&self, ty: MessageType, body: &Body) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        if !EVENT_NAMES.contains_key(&ty.0) {
+            *stats.types.entry(ty).or_default() += 1;
+        }
+        for (k, v) in body {
+            record_key(&mut stats, k);
+            if let Value::Map(vs) = v {
+                for (k, _) in vs {
+                    record_key(&mut stats, k);
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the statistics collected so far.
+    pub fn snapshot(&self) -> UnknownStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the statistics collected so far and resets the
+    /// collector.
+    pub fn take(&self) -> UnknownStats {
+        std::mem::take(&mut self.stats.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+fn record_key(stats: &mut UnknownStats, k: &Key) {
+    let name = match k {
+        Key::Name(r) | Key::NameUID(r) | Key::NameGID(r) => r.as_slice(),
+        _ => return,
+    };
+    if FIELD_NAMES.contains(name)
+        || is_enriched_name(name)
+        || PARSER_FIELDS.iter().any(|f| f.as_bytes() == name)
+    {
+        return;
+    }
+    *stats.keys.entry(name.to_vec()).or_default() += 1;
+}
@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+use crate::constants::*;
+use crate::*;
+
+/// Fields common to records sent by userspace programs
+const USER_FIELDS: &[&str] = &["pid", "uid", "auid", "ses", "msg"];
+
+/// Fields that must be present in records of a given type
+const MANDATORY: &[(MessageType, &[&str])] = &[
+    (
+        MessageType::SYSCALL,
+        &[
+            "arch", "syscall", "pid", "auid", "uid", "ses", "comm", "exe",
+        ],
+    ),
+    (MessageType::EXECVE, &["argc"]),
+    (MessageType::PATH, &["item", "name", "nametype"]),
+    (MessageType::CWD, &["cwd"]),
+    (MessageType::PROCTITLE, &["proctitle"]),
+    (MessageType::SOCKADDR, &["saddr"]),
+    (MessageType::ANOM_ABEND, &["pid", "sig"]),
+    (MessageType::DAEMON_START, &["op", "pid", "res"]),
+    (MessageType::DAEMON_END, &["op", "pid", "res"]),
+    (MessageType::USER, USER_FIELDS),
+    (MessageType::USER_ACCT, USER_FIELDS),
+    (MessageType::USER_AUTH, USER_FIELDS),
+    (MessageType::USER_CMD, USER_FIELDS),
+    (MessageType::USER_START, USER_FIELDS),
+    (MessageType::USER_END, USER_FIELDS),
+    (MessageType::USER_LOGIN, USER_FIELDS),
+    (MessageType::USER_LOGOUT, USER_FIELDS),
+    (MessageType::CRED_ACQ, USER_FIELDS),
+    (MessageType::CRED_DISP, USER_FIELDS),
+    (MessageType::CRED_REFR, USER_FIELDS),
+];
+
+/// Keys produced by the parser for irregular records that are not
+/// part of the field dictionary
+pub(crate) const PARSER_FIELDS: &[&str] = &["denied", "granted", "netlabel"];
+
+/// Fields written by AppArmor that are not part of the field
+/// dictionary
+const APPARMOR_FIELDS: &[&str] = &[
+    "operation",
+    "info",
+    "error",
+    "profile",
+    "label",
+    "requested_mask",
+    "denied_mask",
+];
+
+/// Fields that are expected in records of a given type even though
+/// they are not part of the field dictionary
+const ALLOWED: &[(MessageType, &[&str])] = &[
+    (MessageType::AVC, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_AUDIT, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_ALLOWED, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_DENIED, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_HINT, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_STATUS, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_ERROR, APPARMOR_FIELDS),
+    (MessageType::APPARMOR_KILL, APPARMOR_FIELDS),
+];
+
+/// Fields whose values do not always match the field dictionary,
+/// e.g. `res=1` from the kernel vs. `res=success` from userspace
+const LOOSELY_TYPED: &[&str] = &["res"];
+
+/// A problem found by [`validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A field that is mandatory for the record type is missing.
+    Missing(&'static str),
+    /// The value does not match the type from the field dictionary,
+    /// e.g. a string where a number was expected.
+    Mistyped(Key),
+    /// The field is not part of the field dictionary.
+    Unexpected(Key),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Missing(k) => write!(f, "missing field {k}"),
+            Violation::Mistyped(k) => write!(f, "field {k} has unexpected type"),
+            Violation::Unexpected(k) => write!(f, "unexpected field {k}"),
+        }
+    }
+}
+
+/// Checks a parsed [`Body`] of a record of type `ty` against the
+/// field dictionary and a list of mandatory fields.
+///
+/// Records that have been injected through the user message API
+/// (e.g. using `auditctl -m`) are likely to lack some mandatory
+/// fields. Enriched (ALL-CAPS) keys are not checked, nor are fields
+/// that AppArmor adds to `AVC` and `APPARMOR_*` records.
+pub fn validate(ty: MessageType, body: &Body) -> Vec<Violation> {
+    let mut violations = vec![];
+    let allowed = ALLOWED
+        .iter()
+        .find(|(t, _)| *t == ty)
+        .map(|(_, fields)| *fields)
+        .unwrap_or_default();
+
+    if let Some((_, fields)) = MANDATORY.iter().find(|(t, _)| *t == ty) {
+        for field in fields.iter() {
+            if body.get(field).is_none() {
+                violations.push(Violation::Missing(field));
+            }
+        }
+    }
+
+    for (k, v) in body {
+        let name = match k {
+            Key::Name(r) | Key::NameUID(r) | Key::NameGID(r) => r.to_vec(),
+            Key::Common(c) => <&str>::from(*c).as_bytes().to_vec(),
+            _ => continue,
+        };
+        if is_enriched_name(&name) {
+            continue;
+        }
+        if !FIELD_NAMES.contains(name.as_slice()) {
+            if !PARSER_FIELDS
+                .iter()
+                .chain(allowed)
+                .any(|f| f.as_bytes() == name)
+            {
+                violations.push(Violation::Unexpected(k.clone()));
+            }
+            continue;
+        }
+        if LOOSELY_TYPED.iter().any(|f| f.as_bytes() == name) {
+            continue;
+        }
+        let numeric = matches!(
+            FIELD_TYPES.get(name.as_slice()),
+            Some(FieldType::NumericDec | FieldType::NumericHex | FieldType::NumericOct)
+        );
+        if numeric && !matches!(v, Value::Number(_) | Value::Empty) {
+            violations.push(Violation::Mistyped(k.clone()));
+        }
+    }
+
+    violations
+}
+
+pub(crate) fn is_enriched_name(name: &[u8]) -> bool {
+    name.iter().any(u8::is_ascii_uppercase) && !name.iter().any(u8::is_ascii_lowercase)
+}
+
+/// Location of invalid UTF-8 in a value, see
+/// [`Message::validate_utf8`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8Issue {
+    /// Index of the list element that contains invalid UTF-8, if the
+    /// value is a list
+    pub index: Option<usize>,
+    /// Number of bytes at the start of the string that are valid
+    /// UTF-8, see [`std::str::Utf8Error::valid_up_to`]
+    pub valid_up_to: usize,
+}
+
+impl Display for Utf8Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(i) => write!(
+                f,
+                "invalid UTF-8 in element {i} at offset {}",
+                self.valid_up_to
+            ),
+            None => write!(f, "invalid UTF-8 at offset {}", self.valid_up_to),
+        }
+    }
+}
+
+fn check_utf8(k: &Key, v: &Value, index: Option<usize>, issues: &mut Vec<(Key, Utf8Issue)>) {
+    let bytes = match v {
+        Value::Str(s, _) => Cow::Borrowed(*s),
+        Value::Owned(s) => Cow::Borrowed(s.as_slice()),
+        Value::Segments(segs) => Cow::Owned(segs.concat()),
+        Value::List(vs) | Value::StringifiedList(vs) => {
+            for (i, v) in vs.iter().enumerate() {
+                check_utf8(k, v, Some(i), issues);
+            }
+            return;
+        }
+        Value::Map(vs) => {
+            for (k, v) in vs {
+                check_utf8(k, v, None, issues);
+            }
+            return;
+        }
+        _ => return,
+    };
+    if let Err(e) = std::str::from_utf8(&bytes) {
+        let valid_up_to = e.valid_up_to();
+        issues.push((k.clone(), Utf8Issue { index, valid_up_to }));
+    }
+}
+
+impl Message<'_> {
+    /// Checks the message body, see [`validate`].
+    pub fn validate(&self) -> Vec<Violation> {
+        validate(self.ty, &self.body)
+    }
+
+    /// Returns the fields that contain strings that are not valid
+    /// UTF-8. Fields within `msg='…'` maps are reported with their
+    /// own key.
+    pub fn validate_utf8(&self) -> Vec<(Key, Utf8Issue)> {
+        let mut issues = vec![];
+        for (k, v) in &self.body {
+            check_utf8(k, v, None, &mut issues);
+        }
+        issues
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::convert::{Into, TryFrom};
 use std::fmt::{self, Debug, Display};
 use std::iter::Iterator;
@@ -26,6 +27,26 @@ pub enum Number {
     Oct(u64),
 }
 
+impl Number {
+    /// Returns the number as `u64`, regardless of radix, or `None`
+    /// for negative numbers.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Number::Hex(n) | Number::Oct(n) => Some(*n),
+            Number::Dec(n) => u64::try_from(*n).ok(),
+        }
+    }
+
+    /// Returns the number as `i64`, regardless of radix, or `None`
+    /// if it does not fit.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Number::Hex(n) | Number::Oct(n) => i64::try_from(*n).ok(),
+            Number::Dec(n) => Some(*n),
+        }
+    }
+}
+
 impl Debug for Number {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Num:<{self}>")
@@ -53,10 +74,53 @@ impl Serialize for Number {
     }
 }
 
+/// Representation of hexadecimal and octal [`Number`]s in serialized
+/// output
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// Strings with radix prefix, e.g. `"0x10"`, `"0o20"`
+    #[default]
+    Prefixed,
+    /// Plain unsigned integers, e.g. `16`
+    Integer,
+}
+
+/// Wrapper that serializes a [`Number`], [`Value`], or [`Body`] using
+/// a specific [`NumberFormat`], see e.g. [`Body::with_number_format`]
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy)]
+pub struct WithNumberFormat<'a, T: ?Sized> {
+    pub inner: &'a T,
+    pub format: NumberFormat,
+}
+
+#[cfg(feature = "serde")]
+impl Number {
+    /// Returns a wrapper that serializes the number using `format`.
+    pub fn with_number_format(&self, format: NumberFormat) -> WithNumberFormat<'_, Self> {
+        WithNumberFormat {
+            inner: self,
+            format,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithNumberFormat<'_, Number> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match (self.format, self.inner) {
+            (NumberFormat::Integer, Number::Hex(n) | Number::Oct(n)) => s.serialize_u64(*n),
+            (_, n) => n.serialize(s),
+        }
+    }
+}
+
 /// Representation of the value part of key/value pairs in [`Body`]
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum Value<'a> {
     /// Empty value.
+    #[default]
     Empty,
     /// A byte string.
     Str(&'a [u8], Quote),
@@ -72,18 +136,13 @@ pub enum Value<'a> {
     /// Non-contiguous byte string. Not produced by the parser.
     Segments(Vec<&'a [u8]>),
     StringifiedList(Vec<Value<'a>>),
-    /// Elements removed from ARGV lists. Not produced by the parser.
+    /// Elements removed from ARGV lists. Produced by the parser only
+    /// for values that exceed [`Parser::max_bytes`].
     Skipped((usize, usize)),
     /// A literal string. Not produced by the parser.
     Literal(&'static str),
 }
 
-impl Default for Value<'_> {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 impl Value<'_> {
     pub fn str_len(&self) -> usize {
         match self {
@@ -92,6 +151,81 @@ impl Value<'_> {
             _ => 0,
         }
     }
+
+    /// Returns the value as `u64` if it is a [`Number`] that fits,
+    /// see [`Number::as_u64`].
+    pub fn to_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => n.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes in string values, including those
+    /// contained in lists and maps.
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Value::Str(r, _) => r.len(),
+            Value::Owned(v) => v.len(),
+            Value::Segments(vr) => vr.iter().map(|r| r.len()).sum(),
+            Value::List(vs) | Value::StringifiedList(vs) => vs.iter().map(Value::byte_len).sum(),
+            Value::Map(vs) => vs.iter().map(|(_, v)| v.byte_len()).sum(),
+            Value::Literal(s) => s.len(),
+            Value::Empty | Value::Number(_) | Value::Skipped(_) => 0,
+        }
+    }
+
+    /// Returns the value with `'static` lifetime. Strings are copied
+    /// into [`Value::Owned`], also inside lists and maps.
+    pub(crate) fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Empty => Value::Empty,
+            Value::Str(s, _) => Value::Owned(s.to_vec()),
+            Value::Segments(segs) => Value::Owned(segs.concat()),
+            Value::Number(n) => Value::Number(n),
+            Value::List(vs) => Value::List(vs.into_iter().map(Value::into_owned).collect()),
+            Value::StringifiedList(vs) => {
+                Value::StringifiedList(vs.into_iter().map(Value::into_owned).collect())
+            }
+            Value::Map(vs) => {
+                Value::Map(vs.into_iter().map(|(k, v)| (k, v.into_owned())).collect())
+            }
+            Value::Owned(v) => Value::Owned(v),
+            Value::Skipped(n) => Value::Skipped(n),
+            Value::Literal(s) => Value::Literal(s),
+        }
+    }
+}
+
+/// Joins the elements of a [`Value::StringifiedList`] with spaces, as
+/// they appear in the log. Skipped elements are replaced by a
+/// `<<< Skipped: … >>>` marker. Fails for nested lists and maps,
+/// which the parser does not produce.
+pub(crate) fn join_stringified(vs: &[Value]) -> Result<Vec<u8>, &'static str> {
+    let mut buf = Vec::with_capacity(vs.iter().map(|v| v.byte_len() + 1).sum());
+    for (n, v) in vs.iter().enumerate() {
+        if n > 0 {
+            buf.push(b' ');
+        }
+        match v {
+            Value::Skipped((args, bytes)) => {
+                buf.extend(format!("<<< Skipped: args={args}, bytes={bytes} >>>").bytes())
+            }
+            Value::Number(n) => buf.extend(n.to_string().bytes()),
+            v => buf.extend(Vec::<u8>::try_from(v.clone())?),
+        }
+    }
+    Ok(buf)
+}
+
+/// Returns the bytes of a string value, used by typed views.
+pub(crate) fn value_bytes<'a>(v: &'a Value) -> Option<&'a [u8]> {
+    match v {
+        Value::Str(s, _) => Some(s),
+        Value::Owned(s) => Some(s),
+        Value::Literal(s) => Some(s.as_bytes()),
+        _ => None,
+    }
 }
 
 impl TryFrom<Value<'_>> for Vec<u8> {
@@ -258,23 +392,10 @@ impl Serialize for Value<'_> {
                 s.serialize_bytes(&buf)
             }
             Value::List(vs) => s.collect_seq(vs.iter()),
-            Value::StringifiedList(vs) => {
-                let mut buf: Vec<u8> = Vec::with_capacity(vs.len());
-                let mut first = true;
-                for v in vs {
-                    if first {
-                        first = false;
-                    } else {
-                        buf.push(b' ');
-                    }
-                    if let Value::Skipped((args, bytes)) = v {
-                        buf.extend(format!("<<< Skipped: args={args}, bytes={bytes} >>>").bytes());
-                    } else {
-                        buf.extend(v.clone().try_into().unwrap_or_else(|_| vec![b'x']));
-                    }
-                }
-                s.serialize_bytes(&buf)
-            }
+            Value::StringifiedList(vs) => match join_stringified(vs) {
+                Ok(buf) => s.serialize_bytes(&buf),
+                Err(_) => s.collect_seq(vs.iter()),
+            },
             Value::Number(n) => n.serialize(s),
             Value::Map(vs) => s.collect_map(vs.iter().cloned()),
             Value::Skipped((args, bytes)) => {
@@ -289,6 +410,59 @@ impl Serialize for Value<'_> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Value<'_> {
+    /// Returns a wrapper that serializes the value using `format`
+    /// for numbers, including those contained in lists and maps.
+    pub fn with_number_format(&self, format: NumberFormat) -> WithNumberFormat<'_, Self> {
+        WithNumberFormat {
+            inner: self,
+            format,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithNumberFormat<'_, Value<'_>> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let format = self.format;
+        match self.inner {
+            Value::Number(n) => n.with_number_format(format).serialize(s),
+            Value::List(vs) => s.collect_seq(vs.iter().map(|v| v.with_number_format(format))),
+            Value::Map(vs) => {
+                s.collect_map(vs.iter().map(|(k, v)| (k, v.with_number_format(format))))
+            }
+            v => v.serialize(s),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Value<'_> {
+    /// Returns a wrapper that serializes the value with keys of
+    /// contained maps named according to `naming`.
+    pub fn with_key_naming<'r>(&self, naming: KeyNaming<'r>) -> WithKeyNaming<'_, 'r, Self> {
+        WithKeyNaming {
+            inner: self,
+            naming,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WithKeyNaming<'_, '_, Value<'_>> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let naming = self.naming;
+        match self.inner {
+            Value::Map(vs) => s.collect_map(
+                vs.iter()
+                    .map(|(k, v)| (naming.name(k), v.with_key_naming(naming))),
+            ),
+            v => v.serialize(s),
+        }
+    }
+}
+
 impl PartialEq<str> for Value<'_> {
     fn eq(&self, other: &str) -> bool {
         self == other.as_bytes()
@@ -349,6 +523,42 @@ impl From<i64> for Value<'_> {
     }
 }
 
+/// Policy for representing byte strings in text-based formats that
+/// require valid UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteEncoding {
+    /// Invalid UTF-8 sequences are replaced with U+FFFD.
+    #[default]
+    Lossy,
+    /// Bytes outside of printable ASCII and `%` are percent-encoded
+    /// (`%XX`), making the conversion reversible.
+    Escaped,
+}
+
+impl ByteEncoding {
+    /// Converts `b` to a string according to the policy.
+    pub fn encode<'a>(&self, b: &'a [u8]) -> Cow<'a, str> {
+        match self {
+            ByteEncoding::Lossy => String::from_utf8_lossy(b),
+            ByteEncoding::Escaped => {
+                if b.iter().all(|c| (b' '..=b'~').contains(c) && *c != b'%') {
+                    // safety: printable ASCII
+                    return Cow::Borrowed(unsafe { str::from_utf8_unchecked(b) });
+                }
+                let mut s = String::with_capacity(b.len() * 3);
+                for c in b {
+                    if (b' '..=b'~').contains(c) && *c != b'%' {
+                        s.push(*c as char);
+                    } else {
+                        s.push_str(&format!("%{c:02x}"));
+                    }
+                }
+                Cow::Owned(s)
+            }
+        }
+    }
+}
+
 /// Helper type to enforce that serialize_bytes() is used in serialization.
 #[cfg(feature = "serde")]
 pub(crate) struct Bytes<'a>(pub &'a [u8]);